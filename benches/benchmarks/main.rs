@@ -1,17 +1,17 @@
 //#![allow(unused)]
-mod matrix_generator;
+mod plot;
 use std::{
-    fmt::Display,
     hint::black_box,
     rc::Rc,
+    sync::OnceLock,
     time::{Duration, Instant},
 };
 
-use matrix_generator::MatrixGenerator;
-use projeto::{HashMapMatrix, Matrix, Pair, TableMatrix, TreeMatrix};
+#[cfg(feature = "ahash")]
+use projeto::AHashMapMatrix;
+use projeto::{approx_eq, CscMatrix, CsrMatrix, EPSILON, HashMapMatrix, Matrix, MatrixGenerator, MatrixInfo, MatrixLayout, OpenAddrMatrix, Pair, SparseVec, SymmetricHashMapMatrix, TableMatrix, TreeMatrix, alloc::{self, AllocationProfile}};
 use rand::{Rng, seq::SliceRandom};
 use serde::{Deserialize, Serialize};
-use serde_json;
 use std::fs;
 
 fn mul<T: Matrix>(a: &T, b: &T) -> T {
@@ -32,18 +32,18 @@ fn get<T: Matrix>(a: T, pos: Pair) -> T {
     a
 }
 fn set<T: Matrix>(mut a: T, pos: Pair, value: f64) -> T {
-    black_box(a.set(pos, value));
+    a.set(black_box(pos), black_box(value));
     a
 }
 trait Cross<A: Clone>: Iterator<Item = A> {
-    fn cross<'a, B, IB>(self, ib: IB) -> impl Iterator<Item = (A, B)>
+    fn cross<B, IB>(self, ib: IB) -> impl Iterator<Item = (A, B)>
     where
         B: Clone,
         IB: Iterator<Item = B> + Clone;
 }
 
 impl<A: Clone, IA: Iterator<Item = A>> Cross<A> for IA {
-    fn cross<'a, B, IB>(self, ib: IB) -> impl Iterator<Item = (A, B)>
+    fn cross<B, IB>(self, ib: IB) -> impl Iterator<Item = (A, B)>
     where
         B: Clone,
         IB: Iterator<Item = B> + Clone,
@@ -51,38 +51,18 @@ impl<A: Clone, IA: Iterator<Item = A>> Cross<A> for IA {
         self.flat_map(move |a| ib.clone().map(move |b| (a.clone(), b.clone())))
     }
 }
-fn cross<'a, A, IA, B, IB>(ia: IA, ib: IB) -> impl Iterator<Item = (A, B)>
-where
-    A: Clone,
-    B: Clone,
-    IA: Iterator<Item = A>,
-    IB: Iterator<Item = B> + Clone,
-{
-    ia.flat_map(move |a| ib.clone().map(move |b| (a.clone(), b.clone())))
-}
-type MatrixGen<M> = Box<dyn Fn() -> M>;
-type BuilderMatrixGen<M> = Rc<dyn Fn(usize, usize) -> MatrixGen<M>>;
 type Operation<M> = Rc<dyn Fn(&M, &M) -> M>;
-
-struct Id<'a>(&'a str, u32, i32, &'a str, &'a str, &'a str);
-
-impl<'a> Display for Id<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{};{};{};{};{};{}",
-            self.0, self.1, self.2, self.3, self.4, self.5
-        )
-    }
-}
+type UnaryOperation<M> = Rc<dyn Fn(M, Pair, f64) -> M>;
 #[derive(Serialize, Deserialize)]
 struct Record {
     matrix_type: String,
     population: usize,
+    nnz: usize,
     occupation: usize,
     size: usize,
     operation: String,
     durations: Vec<Duration>,
+    peak_live: usize,
 }
 struct Records {
     records: Vec<Record>,
@@ -93,10 +73,11 @@ impl Records {
             / (record.durations.len() as f64);
 
         println!(
-            "{}, {}, {:0.2}, {}, {}, {}, {:?}",
+            "{}, {}, {:0.2}, {}, {}, {}, {}, {:?}, peak {} bytes",
             record.matrix_type,
             record.size,
             record.population,
+            record.nnz,
             record.operation,
             record.durations.len(),
             mean,
@@ -106,7 +87,8 @@ impl Records {
                 .map(|d| d.as_millis() as f64)
                 .map(|d| (d - mean).powf(2.0))
                 .sum::<f64>()
-                / (record.durations.len() as f64)
+                / (record.durations.len() as f64),
+            record.peak_live
         );
         self.records.push(record);
     }
@@ -119,6 +101,12 @@ struct ExponentialRecord {
     population: usize,
     operation: String,
     durations: Vec<Duration>,
+    /// Bytes alocados pela thread do benchmark em cada execuçao, na mesma ordem de `durations`
+    #[serde(default)]
+    alloc_bytes: Vec<usize>,
+    /// Pico de bytes vivos da thread do benchmark em cada execuçao (ver `alloc::thread_stats`)
+    #[serde(default)]
+    peak_bytes: Vec<usize>,
 }
 
 fn get_density(i : u32) -> Vec<f64> { 
@@ -133,11 +121,12 @@ fn get_density(i : u32) -> Vec<f64> {
     }
 }
 fn exponential_benchs<M: Matrix>(name: &str, records: &mut Vec<ExponentialRecord>, max_expoent : u32) {
+    let mut generator = matrix_generator();
     let bin_operations: [(&str, Operation<M>); 2] = [
         ("mul", Rc::new(|a, b| mul::<M>(a, b))),
         ("add", Rc::new(|a, b| add::<M>(a, b))),
     ];
-    let unary_operations: [(&str, Rc<dyn Fn(M, Pair, f64) -> M>); 4] = [
+    let unary_operations: [(&str, UnaryOperation<M>); 4] = [
         ("transpose", Rc::new(|a, _pos, _s| transposed::<M>(a))),
         ("muls", Rc::new(|a, _pos, s| muls::<M>(a, s))),
         ("get", Rc::new(|a, pos, _s| get::<M>(a, pos))),
@@ -156,13 +145,19 @@ fn exponential_benchs<M: Matrix>(name: &str, records: &mut Vec<ExponentialRecord
                 let mut j = 0;
                 let start_bench = Instant::now();
                 let mut durations = Vec::new();
+                let mut alloc_bytes = Vec::new();
+                let mut peak_bytes = Vec::new();
                 while (j < min_iterations || Instant::now()  - start_bench < max_duration) && j < max_iterations {
-                    let a = MatrixGenerator::uniform::<M>((len, len), population);
-                    let b = MatrixGenerator::uniform::<M>((len, len), population);
+                    let a = generator.uniform::<M>((len, len), population);
+                    let b = generator.uniform::<M>((len, len), population);
+                    alloc::thread_reset();
                     let start = Instant::now();
                     let c = black_box(op(black_box(&a), black_box(&b)));
                     let duration = Instant::now() - start;
+                    let stats = alloc::thread_stats();
                     drop(black_box(c));
+                    alloc_bytes.push(stats.alloc);
+                    peak_bytes.push(stats.peak_live);
                     j += 1;
                     durations.push(duration);
                 }
@@ -173,11 +168,12 @@ fn exponential_benchs<M: Matrix>(name: &str, records: &mut Vec<ExponentialRecord
                     i: i as usize,
                     population,
                     durations,
+                    alloc_bytes,
+                    peak_bytes,
                 });
             }
         }
     }
-    let mut rand = rand::rng();
     for (op_name, op) in unary_operations.iter() {
         for i in 1..=max_expoent {
             let len = 10usize.pow(i);
@@ -187,18 +183,24 @@ fn exponential_benchs<M: Matrix>(name: &str, records: &mut Vec<ExponentialRecord
                 let mut j = 0;
                 let start_bench = Instant::now();
                 let mut durations = Vec::new();
+                let mut alloc_bytes = Vec::new();
+                let mut peak_bytes = Vec::new();
                 while (j < min_iterations || Instant::now()  - start_bench < max_duration) && j < max_iterations {
-                    let a = MatrixGenerator::uniform::<M>((len, len), population);
+                    let a = generator.uniform::<M>((len, len), population);
                     let pos = (
-                        rand.random_range(0..len),
-                        rand.random_range(0..len),
+                        generator.rng().random_range(0..len),
+                        generator.rng().random_range(0..len),
                     );
-                    let scalar = rand.random_range(-10.0..10.0);
+                    let scalar = generator.rng().random_range(-10.0..10.0);
 
+                    alloc::thread_reset();
                     let start = Instant::now();
                     black_box(op(black_box(a), black_box(pos), black_box(scalar)));
                     let duration = Instant::now() - start;
-                    
+                    let stats = alloc::thread_stats();
+                    alloc_bytes.push(stats.alloc);
+                    peak_bytes.push(stats.peak_live);
+
                     j += 1;
                     durations.push(duration);
                 }
@@ -209,72 +211,157 @@ fn exponential_benchs<M: Matrix>(name: &str, records: &mut Vec<ExponentialRecord
                     i: i as usize,
                     population,
                     durations,
+                    alloc_bytes,
+                    peak_bytes,
                 });
             }
         }
     }
 }
 
-fn bench_matrix<M: Matrix>(name: &str, records: &mut Records, qt_samples: usize) {
-    let occupation_percentage: [i32; 4] = [1, 5, 10, 20]; //1] = [1]; //
+/// Operaçao binaria generica no tipo da matriz, para ser executada com todas as implementaçoes
+trait BinaryOperation {
+    fn name(&self) -> &'static str;
+    fn run<M: Matrix>(&self, a: &M, b: &M) -> M;
+}
 
-    let bin_operations: [(&str, Operation<M>); 2] = [
-        ("mul", Rc::new(|a, b| mul::<M>(a, b))),
-        ("add", Rc::new(|a, b| add::<M>(a, b))),
-    ];
-    let unary_operations: [(&str, Rc<dyn Fn(M, Pair, f64) -> M>); 4] = [
-        ("transpose", Rc::new(|a, _pos, _s| transposed::<M>(a))),
-        ("muls", Rc::new(|a, _pos, s| muls::<M>(a, s))),
-        ("get", Rc::new(|a, pos, _s| get::<M>(a, pos))),
-        ("set", Rc::new(|a, pos, s| set::<M>(a, pos,s))),
-    ];
+struct MulOperation;
+impl BinaryOperation for MulOperation {
+    fn name(&self) -> &'static str {
+        "mul"
+    }
+    fn run<M: Matrix>(&self, a: &M, b: &M) -> M {
+        mul(a, b)
+    }
+}
+
+struct AddOperation;
+impl BinaryOperation for AddOperation {
+    fn name(&self) -> &'static str {
+        "add"
+    }
+    fn run<M: Matrix>(&self, a: &M, b: &M) -> M {
+        add(a, b)
+    }
+}
+
+fn time_binary<M: Matrix, O: BinaryOperation>(op: &O, a: &M, b: &M) -> Duration {
+    time_binary_peak(op, a, b).0
+}
+
+/// Como `time_binary`, mas tambem retorna o pico de bytes vivos durante a operaçao (`Stats::peak_live`)
+fn time_binary_peak<M: Matrix, O: BinaryOperation>(op: &O, a: &M, b: &M) -> (Duration, usize) {
+    alloc::reset();
+    let start = Instant::now();
+    let c = black_box(op.run(black_box(a), black_box(b)));
+    let duration = Instant::now() - start;
+    let peak_live = alloc::stats().peak_live;
+    drop(c);
+    (duration, peak_live)
+}
+
+/// Executa `op` com as tres implementaçoes sobre as mesmas entradas, retornando o tempo e o pico de memoria de cada uma
+fn compare_implementations<O: BinaryOperation>(
+    op: &O,
+    a: &MatrixInfo,
+    b: &MatrixInfo,
+) -> Vec<(&'static str, (Duration, usize))> {
+    let hash_a = HashMapMatrix::from_info(a);
+    let hash_b = HashMapMatrix::from_info(b);
+    let tree_a: TreeMatrix = hash_a.clone_as();
+    let tree_b: TreeMatrix = hash_b.clone_as();
+    vec![
+        ("HashMapMatrix", time_binary_peak(op, &hash_a, &hash_b)),
+        ("TreeMatrix", time_binary_peak(op, &tree_a, &tree_b)),
+        (
+            "TableMatrix",
+            time_binary_peak(op, &TableMatrix::from_info(a), &TableMatrix::from_info(b)),
+        ),
+    ]
+}
+
+/// Verifica se as tres implementaçoes produzem o mesmo resultado para `op`, usando `TableMatrix` como referencia
+fn correctness_check_all<O: BinaryOperation>(op: &O, a: &MatrixInfo, b: &MatrixInfo) -> bool {
+    let expected = op.run(&TableMatrix::from_info(a), &TableMatrix::from_info(b));
+    approx_eq(
+        &expected,
+        &op.run(&HashMapMatrix::from_info(a), &HashMapMatrix::from_info(b)),
+        EPSILON,
+    ) && approx_eq(
+        &expected,
+        &op.run(&TreeMatrix::from_info(a), &TreeMatrix::from_info(b)),
+        EPSILON,
+    )
+}
+
+const OCCUPATION_PERCENTAGE: [i32; 4] = [1, 5, 10, 20];
+
+fn sample_lens(qt_samples: usize) -> Vec<usize> {
+    let mut generator = matrix_generator();
     let min = 10.0;
     let max = 500.0;
     let step = (max - min) / (qt_samples as f64);
-    let mut rand = rand::rng();
     let mut lens = (0..qt_samples)
         .rev()
         .map(|i| (min + step * i as f64) as usize)
         .collect::<Vec<_>>();
+    lens.shuffle(generator.rng());
+    lens
+}
 
-    lens.shuffle(&mut rand);
-
-    let iter = bin_operations
-        .iter()
-        .cross(occupation_percentage.iter().cloned())
-        .cross(lens.clone().into_iter())
-        .map(|((nop, occupation), i)| (i, occupation, nop));
-
-    for (len, occupation, nop) in iter {
-        let size = (len, len);
-        let density = (occupation as f64) / 100.0;
-        let population = (density * (len * len) as f64) as usize;
-        let (op_name, op) = nop;
-        let mut durations = Vec::new();
-        let a = MatrixGenerator::uniform::<M>(size, population);
-        let b = MatrixGenerator::uniform::<M>(size, population);
-        let start = Instant::now();
-        let c = black_box(op(black_box(&a), black_box(&b)));
-        let duration = Instant::now() - start;
-        durations.push(duration);
-        drop(c);
-
+fn record_comparison<O: BinaryOperation>(
+    records: &mut Records,
+    op: &O,
+    (a, b): (&MatrixInfo, &MatrixInfo),
+    occupation: i32,
+    population: usize,
+) {
+    let nnz = HashMapMatrix::from_info(a).nnz();
+    for (name, (duration, peak_live)) in compare_implementations(op, a, b) {
         records.add_record(Record {
             matrix_type: name.to_string(),
-            population: population,
+            population,
+            nnz,
             occupation: occupation as usize,
-            size: len,
-            operation: op_name.to_string(),
-            durations,
+            size: a.size.0,
+            operation: op.name().to_string(),
+            durations: vec![duration],
+            peak_live,
         });
     }
+}
 
+/// Executa as operaçoes binarias nas tres implementaçoes, com as mesmas matrizes de entrada para todas
+fn bench_binary_operations(records: &mut Records, lens: &[usize]) {
+    let mut generator = matrix_generator();
+    let iter = OCCUPATION_PERCENTAGE
+        .iter()
+        .cloned()
+        .cross(lens.iter().cloned());
+    for (occupation, len) in iter {
+        let size = (len, len);
+        let density = (occupation as f64) / 100.0;
+        let population = (density * (len * len) as f64) as usize;
+        let a = generator.uniform_info(size, population);
+        let b = generator.uniform_info(size, population);
+        record_comparison(records, &MulOperation, (&a, &b), occupation, population);
+        record_comparison(records, &AddOperation, (&a, &b), occupation, population);
+    }
+}
 
+fn bench_matrix<M: Matrix>(name: &str, records: &mut Records, lens: &[usize]) {
+    let mut generator = matrix_generator();
+    let unary_operations: [(&str, UnaryOperation<M>); 4] = [
+        ("transpose", Rc::new(|a, _pos, _s| transposed::<M>(a))),
+        ("muls", Rc::new(|a, _pos, s| muls::<M>(a, s))),
+        ("get", Rc::new(|a, pos, _s| get::<M>(a, pos))),
+        ("set", Rc::new(|a, pos, s| set::<M>(a, pos,s))),
+    ];
 
     let iter = unary_operations
         .iter()
-        .cross(occupation_percentage.iter().cloned())
-        .cross(lens.into_iter())
+        .cross(OCCUPATION_PERCENTAGE.iter().cloned())
+        .cross(lens.iter().cloned())
         .map(|((nop, occupation), i)| (i, occupation, nop));
     for (len, occupation, nop) in iter {
         let size = (len, len);
@@ -282,25 +369,30 @@ fn bench_matrix<M: Matrix>(name: &str, records: &mut Records, qt_samples: usize)
         let population = (density * (len * len) as f64) as usize;
         let (op_name, op) = nop;
         let mut durations = Vec::new();
-        let a = black_box(MatrixGenerator::uniform::<M>(size, population));
+        let a = black_box(generator.uniform::<M>(size, population));
+        let nnz = a.nnz();
         let pos = (
-            rand.random_range(0..size.0),
-            rand.random_range(0..size.1),
+            generator.rng().random_range(0..size.0),
+            generator.rng().random_range(0..size.1),
         );
-        let scalar = rand.random_range(-10.0..10.0);
+        let scalar = generator.rng().random_range(-10.0..10.0);
+        alloc::reset();
         let start = Instant::now();
         let c = black_box(op(a, black_box(pos), black_box(scalar)));
         let duration = Instant::now() - start;
+        let peak_live = alloc::stats().peak_live;
         drop(black_box(c));
         durations.push(duration);
 
         records.add_record(Record {
             matrix_type: name.to_string(),
-            population: population,
+            population,
+            nnz,
             occupation: occupation as usize,
             size: len,
             operation: op_name.to_string(),
             durations,
+            peak_live,
         });
     }
 }
@@ -310,25 +402,688 @@ pub fn b2(){
     exponential_benchs::<TableMatrix>("TableMatrix", &mut records, 3);
     exponential_benchs::<HashMapMatrix>("HashMapMatrix", &mut records, 6);
     exponential_benchs::<TreeMatrix>("TreeMatrix", &mut records, 6);
-    let file = fs::File::create(format!("b2.json")).unwrap();
+    for operation in ["mul", "add", "transpose", "muls", "get", "set"] {
+        fs::write(
+            format!("{}_scaling.svg", operation),
+            plot::plot_scaling_curve(&records, operation),
+        )
+        .unwrap();
+    }
+    let file = fs::File::create("b2.json").unwrap();
     serde_json::to_writer_pretty(file, &records).unwrap();
 }
 pub fn b1(){
+    let mut generator = matrix_generator();
     let mut records = Records {
         records: Vec::new(),
     };
-    bench_matrix::<HashMapMatrix>("HashMapMatrix", &mut records, 100);
-    bench_matrix::<TreeMatrix>("TreeMatrix", &mut records, 100);
-    bench_matrix::<TableMatrix>("TableMatrix", &mut records, 100);
-    let file = fs::File::create(format!("b1.json")).unwrap();
+    let a = generator.uniform_info((50, 50), 250);
+    let b = generator.uniform_info((50, 50), 250);
+    assert!(correctness_check_all(&MulOperation, &a, &b));
+    assert!(correctness_check_all(&AddOperation, &a, &b));
+    let t = generator.uniform_table((50, 40), 250);
+    assert!(TableMatrix::from_lapack_layout(t.data.clone(), t.size, MatrixLayout::RowMajor).unwrap() == t);
+    assert!(TableMatrix::from(Vec::from(t.clone())) == t);
+
+    let lens = sample_lens(100);
+    bench_binary_operations(&mut records, &lens);
+    bench_matrix::<HashMapMatrix>("HashMapMatrix", &mut records, &lens);
+    bench_matrix::<TreeMatrix>("TreeMatrix", &mut records, &lens);
+    bench_matrix::<TableMatrix>("TableMatrix", &mut records, &lens);
+    let file = fs::File::create("b1.json").unwrap();
     serde_json::to_writer_pretty(file, &records.records).unwrap();
 }
 
+/// Mede a memoria devolvida por `shrink_to_fit` depois de remover metade dos elementos de uma `HashMapMatrix`
+pub fn bench_shrink_to_fit() {
+    let mut generator = matrix_generator();
+    let len = 1000;
+    let population = len * len / 10;
+    let mut a = generator.uniform::<HashMapMatrix>((len, len), population);
+    for (pos, _) in a.to_info().values.iter().step_by(2) {
+        a.set(*pos, 0.0);
+    }
+    alloc::reset();
+    a.shrink_to_fit();
+    let stats = alloc::stats();
+    println!(
+        "shrink_to_fit, {}, {}, alloc {} bytes, dealloc {} bytes, diff {} bytes",
+        len, population, stats.alloc, stats.dealloc, stats.diff
+    );
+}
+
+/// Compara as alocaçoes de `shrink_size` com as de reconstruir a matriz a partir dos valores que sobram
+fn bench_shrink_size_matrix<M: Matrix>(name: &str, len: usize, population: usize) {
+    let mut generator = matrix_generator();
+    let info = generator.uniform_info((len, len), population);
+    let new_size = (len / 2, len / 2);
+    let mut a = M::from_info(&info);
+    let (removed, shrink) = alloc::with_tracking("shrink_size", || a.shrink_size(new_size).unwrap());
+    let a = M::from_info(&info);
+    let (rebuilt, rebuild) = alloc::with_tracking("rebuild", || {
+        let values = a.to_info().values.into_iter()
+            .filter(|((i, j), _)| *i < new_size.0 && *j < new_size.1)
+            .collect();
+        M::from_info(&MatrixInfo { size: new_size, values })
+    });
+    drop(black_box(rebuilt));
+    println!(
+        "shrink_size, {}, {}, {}, removed {}, shrink_size alloc {} bytes, rebuild alloc {} bytes",
+        name, len, population, removed, shrink.stats.alloc, rebuild.stats.alloc
+    );
+}
+
+/// Compara as alocaçoes de `to_info`, que clona os valores do mapa, com as de `into_info`, que os move com `Map::into_entries`
+fn bench_into_info_matrix<M: Matrix>(name: &str, len: usize, population: usize, into_info: fn(M) -> MatrixInfo) {
+    let mut generator = matrix_generator();
+    let info = generator.uniform_info((len, len), population);
+    let a = M::from_info(&info);
+    let (copied, to_info) = alloc::with_tracking("to_info", || a.to_info());
+    drop(black_box(copied));
+    let (moved, into) = alloc::with_tracking("into_info", || into_info(a));
+    drop(black_box(moved));
+    println!(
+        "into_info, {}, {}, {}, to_info alloc {} bytes peak {} bytes, into_info alloc {} bytes peak {} bytes",
+        name, len, population, to_info.stats.alloc, to_info.stats.peak_live, into.stats.alloc, into.stats.peak_live
+    );
+}
+
+pub fn bench_into_info() {
+    let len = 1000;
+    let population = len * len / 10;
+    bench_into_info_matrix::<HashMapMatrix>("HashMapMatrix", len, population, HashMapMatrix::into_info);
+    bench_into_info_matrix::<TreeMatrix>("TreeMatrix", len, population, TreeMatrix::into_info);
+}
+
+pub fn bench_shrink_size() {
+    let len = 1000;
+    let population = len * len / 10;
+    bench_shrink_size_matrix::<HashMapMatrix>("HashMapMatrix", len, population);
+    bench_shrink_size_matrix::<TreeMatrix>("TreeMatrix", len, population);
+    bench_shrink_size_matrix::<TableMatrix>("TableMatrix", len, population);
+}
+
+/// Compara `inner_product_matrix` com montar `A` e `B` com os vetores nas linhas e calcular `A * B^T` com `mul`
+fn bench_inner_product_matrix_type<M: Matrix>(name: &str, a_vecs: &[Vec<f64>], b_vecs: &[Vec<f64>]) {
+    let start = Instant::now();
+    drop(black_box(M::inner_product_matrix(black_box(a_vecs), black_box(b_vecs)).unwrap()));
+    let direct = Instant::now() - start;
+
+    let start = Instant::now();
+    let mut a = M::new((a_vecs.len(), a_vecs[0].len()));
+    let mut bt = M::new((b_vecs[0].len(), b_vecs.len()));
+    for (i, v) in a_vecs.iter().enumerate() {
+        for (j, value) in v.iter().enumerate() {
+            a.set((i, j), *value);
+        }
+    }
+    for (i, v) in b_vecs.iter().enumerate() {
+        for (j, value) in v.iter().enumerate() {
+            bt.set((j, i), *value);
+        }
+    }
+    drop(black_box(M::mul(&a, &bt)));
+    let with_mul = Instant::now() - start;
+    println!(
+        "inner_product_matrix, {}, {}, direct {:?}, mul {:?}",
+        name, a_vecs.len(), direct, with_mul
+    );
+}
+
+pub fn bench_inner_product_matrix() {
+    let mut generator = matrix_generator();
+    let len = 200;
+    let mut vectors = || -> Vec<Vec<f64>> {
+        (0..len)
+            .map(|_| (0..len).map(|_| generator.rng().random_range(-1.0..1.0)).collect())
+            .collect()
+    };
+    let a_vecs = vectors();
+    let b_vecs = vectors();
+    bench_inner_product_matrix_type::<HashMapMatrix>("HashMapMatrix", &a_vecs, &b_vecs);
+    bench_inner_product_matrix_type::<TreeMatrix>("TreeMatrix", &a_vecs, &b_vecs);
+    bench_inner_product_matrix_type::<TableMatrix>("TableMatrix", &a_vecs, &b_vecs);
+}
+
+/// Compara `Matrix::mul` generico com `TreeMatrix::mul_sorted` a 10% de densidade
+pub fn bench_tree_mul_sorted() {
+    let mut generator = matrix_generator();
+    for len in [100, 200, 400] {
+        let population = len * len / 10;
+        let a = generator.uniform::<TreeMatrix>((len, len), population);
+        let b = generator.uniform::<TreeMatrix>((len, len), population);
+        let generic = time_binary(&MulOperation, &a, &b);
+        let start = Instant::now();
+        drop(black_box(TreeMatrix::mul_sorted(black_box(&a), black_box(&b))));
+        let sorted = Instant::now() - start;
+        println!("tree_mul_sorted, {}, {}, mul {:?}, mul_sorted {:?}", len, population, generic, sorted);
+    }
+}
+
+/// Compara a norma de Frobenius calculada com `iter_nnz` (sem alocar) com a calculada a partir de `to_info`
+fn bench_norm_iter_nnz_matrix<M: Matrix>(name: &str, len: usize, population: usize) {
+    let mut generator = matrix_generator();
+    let a = generator.uniform::<M>((len, len), population);
+    let start = Instant::now();
+    let via_info = black_box(a.to_info().values.iter().map(|(_, v)| v * v).sum::<f64>().sqrt());
+    let info_time = Instant::now() - start;
+    let start = Instant::now();
+    let via_iter = black_box(a.norm_frobenius());
+    let iter_time = Instant::now() - start;
+    assert!((via_info - via_iter).abs() < EPSILON * via_info.max(1.0));
+    println!("norm_iter_nnz, {}, {}, {}, to_info {:?}, iter_nnz {:?}", name, len, population, info_time, iter_time);
+}
+
+pub fn bench_norm_iter_nnz() {
+    for len in [100, 1000, 3000] {
+        let population = len * len / 100;
+        bench_norm_iter_nnz_matrix::<HashMapMatrix>("HashMapMatrix", len, population);
+        bench_norm_iter_nnz_matrix::<TreeMatrix>("TreeMatrix", len, population);
+        bench_norm_iter_nnz_matrix::<CsrMatrix>("CsrMatrix", len, population);
+    }
+}
+
+/// Compara as alocaçoes de somar `b` 1000 vezes em um acumulador com `add` (uma matriz nova por soma)
+/// e com `+=` (`add_assign`, no lugar)
+fn bench_add_assign_matrix<M>(name: &str)
+where
+    for<'a> M: Matrix + std::ops::AddAssign<&'a M>,
+{
+    let mut generator = matrix_generator();
+    let iterations = 1000;
+    let b = generator.uniform::<M>((100, 100), 500);
+    let (_, with_add) = alloc::with_tracking("add", || {
+        let mut acc = M::new((100, 100));
+        for _ in 0..iterations {
+            acc = M::add(&acc, &b);
+        }
+        black_box(acc)
+    });
+    let (_, with_assign) = alloc::with_tracking("add_assign", || {
+        let mut acc = M::new((100, 100));
+        for _ in 0..iterations {
+            acc += &b;
+        }
+        black_box(acc)
+    });
+    println!(
+        "add_assign, {}, {} iterations, add {} bytes, add_assign {} bytes",
+        name, iterations, with_add.stats.alloc, with_assign.stats.alloc
+    );
+}
+
+pub fn bench_add_assign() {
+    bench_add_assign_matrix::<HashMapMatrix>("HashMapMatrix");
+    bench_add_assign_matrix::<TreeMatrix>("TreeMatrix");
+    bench_add_assign_matrix::<TableMatrix>("TableMatrix");
+}
+
+/// Tempo de `TableMatrix::mul` com matrizes densas 500x500, a media de algumas execuçoes
+pub fn bench_table_mul() {
+    let mut generator = matrix_generator();
+    let len = 500;
+    let runs = 5;
+    let a = generator.uniform_table((len, len), len * len);
+    let b = generator.uniform_table((len, len), len * len);
+    let total: Duration = (0..runs).map(|_| time_binary(&MulOperation, &a, &b)).sum();
+    println!("table_mul, {}, {:?}", len, total / runs);
+}
+
+/// Compara `TableMatrix::mul` com `TableMatrix::mul_strassen` em matrizes densas
+pub fn bench_strassen() {
+    let mut generator = matrix_generator();
+    for len in [128, 256, 512, 1024] {
+        let a = generator.uniform_table((len, len), len * len);
+        let b = generator.uniform_table((len, len), len * len);
+        let start = Instant::now();
+        let standard = black_box(TableMatrix::mul(&a, &b));
+        let mul_time = Instant::now() - start;
+        let start = Instant::now();
+        let fast = black_box(TableMatrix::mul_strassen(&a, &b));
+        let strassen_time = Instant::now() - start;
+        assert!(approx_eq(&standard, &fast, EPSILON * len as f64));
+        println!("strassen, {}, mul {:?}, mul_strassen {:?}", len, mul_time, strassen_time);
+    }
+}
+
+/// Compara `TableMatrix::mul` com `TableMatrix::mul_parallel` em matrizes densas
+/// Roda apenas com a feature `rayon`: `cargo bench --bench benchmarks --features rayon`
+#[cfg(feature = "rayon")]
+pub fn bench_parallel() {
+    let mut generator = matrix_generator();
+    for len in [200, 400, 600, 800, 1000] {
+        let a = generator.uniform_table((len, len), len * len);
+        let b = generator.uniform_table((len, len), len * len);
+        let start = Instant::now();
+        let sequential = black_box(TableMatrix::mul(&a, &b));
+        let mul_time = Instant::now() - start;
+        let start = Instant::now();
+        let parallel = black_box(TableMatrix::mul_parallel(&a, &b));
+        let parallel_time = Instant::now() - start;
+        assert!(approx_eq(&sequential, &parallel, EPSILON * len as f64));
+        println!("parallel, {}, mul {:?}, mul_parallel {:?}", len, mul_time, parallel_time);
+    }
+}
+
+/// Tempo medio de `mul_vec` (SpMV) em matrizes 1000x1000 com 1% de densidade
+fn bench_mul_vec_matrix<M: Matrix>(name: &str, info: &MatrixInfo, v: &[f64]) -> Vec<f64> {
+    let runs = 100;
+    let m = M::from_info(info);
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(m.mul_vec(black_box(v)));
+    }
+    println!("mul_vec, {}, {}, {}, {:?}", name, info.size.0, info.values.len(), (Instant::now() - start) / runs);
+    m.mul_vec(v)
+}
+
+pub fn bench_mul_vec() {
+    let mut generator = matrix_generator();
+    let len = 1000;
+    let info = generator.uniform_info((len, len), len * len / 100);
+    let v: Vec<f64> = (0..len).map(|_| generator.rng().random_range(-1.0..1.0)).collect();
+    let expected = bench_mul_vec_matrix::<HashMapMatrix>("HashMapMatrix", &info, &v);
+    for result in [
+        bench_mul_vec_matrix::<TreeMatrix>("TreeMatrix", &info, &v),
+        bench_mul_vec_matrix::<CsrMatrix>("CsrMatrix", &info, &v),
+        bench_mul_vec_matrix::<CscMatrix>("CscMatrix", &info, &v),
+    ] {
+        assert!(result.iter().zip(&expected).all(|(a, b)| (a - b).abs() < EPSILON));
+    }
+}
+
+/// Compara `mul_sparse_vec` com converter o vetor para denso e usar `mul_vec`, na mesma matriz do tipo `M`
+fn bench_mul_sparse_vec_matrix<M: Matrix>(name: &str, info: &MatrixInfo, v: &SparseVec) {
+    let runs = 10;
+    let m = M::from_info(info);
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(m.mul_sparse_vec(black_box(v)));
+    }
+    let sparse = (Instant::now() - start) / runs;
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(m.mul_vec(&black_box(v).to_dense(info.size.1)));
+    }
+    let dense = (Instant::now() - start) / runs;
+    println!("mul_sparse_vec, {}, {}, {}, {}, sparse {:?}, dense {:?}", name, info.size.0, info.values.len(), v.nnz(), sparse, dense);
+}
+
+/// Matriz 10000x10000 com 0,1% de densidade e vetor com 1% de densidade
+pub fn bench_mul_sparse_vec() {
+    let mut generator = matrix_generator();
+    let len = 10000;
+    let info = generator.uniform_info((len, len), len * len / 1000);
+    let mut v = SparseVec::new();
+    for _ in 0..len / 100 {
+        let i = generator.rng().random_range(0..len);
+        v.set(i, generator.rng().random_range(-1.0..1.0));
+    }
+    bench_mul_sparse_vec_matrix::<HashMapMatrix>("HashMapMatrix", &info, &v);
+    bench_mul_sparse_vec_matrix::<TreeMatrix>("TreeMatrix", &info, &v);
+    bench_mul_sparse_vec_matrix::<CsrMatrix>("CsrMatrix", &info, &v);
+    bench_mul_sparse_vec_matrix::<CscMatrix>("CscMatrix", &info, &v);
+}
+
+/// Tempo de `kron` de duas matrizes 100x100 com 1% de densidade, resultando em uma matriz 10000x10000
+fn bench_kron_matrix<M: Matrix>(name: &str, a: &MatrixInfo, b: &MatrixInfo) {
+    let (a, b) = (M::from_info(a), M::from_info(b));
+    let start = Instant::now();
+    let c = black_box(M::kron(&a, &b));
+    println!("kron, {}, {}, nnz {}, {:?}", name, a.size().0, c.nnz(), Instant::now() - start);
+}
+
+pub fn bench_kron() {
+    let mut generator = matrix_generator();
+    let len = 100;
+    let a = generator.uniform_info((len, len), len * len / 100);
+    let b = generator.uniform_info((len, len), len * len / 100);
+    bench_kron_matrix::<HashMapMatrix>("HashMapMatrix", &a, &b);
+    bench_kron_matrix::<TreeMatrix>("TreeMatrix", &a, &b);
+    bench_kron_matrix::<CsrMatrix>("CsrMatrix", &a, &b);
+}
+
+/// Tempo de 100 passagens de `get` pelas posiçoes guardadas de uma matriz transposta, antes e depois de `realize`
+fn bench_realize_matrix<M: Matrix>(name: &str, len: usize, population: usize, realize: fn(&mut M)) {
+    let mut generator = matrix_generator();
+    let passes = 100;
+    let info = generator.uniform_info((len, len), population);
+    let positions: Vec<Pair> = info.values.iter().map(|((row, col), _)| (*col, *row)).collect();
+    let mut m = M::from_info(&info).transposed();
+    let sum_gets = |m: &M| {
+        let start = Instant::now();
+        let mut sum = 0.0;
+        for _ in 0..passes {
+            for pos in positions.iter() {
+                sum += m.get(*pos);
+            }
+        }
+        black_box(sum);
+        Instant::now() - start
+    };
+    let flagged = sum_gets(&m);
+    realize(&mut m);
+    let realized = sum_gets(&m);
+    println!("realize, {}, {}, {}, transposed {:?}, realized {:?}", name, len, population, flagged, realized);
+}
+
+pub fn bench_realize() {
+    for len in [1000, 3000] {
+        let population = len * len / 100;
+        bench_realize_matrix::<HashMapMatrix>("HashMapMatrix", len, population, HashMapMatrix::realize);
+        bench_realize_matrix::<TreeMatrix>("TreeMatrix", len, population, TreeMatrix::realize);
+    }
+}
+
+/// Compara `memory_estimate` com a memoria realmente alocada ao construir a matriz, imprimindo `estimado / real`
+pub fn bench_memory_estimate() {
+    let mut generator = matrix_generator();
+    for (len, population) in [(100, 100), (100, 1000), (1000, 10000), (1000, 100000)] {
+        let info = generator.uniform_info((len, len), population);
+        let (a, hash) = alloc::with_tracking("HashMapMatrix", || HashMapMatrix::from_info(&info));
+        let (b, tree) = alloc::with_tracking("TreeMatrix", || TreeMatrix::from_info(&info));
+        let (c, table) = alloc::with_tracking("TableMatrix", || TableMatrix::from_info(&info));
+        let estimates = [a.memory_estimate(), b.memory_estimate(), c.memory_estimate()];
+        for (stats, estimate) in [hash, tree, table].iter().zip(estimates) {
+            // a struct fica na pilha, entao o valor real inclui apenas o heap
+            println!(
+                "memory_estimate, {}, {}, {}, estimated {} bytes, actual {} bytes, ratio {:.3}",
+                stats.label, len, population, estimate, stats.stats.diff,
+                estimate as f64 / stats.stats.diff as f64
+            );
+        }
+    }
+}
+
+/// Compara a memoria de uma matriz simetrica guardada inteira (`HashMapMatrix`) e so com o triangulo superior
+/// (`SymmetricHashMapMatrix`), com os valores sorteados do triangulo superior espelhados na posiçao transposta
+pub fn bench_symmetric() {
+    let mut generator = matrix_generator();
+    for (len, population) in [(1000, 10000), (1000, 100000)] {
+        let mut info = generator.uniform_info((len, len), population);
+        info.values.retain(|((i, j), _)| i <= j);
+        let mirrored: Vec<(Pair, f64)> = info.values.iter()
+            .filter(|((i, j), _)| i != j)
+            .map(|((i, j), value)| ((*j, *i), *value))
+            .collect();
+        info.values.extend(mirrored);
+        let full = HashMapMatrix::from_info(&info);
+        let upper = SymmetricHashMapMatrix::from_info(&info);
+        println!(
+            "symmetric, {}, {}, full {} bytes, upper {} bytes, ratio {:.3}",
+            len, population, full.memory_estimate(), upper.memory_estimate(),
+            upper.memory_estimate() as f64 / full.memory_estimate() as f64
+        );
+    }
+}
+
+/// Caracteriza as matrizes dos geradores pela largura de banda (`lower_bandwidth`, `upper_bandwidth` e `bandwidth`)
+/// e pelo tempo para calcula-la em `HashMapMatrix` (percorrendo os nao nulos) e `TableMatrix` (percorrendo a tabela)
+pub fn bench_bandwidth() {
+    let mut generator = matrix_generator();
+    let len = 1000;
+    let infos = [
+        ("uniform", generator.uniform_info((len, len), 10000)),
+        ("symmetric", generator.symmetric_info(len, 10000)),
+        ("banded_5", generator.banded_info(len, 5)),
+        ("banded_50", generator.banded_info(len, 50)),
+    ];
+    for (kind, info) in &infos {
+        let hash = HashMapMatrix::from_info(info);
+        let table = TableMatrix::from_info(info);
+        let start = Instant::now();
+        let hash_bw = black_box(hash.bandwidth());
+        let hash_time = start.elapsed();
+        let start = Instant::now();
+        let table_bw = black_box(table.bandwidth());
+        let table_time = start.elapsed();
+        assert_eq!(hash_bw, table_bw);
+        println!(
+            "bandwidth, {}, {}, nnz {}, lower {}, upper {}, bandwidth {}, hash {:?}, table {:?}",
+            kind, len, info.values.len(), hash.lower_bandwidth(), hash.upper_bandwidth(), hash_bw,
+            hash_time, table_time
+        );
+    }
+}
+
+/// Compara a tabela de enderecamento aberto (`OpenAddrMatrix`) com o `HashMap` da biblioteca padrao (`HashMapMatrix`)
+pub fn bench_open_addr() {
+    let mut generator = matrix_generator();
+    for len in [100, 300, 1000] {
+        let population = len * len / 20;
+        let a_info = generator.uniform_info((len, len), population);
+        let b_info = generator.uniform_info((len, len), population);
+        let positions: Vec<Pair> = (0..10000)
+            .map(|_| (generator.rng().random_range(0..len), generator.rng().random_range(0..len)))
+            .collect();
+        let (a, b) = (HashMapMatrix::from_info(&a_info), HashMapMatrix::from_info(&b_info));
+        let (oa, ob) = (OpenAddrMatrix::from_info(&a_info), OpenAddrMatrix::from_info(&b_info));
+        println!(
+            "open_addr, {}, {}, mul {:?} vs {:?}, add {:?} vs {:?}, get {:?} vs {:?}",
+            len,
+            population,
+            time_binary(&MulOperation, &oa, &ob),
+            time_binary(&MulOperation, &a, &b),
+            time_binary(&AddOperation, &oa, &ob),
+            time_binary(&AddOperation, &a, &b),
+            time_gets(&oa, &positions),
+            time_gets(&a, &positions),
+        );
+    }
+}
+
+/// Compara o `HashMap` com SipHash (`HashMapMatrix`) e com AHash (`AHashMapMatrix`)
+/// Roda apenas com a feature `ahash`: `cargo bench --bench benchmarks --features ahash`
+#[cfg(feature = "ahash")]
+pub fn bench_ahash() {
+    let mut generator = matrix_generator();
+    for len in [100, 300, 1000] {
+        let population = len * len / 20;
+        let a_info = generator.uniform_info((len, len), population);
+        let b_info = generator.uniform_info((len, len), population);
+        let positions: Vec<Pair> = (0..10000)
+            .map(|_| (generator.rng().random_range(0..len), generator.rng().random_range(0..len)))
+            .collect();
+        let (a, b) = (HashMapMatrix::from_info(&a_info), HashMapMatrix::from_info(&b_info));
+        let (aa, ab) = (AHashMapMatrix::from_info(&a_info), AHashMapMatrix::from_info(&b_info));
+        println!(
+            "ahash, {}, {}, mul {:?} vs {:?}, add {:?} vs {:?}, get {:?} vs {:?}, set {:?} vs {:?}",
+            len,
+            population,
+            time_binary(&MulOperation, &aa, &ab),
+            time_binary(&MulOperation, &a, &b),
+            time_binary(&AddOperation, &aa, &ab),
+            time_binary(&AddOperation, &a, &b),
+            time_gets(&aa, &positions),
+            time_gets(&a, &positions),
+            time_sets(aa, &positions),
+            time_sets(a, &positions),
+        );
+    }
+}
+
+/// Compara `mul` da `CsrMatrix` com o da `HashMapMatrix` em matrizes 500x500 com 5% e 10% de densidade
+pub fn bench_csr_mul() {
+    let mut generator = matrix_generator();
+    let len = 500;
+    for occupation in [5, 10] {
+        let population = len * len * occupation / 100;
+        let a = generator.uniform_info((len, len), population);
+        let b = generator.uniform_info((len, len), population);
+        let csr = time_binary(&MulOperation, &CsrMatrix::from_info(&a), &CsrMatrix::from_info(&b));
+        let hash = time_binary(&MulOperation, &HashMapMatrix::from_info(&a), &HashMapMatrix::from_info(&b));
+        println!("csr_mul, {}, {}%, CsrMatrix {:?}, HashMapMatrix {:?}", len, occupation, csr, hash);
+    }
+}
+
+fn time_gets<M: Matrix>(a: &M, positions: &[Pair]) -> Duration {
+    let start = Instant::now();
+    for pos in positions {
+        black_box(a.get(black_box(*pos)));
+    }
+    Instant::now() - start
+}
+
+#[cfg(feature = "ahash")]
+fn time_sets<M: Matrix>(mut a: M, positions: &[Pair]) -> Duration {
+    let start = Instant::now();
+    for pos in positions {
+        a.set(black_box(*pos), 1.0);
+    }
+    black_box(a);
+    Instant::now() - start
+}
+
+/// Linhas esparsas, com os pares `(coluna, valor)` de cada linha
+type SparseRows = Vec<Vec<(usize, f64)>>;
+
+/// Compara o tempo e as alocaçoes de `from_rows`, com as linhas esparsas ja montadas, com os de `from_info`
+fn bench_from_rows_matrix<M: Matrix>(name: &str, len: usize, population: usize, from_rows: fn(SparseRows, usize) -> M) {
+    let mut generator = matrix_generator();
+    let info = generator.uniform_info((len, len), population);
+    let mut rows = vec![Vec::new(); len];
+    for ((i, j), value) in info.values.iter() {
+        rows[*i].push((*j, *value));
+    }
+    let start = Instant::now();
+    let (a, info_alloc) = alloc::with_tracking("from_info", || M::from_info(&info));
+    let info_time = Instant::now() - start;
+    drop(black_box(a));
+    let start = Instant::now();
+    let (b, rows_alloc) = alloc::with_tracking("from_rows", || from_rows(rows, len));
+    let rows_time = Instant::now() - start;
+    drop(black_box(b));
+    println!(
+        "from_rows, {}, {}, {}, from_info {:?} alloc {} bytes, from_rows {:?} alloc {} bytes",
+        name, len, population, info_time, info_alloc.stats.alloc, rows_time, rows_alloc.stats.alloc
+    );
+}
+
+pub fn bench_from_rows() {
+    let len = 1000;
+    let population = len * len / 100;
+    bench_from_rows_matrix::<HashMapMatrix>("HashMapMatrix", len, population, HashMapMatrix::from_rows);
+    bench_from_rows_matrix::<TreeMatrix>("TreeMatrix", len, population, TreeMatrix::from_rows);
+}
+
+/// Compara 1000 chamadas de `get` em uma `HashMapMatrix` fragmentada (depois de remover 90% dos valores)
+/// com as mesmas chamadas depois de `consolidate`
+pub fn bench_consolidate() {
+    let mut generator = matrix_generator();
+    let len = 1000;
+    let population = len * len / 10;
+    let mut a = generator.uniform::<HashMapMatrix>((len, len), population);
+    for (pos, _) in a.to_info().values.iter().skip(population / 10) {
+        a.set(*pos, 0.0);
+    }
+    let positions: Vec<Pair> = (0..1000)
+        .map(|_| (generator.rng().random_range(0..len), generator.rng().random_range(0..len)))
+        .collect();
+    let fragmented = time_gets(&a, &positions);
+    a.consolidate();
+    let consolidated = time_gets(&a, &positions);
+    println!(
+        "consolidate, {}, {}, fragmented {:?}, consolidated {:?}",
+        len, population, fragmented, consolidated
+    );
+}
+
+/// Soma `a` com `-(1 - 1e-12) a`, deixando apenas residuos da ordem de `1e-11`, e compara `nnz` (o numero de
+/// valores guardados no mapa) antes e depois de `compress`
+pub fn bench_compress() {
+    let mut generator = matrix_generator();
+    let len = 1000;
+    let population = len * len / 10;
+    let a = generator.uniform::<HashMapMatrix>((len, len), population);
+    let mut c = HashMapMatrix::add(&a, &HashMapMatrix::muls(&a, -(1.0 - 1e-12)));
+    let nnz = c.nnz();
+    let start = Instant::now();
+    c.compress();
+    let elapsed = Instant::now() - start;
+    println!(
+        "compress, {}, {}, nnz before {}, nnz after {}, {:?}",
+        len, population, nnz, c.nnz(), elapsed
+    );
+}
+
+/// Mede as alocaçoes de cada operaçao em uma matriz do tipo `M`, com as mesmas entradas para todos os tipos
+fn profile_matrix<M: Matrix>(name: &str, profile: &mut AllocationProfile, a: &MatrixInfo, b: &MatrixInfo) {
+    let a = M::from_info(a);
+    let b = M::from_info(b);
+    drop(black_box(profile.add_measurement(&format!("{name}/mul"), || M::mul(&a, &b))));
+    drop(black_box(profile.add_measurement(&format!("{name}/add"), || M::add(&a, &b))));
+    drop(black_box(profile.add_measurement(&format!("{name}/muls"), || M::muls(&a, 2.0))));
+    drop(black_box(profile.add_measurement(&format!("{name}/transpose"), || a.transposed())));
+}
+
+/// Custo de alocaçao de cada operaçao por tipo de matriz, salvo em b3.json com rotulos "tipo/operaçao"
+pub fn b3() {
+    let mut generator = matrix_generator();
+    let mut profile = AllocationProfile::new();
+    let size = (200, 200);
+    let population = size.0 * size.1 / 10;
+    let a = generator.uniform_info(size, population);
+    let b = generator.uniform_info(size, population);
+    profile_matrix::<HashMapMatrix>("HashMapMatrix", &mut profile, &a, &b);
+    profile_matrix::<TreeMatrix>("TreeMatrix", &mut profile, &a, &b);
+    profile_matrix::<TableMatrix>("TableMatrix", &mut profile, &a, &b);
+    for sample in &profile.samples {
+        println!(
+            "{}, alloc {} bytes, dealloc {} bytes, diff {} bytes",
+            sample.label, sample.stats.alloc, sample.stats.dealloc, sample.stats.diff
+        );
+    }
+    let file = fs::File::create("b3.json").unwrap();
+    serde_json::to_writer_pretty(file, &profile.samples).unwrap();
+}
+
 pub fn criterion_benchmark() {
     b1();
     b2();
+    b3();
+    bench_shrink_to_fit();
+    bench_shrink_size();
+    bench_into_info();
+    bench_from_rows();
+    bench_consolidate();
+    bench_inner_product_matrix();
+    bench_tree_mul_sorted();
+    bench_memory_estimate();
+    bench_open_addr();
+    bench_csr_mul();
+    bench_norm_iter_nnz();
+    bench_add_assign();
+    bench_table_mul();
+    bench_realize();
+    bench_strassen();
+    bench_mul_vec();
+    bench_kron();
+    bench_compress();
+    bench_symmetric();
+    bench_bandwidth();
+    bench_mul_sparse_vec();
+    #[cfg(feature = "rayon")]
+    bench_parallel();
+    #[cfg(feature = "ahash")]
+    bench_ahash();
+}
+
+/// Semente dos geradores de matrizes, lida de `--seed <n>` (`cargo bench --bench benchmarks -- --seed 42`)
+/// ou sorteada; é impressa no inicio da execuçao para que a execuçao possa ser repetida
+static SEED: OnceLock<u64> = OnceLock::new();
+
+/// Gerador de matrizes com a semente da execuçao; cada chamada recomeça a mesma sequencia
+fn matrix_generator() -> MatrixGenerator {
+    MatrixGenerator::with_seed(*SEED.get().expect("SEED is set in main"))
 }
 
 pub fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let seed = match args.iter().position(|arg| arg == "--seed") {
+        Some(i) => args.get(i + 1).and_then(|value| value.parse().ok()).expect("--seed expects an integer"),
+        None => rand::rng().random(),
+    };
+    println!("seed, {}", seed);
+    SEED.set(seed).unwrap();
     criterion_benchmark();
 }
@@ -0,0 +1,74 @@
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use crate::ExponentialRecord;
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 400.0;
+const MARGIN: f64 = 50.0;
+const COLORS: [&str; 4] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd"];
+
+fn mean_nanos(durations: &[Duration]) -> f64 {
+    durations.iter().sum::<Duration>().as_nanos() as f64 / durations.len() as f64
+}
+
+/// Gera um grafico de linhas em SVG com o tempo da operaçao `operation` para cada tipo de matriz
+///
+/// O eixo x é o expoente `i` do tamanho da matriz (10^i) e o eixo y é `log10` do tempo medio em nanosegundos.
+/// Para cada `i` é usado o registro de maior populaçao.
+pub fn plot_scaling_curve(records: &[ExponentialRecord], operation: &str) -> String {
+    // tipo de matriz -> i -> (populaçao, log10 do tempo medio)
+    let mut series: BTreeMap<&str, BTreeMap<usize, (usize, f64)>> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.operation == operation && !r.durations.is_empty()) {
+        let point = (record.population, mean_nanos(&record.durations).max(1.0).log10());
+        let points = series.entry(&record.matrix_type).or_default();
+        let current = points.entry(record.i).or_insert(point);
+        if point.0 > current.0 {
+            *current = point;
+        }
+    }
+
+    let points = series.values().flat_map(|points| points.iter());
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (usize::MAX, 0, f64::INFINITY, f64::NEG_INFINITY);
+    for (i, (_, y)) in points {
+        min_x = min_x.min(*i);
+        max_x = max_x.max(*i);
+        min_y = min_y.min(*y);
+        max_y = max_y.max(*y);
+    }
+    let (min_y, max_y) = (min_y.floor(), max_y.ceil().max(min_y.floor() + 1.0));
+    let x_range = (max_x.saturating_sub(min_x)).max(1) as f64;
+    let to_x = |i: usize| MARGIN + (i.saturating_sub(min_x)) as f64 / x_range * (WIDTH - 2.0 * MARGIN);
+    let to_y = |y: f64| HEIGHT - MARGIN - (y - min_y) / (max_y - min_y) * (HEIGHT - 2.0 * MARGIN);
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="12">"#).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(svg, r#"<text x="{}" y="20" text-anchor="middle">{}</text>"#, WIDTH / 2.0, operation).unwrap();
+    writeln!(
+        svg,
+        r#"<polyline points="{m},{m} {m},{b} {r},{b}" fill="none" stroke="black"/>"#,
+        m = MARGIN,
+        b = HEIGHT - MARGIN,
+        r = WIDTH - MARGIN
+    )
+    .unwrap();
+    if !series.is_empty() {
+        for i in min_x..=max_x {
+            writeln!(svg, r#"<text x="{:.1}" y="{}" text-anchor="middle">10^{}</text>"#, to_x(i), HEIGHT - MARGIN + 18.0, i).unwrap();
+        }
+        for y in (min_y as i32)..=(max_y as i32) {
+            writeln!(svg, r#"<text x="{}" y="{:.1}" text-anchor="end">1e{}ns</text>"#, MARGIN - 6.0, to_y(y as f64) + 4.0, y).unwrap();
+        }
+    }
+    for (k, (name, points)) in series.iter().enumerate() {
+        let color = COLORS[k % COLORS.len()];
+        let coords: Vec<String> = points
+            .iter()
+            .map(|(i, (_, y))| format!("{:.1},{:.1}", to_x(*i), to_y(*y)))
+            .collect();
+        writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#, coords.join(" "), color).unwrap();
+        writeln!(svg, r#"<text x="{}" y="{}" fill="{}">{}</text>"#, WIDTH - MARGIN - 110.0, MARGIN + 16.0 * k as f64, color, name).unwrap();
+    }
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
@@ -1,23 +1,69 @@
 /// https://www.ntietz.com/blog/rust-hashmap-overhead/
 use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
 static ALLOC: AtomicUsize = AtomicUsize::new(0);
 static DEALLOC: AtomicUsize = AtomicUsize::new(0);
+/// Bytes alocados e ainda nao liberados, desde o inicio do programa
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+/// Maior valor de `LIVE` desde o ultimo `reset_peak`
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// Valor de `LIVE` no ultimo `reset_peak`
+static BASELINE: AtomicUsize = AtomicUsize::new(0);
+
+// Contadores por thread, com o mesmo significado dos globais, mas so com as alocaçoes e liberaçoes feitas
+// pela propria thread: medem uma operaçao sem interferencia de outras threads que alocam ao mesmo tempo.
+// Os inicializadores `const` nao alocam, entao podem ser usados de dentro do alocador.
+thread_local! {
+    static TL_ALLOC: Cell<usize> = const { Cell::new(0) };
+    static TL_DEALLOC: Cell<usize> = const { Cell::new(0) };
+    static TL_LIVE: Cell<usize> = const { Cell::new(0) };
+    static TL_PEAK: Cell<usize> = const { Cell::new(0) };
+    static TL_BASELINE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Soma `value` ao contador da thread, ignorando chamadas feitas depois que a thread liberou os seus dados locais
+fn thread_add(counter: &'static std::thread::LocalKey<Cell<usize>>, value: usize) -> usize {
+    counter.try_with(|c| {
+        c.set(c.get() + value);
+        c.get()
+    }).unwrap_or(0)
+}
 
 pub struct TrackingAllocator;
 
 pub fn record_alloc(layout: Layout) {
     ALLOC.fetch_add(layout.size(), Ordering::SeqCst);
+    let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+    PEAK.fetch_max(live, Ordering::SeqCst);
+    thread_add(&TL_ALLOC, layout.size());
+    let thread_live = thread_add(&TL_LIVE, layout.size());
+    let _ = TL_PEAK.try_with(|peak| peak.set(peak.get().max(thread_live)));
 }
 
 pub fn record_dealloc(layout: Layout) {
     DEALLOC.fetch_add(layout.size(), Ordering::SeqCst);
+    LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    thread_add(&TL_DEALLOC, layout.size());
+    // memoria alocada por outra thread pode ser liberada nesta, entao os bytes vivos da thread nao ficam negativos
+    let _ = TL_LIVE.try_with(|live| live.set(live.get().saturating_sub(layout.size())));
 }
 
 pub fn reset() {
     ALLOC.store(0, Ordering::SeqCst);
     DEALLOC.store(0, Ordering::SeqCst);
+    reset_peak();
+}
+
+/// Passa a medir o pico a partir dos bytes vivos agora, sem zerar os contadores de `alloc` e `dealloc`
+pub fn reset_peak() {
+    let live = LIVE.load(Ordering::SeqCst);
+    BASELINE.store(live, Ordering::SeqCst);
+    PEAK.store(live, Ordering::SeqCst);
 }
 
 pub fn stats() -> Stats {
@@ -25,17 +71,83 @@ pub fn stats() -> Stats {
     let dealloc = DEALLOC.load(Ordering::SeqCst);
     let diff = (alloc as isize) - (dealloc as isize);
 
+    let peak_live = PEAK.load(Ordering::SeqCst).saturating_sub(BASELINE.load(Ordering::SeqCst));
+
     Stats {
         alloc,
         dealloc,
         diff,
+        peak_live,
     }
 }
 
+/// Como `reset`, mas zera apenas os contadores da thread atual
+pub fn thread_reset() {
+    TL_ALLOC.set(0);
+    TL_DEALLOC.set(0);
+    let live = TL_LIVE.get();
+    TL_BASELINE.set(live);
+    TL_PEAK.set(live);
+}
+
+/// Como `stats`, mas com as alocaçoes e liberaçoes feitas pela thread atual desde o ultimo `thread_reset`
+pub fn thread_stats() -> Stats {
+    let alloc = TL_ALLOC.get();
+    let dealloc = TL_DEALLOC.get();
+    Stats {
+        alloc,
+        dealloc,
+        diff: (alloc as isize) - (dealloc as isize),
+        peak_live: TL_PEAK.get().saturating_sub(TL_BASELINE.get()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Stats {
     pub alloc: usize,
     pub dealloc: usize,
     pub diff: isize, 
+    /// Maior quantidade de bytes vivos ao mesmo tempo desde o ultimo `reset` (ou `reset_peak`),
+    /// descontando os que ja estavam vivos nesse momento
+    pub peak_live: usize,
+}
+
+/// Estatisticas de alocaçao de uma operaçao, identificadas por um nome
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LabeledStats {
+    pub label: String,
+    pub stats: Stats,
+}
+
+/// Executa `f` e retorna o seu resultado junto com as alocaçoes feitas durante a execuçao
+/// Zera os contadores globais antes de executar `f`
+pub fn with_tracking<T>(label: &str, f: impl FnOnce() -> T) -> (T, LabeledStats) {
+    reset();
+    let result = f();
+    let stats = stats();
+    (result, LabeledStats { label: label.to_string(), stats })
+}
+
+/// Sequencia de mediçoes de alocaçao, na ordem em que foram feitas
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AllocationProfile {
+    pub samples: Vec<LabeledStats>,
+}
+
+impl AllocationProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Executa `f` com `with_tracking` e guarda a mediçao no perfil
+    pub fn add_measurement<T>(&mut self, label: &str, f: impl FnOnce() -> T) -> T {
+        let (result, sample) = with_tracking(label, f);
+        self.samples.push(sample);
+        result
+    }
 }
 
 unsafe impl GlobalAlloc for TrackingAllocator {
@@ -0,0 +1,381 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_mul_sizes};
+
+/// Matriz de banda `n x n`, guardando apenas as posiçoes `(i, j)` com `i - j <= lower_bw` e `j - i <= upper_bw`
+///
+/// Usa o armazenamento de banda do LAPACK: cada diagonal da banda ocupa `n` posiçoes de `data`, e `(i, j)`
+/// fica no indice `(upper_bw + i - j) * n + j`. A matriz usa `(lower_bw + upper_bw + 1) * n` valores, em vez
+/// dos `n^2` de uma `TableMatrix`; as posiçoes das diagonais que ficam fora da matriz nao sao usadas.
+/// `get` fora da banda retorna 0 sem consultar `data`, e `set` entra em panico com valores nao nulos fora dela.
+/// `new` nao sabe a banda e cria a banda completa, `n - 1` diagonais de cada lado; use `with_band` para
+/// escolher a banda, ou `from_info` e `from_matrix`, que usam a menor banda que contem os valores.
+/// A matriz é sempre quadrada: `new` entra em panico com dimensoes diferentes.
+#[derive(Clone, Debug)]
+pub struct BandMatrix {
+	n: usize,
+	lower_bw: usize,
+	upper_bw: usize,
+	data: Vec<f64>,
+}
+
+impl BandMatrix {
+	/// Cria a matriz nula `n x n` com `lower_bw` diagonais abaixo e `upper_bw` acima da diagonal principal
+	/// As larguras sao limitadas a `n - 1`, o maximo que cabe na matriz.
+	pub fn with_band(n: usize, lower_bw: usize, upper_bw: usize) -> Self {
+		let max_bw = n.saturating_sub(1);
+		let (lower_bw, upper_bw) = (lower_bw.min(max_bw), upper_bw.min(max_bw));
+		BandMatrix { n, lower_bw, upper_bw, data: vec![0.0; (lower_bw + upper_bw + 1) * n] }
+	}
+
+	/// Copia uma matriz quadrada de qualquer tipo, com a banda dada por `lower_bandwidth` e `upper_bandwidth`
+	/// Entra em panico se a matriz nao for quadrada.
+	pub fn from_matrix(a: &impl Matrix) -> Self {
+		assert!(a.is_square(), "BandMatrix must be square");
+		let mut m = BandMatrix::with_band(a.size().0, a.lower_bandwidth(), a.upper_bandwidth());
+		for (pos, value) in a.iter_nnz() {
+			m.set(pos, value);
+		}
+		m
+	}
+
+	fn in_band(&self, i: usize, j: usize) -> bool {
+		i <= j + self.lower_bw && j <= i + self.upper_bw
+	}
+
+	fn index(&self, i: usize, j: usize) -> usize {
+		(self.upper_bw + i - j) * self.n + j
+	}
+
+	/// Colunas da banda na linha `i`
+	fn row_range(&self, i: usize) -> std::ops::RangeInclusive<usize> {
+		i.saturating_sub(self.lower_bw)..=(i + self.upper_bw).min(self.n - 1)
+	}
+
+	/// Numero de diagonais guardadas abaixo e acima da diagonal principal, `(lower_bw, upper_bw)`, sem olhar os valores
+	/// Pode ser maior que a banda dos valores nao nulos (`lower_bandwidth` e `upper_bandwidth`), por exemplo
+	/// em uma matriz criada por `new`.
+	pub fn storage_bandwidth(&self) -> (usize, usize) {
+		(self.lower_bw, self.upper_bw)
+	}
+
+	/// Maior diagonal guardada com algum valor nao nulo, abaixo (`lower`) ou acima da diagonal principal
+	/// Percorre as diagonais de fora para dentro, parando na primeira com um valor nao nulo.
+	fn widest_nonzero_diagonal(&self, lower: bool) -> usize {
+		let bw = if lower { self.lower_bw } else { self.upper_bw };
+		(1..=bw).rev()
+			.find(|&d| (0..self.n - d).any(|k| {
+				let (i, j) = if lower { (k + d, k) } else { (k, k + d) };
+				self.data[self.index(i, j)] != 0.0
+			}))
+			.unwrap_or(0)
+	}
+
+	/// Estimativa dos bytes usados pela matriz: a struct e um `f64` por posiçao das diagonais guardadas
+	pub fn memory_estimate(&self) -> usize {
+		size_of::<Self>() + self.data.len() * size_of::<f64>()
+	}
+}
+
+impl Matrix for BandMatrix {
+	/// Cria a banda completa: entra em panico se a matriz nao for quadrada
+	fn new(size: Pair) -> Self {
+		assert_eq!(size.0, size.1, "BandMatrix must be square");
+		BandMatrix::with_band(size.0, size.0, size.0)
+	}
+
+	fn size(&self) -> Pair {
+		(self.n, self.n)
+	}
+
+	/// Entra em panico se `value` nao for 0 e a posiçao estiver fora da banda
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if self.in_band(pos.0, pos.1) {
+			let k = self.index(pos.0, pos.1);
+			self.data[k] = value;
+		} else {
+			assert!(value == 0.0, "BandMatrix only stores values inside the band");
+		}
+	}
+
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if self.in_band(pos.0, pos.1) { self.data[self.index(pos.0, pos.1)] } else { 0.0 }
+	}
+
+	/// A transposta troca `lower_bw` e `upper_bw`, copiando cada diagonal para a posiçao da diagonal oposta
+	/// Complexidade de tempo: O(n * (lower_bw + upper_bw))
+	fn transposed(self) -> Self {
+		let mut t = BandMatrix::with_band(self.n, self.upper_bw, self.lower_bw);
+		for i in 0..self.n {
+			for j in self.row_range(i) {
+				let k = t.index(j, i);
+				t.data[k] = self.data[self.index(i, j)];
+			}
+		}
+		t
+	}
+
+	/// A soma tem a maior das duas bandas de cada lado
+	/// Complexidade de tempo: O(n * (lower_bw + upper_bw))
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size(), b.size())?;
+		if a.lower_bw == b.lower_bw && a.upper_bw == b.upper_bw {
+			let data = a.data.iter().zip(&b.data).map(|(x, y)| x + y).collect();
+			return Ok(BandMatrix { n: a.n, lower_bw: a.lower_bw, upper_bw: a.upper_bw, data });
+		}
+		let mut c = BandMatrix::with_band(a.n, a.lower_bw.max(b.lower_bw), a.upper_bw.max(b.upper_bw));
+		for m in [a, b] {
+			for i in 0..m.n {
+				for j in m.row_range(i) {
+					let k = c.index(i, j);
+					c.data[k] += m.data[m.index(i, j)];
+				}
+			}
+		}
+		Ok(c)
+	}
+
+	/// O produto tem `a.lower_bw + b.lower_bw` diagonais abaixo e `a.upper_bw + b.upper_bw` acima da diagonal,
+	/// e `C[i][j]` soma apenas os `k` dentro da banda das duas matrizes
+	/// Complexidade de tempo: O(n * (lower_bw + upper_bw)^2)
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size(), b.size())?;
+		let mut c = BandMatrix::with_band(a.n, a.lower_bw + b.lower_bw, a.upper_bw + b.upper_bw);
+		for i in 0..c.n {
+			for j in c.row_range(i) {
+				let start = i.saturating_sub(a.lower_bw).max(j.saturating_sub(b.upper_bw));
+				let end = (i + a.upper_bw).min(j + b.lower_bw).min(c.n - 1);
+				let k = c.index(i, j);
+				c.data[k] = (start..=end).map(|k| a.data[a.index(i, k)] * b.data[b.index(k, j)]).sum();
+			}
+		}
+		Ok(c)
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		BandMatrix { data: a.data.iter().map(|value| value * scalar).collect(), ..*a }
+	}
+
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		for value in a.data.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Soma no lugar quando a banda de `b` cabe na de `a`, ou troca `a` pela soma com a banda maior
+	fn add_assign(a: &mut Self, b: &Self) {
+		assert_eq!(a.n, b.n, "Incompatible matrices for addition");
+		if b.lower_bw > a.lower_bw || b.upper_bw > a.upper_bw {
+			*a = Self::add(a, b);
+			return;
+		}
+		for i in 0..b.n {
+			for j in b.row_range(i) {
+				let k = a.index(i, j);
+				a.data[k] += b.data[b.index(i, j)];
+			}
+		}
+	}
+
+	/// Usa a menor banda que contem os valores nao nulos
+	/// Entra em panico se a matriz nao for quadrada.
+	fn from_info(info: &MatrixInfo) -> Self {
+		assert_eq!(info.size.0, info.size.1, "BandMatrix must be square");
+		let nonzero = || info.values.iter().filter(|(_, value)| *value != 0.0);
+		let lower_bw = nonzero().map(|((i, j), _)| i.saturating_sub(*j)).max().unwrap_or(0);
+		let upper_bw = nonzero().map(|((i, j), _)| j.saturating_sub(*i)).max().unwrap_or(0);
+		let mut m = BandMatrix::with_band(info.size.0, lower_bw, upper_bw);
+		for (pos, value) in info.values.iter() {
+			m.set(*pos, *value);
+		}
+		m
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size(),
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Percorre a banda linha por linha, pulando os zeros
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new((0..self.n)
+			.flat_map(move |i| self.row_range(i).map(move |j| ((i, j), self.data[self.index(i, j)])))
+			.filter(|(_, value)| *value != 0.0))
+	}
+
+	fn diagonal(&self) -> Vec<f64> {
+		self.data[self.upper_bw * self.n..(self.upper_bw + 1) * self.n].to_vec()
+	}
+
+	/// Complexidade de tempo: O(n * (lower_bw + upper_bw))
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.n, v.len(), "Incompatible vector for mul_vec");
+		(0..self.n)
+			.map(|i| self.row_range(i).map(|j| self.data[self.index(i, j)] * v[j]).sum())
+			.collect()
+	}
+
+	/// Percorre as diagonais guardadas abaixo da diagonal principal, da mais externa para dentro
+	/// Complexidade de tempo: O(n * lower_bw)
+	fn lower_bandwidth(&self) -> usize {
+		self.widest_nonzero_diagonal(true)
+	}
+
+	/// Percorre as diagonais guardadas acima da diagonal principal, da mais externa para dentro
+	/// Complexidade de tempo: O(n * upper_bw)
+	fn upper_bandwidth(&self) -> usize {
+		self.widest_nonzero_diagonal(false)
+	}
+
+	/// Complexidade de tempo: O(n * (lower_bw + upper_bw))
+	fn bandwidth(&self) -> usize {
+		self.lower_bandwidth().max(self.upper_bandwidth())
+	}
+
+	/// Verdadeiro sem percorrer os valores se a banda guardada couber em `lower_bw` e `upper_bw`;
+	/// senao verifica as posiçoes nao nulas
+	fn is_banded(&self, lower_bw: usize, upper_bw: usize) -> bool {
+		(self.lower_bw <= lower_bw && self.upper_bw <= upper_bw)
+			|| self.iter_nnz().all(|((i, j), _)| i <= j + lower_bw && j <= i + upper_bw)
+	}
+}
+
+/// Resolve `A x = b` por fatoraçao LU sem pivoteamento, feita em uma copia da banda
+/// Sem troca de linhas, os fatores `L` e `U` ficam dentro da banda de `A` (nao ha preenchimento fora dela).
+/// Nao verifica os pivos: um pivo nulo produz valores infinitos ou `NaN`, entao a matriz deve ser, por exemplo,
+/// diagonal dominante ou simetrica definida positiva.
+/// Entra em panico se `b` nao tiver n elementos.
+/// Complexidade de tempo: O(n * lower_bw * upper_bw)
+pub fn solve_banded(a: &BandMatrix, b: &[f64]) -> Vec<f64> {
+	assert_eq!(a.n, b.len(), "Incompatible vector for solve_banded");
+	let mut lu = a.clone();
+	let mut x = b.to_vec();
+	let n = a.n;
+	// eliminaçao: L fica abaixo da diagonal de `lu` e U na diagonal e acima, e `x` recebe L^-1 b
+	for k in 0..n {
+		let pivot = lu.data[lu.index(k, k)];
+		for i in k + 1..=(k + lu.lower_bw).min(n - 1) {
+			let ik = lu.index(i, k);
+			let factor = lu.data[ik] / pivot;
+			lu.data[ik] = factor;
+			for j in k + 1..=(k + lu.upper_bw).min(n - 1) {
+				let (ij, kj) = (lu.index(i, j), lu.index(k, j));
+				lu.data[ij] -= factor * lu.data[kj];
+			}
+			x[i] -= factor * x[k];
+		}
+	}
+	// substituiçao reversa com U
+	for i in (0..n).rev() {
+		let sum: f64 = (i + 1..=(i + lu.upper_bw).min(n - 1)).map(|j| lu.data[lu.index(i, j)] * x[j]).sum();
+		x[i] = (x[i] - sum) / lu.data[lu.index(i, i)];
+	}
+	x
+}
+
+/// Compara os valores e nao a banda guardada: matrizes com bandas diferentes e os mesmos valores sao iguais
+/// Percorre a uniao das duas bandas, sem consultar as posiçoes fora delas.
+impl PartialEq for BandMatrix {
+	fn eq(&self, other: &Self) -> bool {
+		let (lower_bw, upper_bw) = (self.lower_bw.max(other.lower_bw), self.upper_bw.max(other.upper_bw));
+		self.n == other.n && (0..self.n).all(|i| {
+			(i.saturating_sub(lower_bw)..=(i + upper_bw).min(self.n - 1)).all(|j| self.get((i, j)) == other.get((i, j)))
+		})
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&BandMatrix> for BandMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for BandMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::HashMapMatrix;
+
+	fn poisson(n: usize) -> BandMatrix {
+		let mut a = BandMatrix::with_band(n, 1, 1);
+		for i in 0..n {
+			a.set((i, i), 2.0);
+			if i + 1 < n {
+				a.set((i + 1, i), -1.0);
+				a.set((i, i + 1), -1.0);
+			}
+		}
+		a
+	}
+
+	#[test]
+	fn solve_banded_solves_tridiagonal_system() {
+		let a = poisson(100);
+		let x: Vec<f64> = (0..100).map(|i| (i as f64 * 0.1).sin()).collect();
+		let solution = solve_banded(&a, &a.mul_vec(&x));
+		for (got, expected) in solution.iter().zip(&x) {
+			assert!((got - expected).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn info_roundtrip_keeps_band() {
+		let mut a = BandMatrix::with_band(5, 2, 1);
+		for i in 0..5 {
+			for j in a.row_range(i) {
+				a.set((i, j), (i * 5 + j + 1) as f64);
+			}
+		}
+		assert_eq!(BandMatrix::from_info(&a.to_info()), a);
+		assert_eq!(BandMatrix::from_matrix(&HashMapMatrix::from_info(&a.to_info())), a);
+	}
+
+	#[test]
+	fn bandwidth_is_the_band_of_the_nonzero_values() {
+		let mut a = BandMatrix::with_band(6, 2, 1);
+		a.set((3, 1), 1.0);
+		a.set((1, 2), 1.0);
+		assert_eq!((a.lower_bandwidth(), a.upper_bandwidth(), a.bandwidth()), (2, 1, 2));
+		assert_eq!(poisson(4).bandwidth(), 1);
+
+		// `new` guarda a banda completa, mas a banda dos valores é a da diagonal
+		let mut b = BandMatrix::new((5, 5));
+		b.set((2, 2), 3.0);
+		assert_eq!(b.storage_bandwidth(), (4, 4));
+		assert_eq!((b.lower_bandwidth(), b.upper_bandwidth(), b.bandwidth()), (0, 0, 0));
+		b.set((0, 3), 1.0);
+		assert_eq!((b.lower_bandwidth(), b.upper_bandwidth(), b.bandwidth()), (0, 3, 3));
+		b.set((0, 3), 0.0);
+		assert_eq!(b.bandwidth(), 0);
+		assert_eq!(BandMatrix::new((0, 0)).bandwidth(), 0);
+	}
+
+	#[test]
+	fn eq_compares_values_and_not_the_stored_band() {
+		let mut wide = BandMatrix::with_band(4, 3, 2);
+		for i in 0..4 {
+			wide.set((i, i), 2.0);
+			if i + 1 < 4 {
+				wide.set((i + 1, i), -1.0);
+				wide.set((i, i + 1), -1.0);
+			}
+		}
+		assert_eq!(wide, poisson(4));
+		assert_eq!(poisson(4), wide);
+		wide.set((3, 0), 1e-3);
+		assert_ne!(wide, poisson(4));
+		assert_ne!(poisson(4), wide);
+		assert_ne!(poisson(4), poisson(5));
+	}
+}
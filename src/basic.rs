@@ -1,35 +1,2175 @@
+use std::{collections::HashMap, fmt, ops::{Add, Mul, Range}};
+
+use crate::{EPSILON, factorization, linalg, sparse_vec::SparseVec, table_matrix::TableMatrix};
 
 pub type Pair = (usize, usize); 
 
-pub trait Matrix {
+/// Tipo dos valores que uma `MapMatrix` consegue guardar: precisa ter um zero (`Default`), soma e multiplicaçao
+/// Implementado automaticamente para `f64`, `f32`, `i64` e outros tipos numericos
+/// O trait `Matrix`, a `MatrixInfo` e a `TableMatrix` continuam apenas com `f64`; para os outros tipos a `MapMatrix`
+/// serve so como armazenamento (ver a documentaçao de `MapMatrix`)
+pub trait Scalar: Copy + Default + PartialEq + Add<Output = Self> + Mul<Output = Self> {}
+
+impl<S: Copy + Default + PartialEq + Add<Output = S> + Mul<Output = S>> Scalar for S {}
+
+/// Erros das operaçoes sobre matrizes
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixError {
+	/// As dimensoes da matriz nao sao as esperadas pela operaçao
+	DimensionMismatch { expected: Pair, got: Pair },
+	/// A posiçao esta fora dos limites da matriz
+	OutOfBounds { pos: Pair, size: Pair },
+	/// A matriz é singular (ou o metodo numerico nao convergiu)
+	SingularMatrix,
+	/// O texto lido nao descreve uma matriz valida
+	Parse(String),
+}
+
+impl fmt::Display for MatrixError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MatrixError::DimensionMismatch { expected, got } =>
+				write!(f, "dimensoes incompativeis: esperado {}x{}, recebido {}x{}", expected.0, expected.1, got.0, got.1),
+			MatrixError::OutOfBounds { pos, size } =>
+				write!(f, "posiçao ({}, {}) fora de uma matriz {}x{}", pos.0, pos.1, size.0, size.1),
+			MatrixError::SingularMatrix => write!(f, "matriz singular"),
+			MatrixError::Parse(message) => write!(f, "erro de leitura: {}", message),
+		}
+	}
+}
+
+impl std::error::Error for MatrixError {}
+
+/// Operaçoes comuns a todos os tipos de matriz
+///
+/// Os tipos que so guardam uma estrutura fixa (`DiagonalMatrix`, `BandMatrix`, `LowerTriMatrix` e `UpperTriMatrix`)
+/// entram em panico quando uma operaçao produziria um valor nao nulo fora dessa estrutura: `set` fora dela e as
+/// operaçoes cujo resultado nao tem a mesma estrutura, como `transposed` de uma matriz triangular que nao é diagonal.
+pub trait Matrix: Sized {
     
     fn new(size: Pair) -> Self;
+	/// Retorna as dimensoes da matriz, como um par (linhas, colunas)
+	fn size(&self) -> Pair;
     fn set(&mut self, pos: Pair, value: f64);
     fn get(&self, pos: Pair) -> f64;
+	/// Retorna a transposta da matriz
+	/// Em tipos com estrutura fixa entra em panico se a transposta nao couber nela (ver a documentaçao do trait)
     fn transposed(self) -> Self;
-    fn add(a : &Self, b : &Self) -> Self;
-    fn mul(a : &Self, b : &Self) -> Self;
+	/// Retorna `a + b`, ou `MatrixError::DimensionMismatch` se as matrizes tiverem tamanhos diferentes (ver `check_add_sizes`)
+	fn try_add(a : &Self, b : &Self) -> Result<Self, MatrixError>;
+	/// Retorna `a * b`, ou `MatrixError::DimensionMismatch` se `b` nao tiver tantas linhas quanto `a` tem colunas (ver `check_mul_sizes`)
+	fn try_mul(a : &Self, b : &Self) -> Result<Self, MatrixError>;
+	/// Retorna `a + b`
+	/// Entra em panico se as matrizes tiverem tamanhos diferentes; chama `try_add`
+	fn add(a : &Self, b : &Self) -> Self {
+		Self::try_add(a, b).expect("Incompatible matrices for addition")
+	}
+	/// Retorna `a * b`
+	/// Entra em panico se `b` nao tiver tantas linhas quanto `a` tem colunas; chama `try_mul`
+	fn mul(a : &Self, b : &Self) -> Self {
+		Self::try_mul(a, b).expect("Incompatible matrices for multiplication")
+	}
     fn muls(a : &Self, scalar: f64) -> Self;
+	/// Soma `b` em `a`, `a = a + b`, sem criar uma nova matriz
+	/// Entra em panico se as matrizes tiverem tamanhos diferentes.
+	/// Por padrao atualiza com `set` as posiçoes dos valores de `iter_nnz` de `b`
+	fn add_assign(a : &mut Self, b : &Self) {
+		assert_eq!(a.size(), b.size(), "Incompatible matrices for addition");
+		for (pos, value) in b.iter_nnz() {
+			a.set(pos, a.get(pos) + value);
+		}
+	}
+	/// Multiplica todos os valores de `a` por `scalar`
+	/// Por padrao substitui `a` por `muls(a, scalar)`
+	fn muls_assign(a : &mut Self, scalar: f64) {
+		*a = Self::muls(a, scalar);
+	}
+	/// Retorna `a - b`
+	/// Por padrao calcula `add(a, muls(b, -1.0))`
+	fn sub(a : &Self, b : &Self) -> Self {
+		Self::add(a, &Self::muls(b, -1.0))
+	}
+	/// Retorna `-a`
+	fn neg(a : &Self) -> Self {
+		Self::muls(a, -1.0)
+	}
+	/// Produto de Hadamard (elemento a elemento), `C[i][j] = A[i][j] * B[i][j]`
+	/// Entra em panico se as matrizes tiverem tamanhos diferentes.
+	/// Por padrao percorre os valores de `iter_nnz` de `a`, consultando `b` com `get`
+	fn hadamard(a : &Self, b : &Self) -> Self {
+		assert_eq!(a.size(), b.size(), "Incompatible matrices for hadamard product");
+		let mut c = Self::new(a.size());
+		for (pos, va) in a.iter_nnz() {
+			let value = va * b.get(pos);
+			if value != 0.0 {
+				c.set(pos, value);
+			}
+		}
+		c
+	}
+	/// Aplica `f` a todos os elementos da matriz, `C[i][j] = f(A[i][j])`
+	/// Em matrizes esparsas as posiçoes vazias continuam vazias, entao `f(0.0)` precisa ser 0;
+	/// por padrao entra em panico se nao for (o resultado seria denso, use `TableMatrix`).
+	/// Por padrao chama `map_nnz`
+	fn map_elements(a : &Self, f: impl Fn(f64) -> f64) -> Self {
+		assert!(f(0.0) == 0.0, "map_elements: f(0.0) != 0.0 tornaria a matriz densa, use TableMatrix");
+		Self::map_nnz(a, |_, value| f(value))
+	}
+	/// Aplica `f(posiçao, valor)` apenas aos valores nao nulos da matriz; as posiçoes nulas continuam nulas
+	/// Os valores que `f` leva para 0 sao removidos. Por padrao percorre `iter_nnz`
+	fn map_nnz(a : &Self, f: impl Fn(Pair, f64) -> f64) -> Self {
+		let mut c = Self::new(a.size());
+		for (pos, value) in a.iter_nnz() {
+			let value = f(pos, value);
+			if value != 0.0 {
+				c.set(pos, value);
+			}
+		}
+		c
+	}
 	fn from_info(info: &MatrixInfo) -> Self;
 	fn to_info(&self) -> MatrixInfo;
+
+	/// Itera sobre os valores nao nulos da matriz como pares `(posiçao, valor)`, sem ordem definida
+	/// Por padrao percorre os valores de `to_info`, que aloca um vetor com todos eles
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new(self.to_info().values.into_iter())
+	}
+
+	/// Chama `f` para cada valor nao nulo da matriz (ver `iter_nnz`)
+	fn for_each_nnz(&self, mut f: impl FnMut(Pair, f64)) {
+		for (pos, value) in self.iter_nnz() {
+			f(pos, value);
+		}
+	}
+
+	/// Copia a matriz para os vetores `(row_ptr, col_idx, values)` do formato CSR (ver `CsrMatrix`),
+	/// usados por bibliotecas externas como o `sprs`
+	/// Por padrao ordena os valores nao nulos de `iter_nnz` por posiçao e conta os valores de cada linha
+	/// Complexidade de tempo: O(k log k + n), onde k é o numero de elementos e n o numero de linhas
+	fn to_csr_arrays(&self) -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+		let mut entries: Vec<(Pair, f64)> = self.iter_nnz().filter(|(_, value)| *value != 0.0).collect();
+		entries.sort_unstable_by_key(|(pos, _)| *pos);
+		let mut row_ptr = vec![0; self.size().0 + 1];
+		for ((i, _), _) in entries.iter() {
+			row_ptr[i + 1] += 1;
+		}
+		for i in 0..self.size().0 {
+			row_ptr[i + 1] += row_ptr[i];
+		}
+		let (col_idx, values) = entries.into_iter().map(|((_, j), value)| (j, value)).unzip();
+		(row_ptr, col_idx, values)
+	}
+
+	/// Constroi a matriz `nrows x ncols` a partir dos vetores do formato CSR, o inverso de `to_csr_arrays`
+	/// Entra em panico se os vetores nao estiverem no formato descrito em `CsrMatrix` (ver `check_csr_arrays`).
+	/// Por padrao chama `set` para cada valor
+	fn from_csr_arrays(nrows: usize, ncols: usize, row_ptr: Vec<usize>, col_idx: Vec<usize>, values: Vec<f64>) -> Self {
+		check_csr_arrays((nrows, ncols), &row_ptr, &col_idx, &values);
+		let mut m = Self::new((nrows, ncols));
+		for i in 0..nrows {
+			for k in row_ptr[i]..row_ptr[i + 1] {
+				m.set((i, col_idx[k]), values[k]);
+			}
+		}
+		m
+	}
+
+	/// Itera sobre as linhas da matriz em ordem, cada uma como um vetor denso com uma posiçao por coluna
+	/// Por padrao agrupa os valores de `iter_nnz` por linha uma unica vez; cada linha densa é montada quando pedida
+	fn iter_rows<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		let (rows, cols) = self.size();
+		let mut grouped: Vec<Vec<(usize, f64)>> = vec![Vec::new(); rows];
+		self.for_each_nnz(|(i, j), value| grouped[i].push((j, value)));
+		Box::new(grouped.into_iter().map(move |values| {
+			let mut row = vec![0.0; cols];
+			for (j, value) in values {
+				row[j] = value;
+			}
+			row
+		}))
+	}
+
+	/// Itera sobre as colunas da matriz em ordem, cada uma como um vetor denso com uma posiçao por linha
+	/// Por padrao agrupa os valores de `iter_nnz` por coluna, como `iter_rows`
+	fn iter_cols<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		let (rows, cols) = self.size();
+		let mut grouped: Vec<Vec<(usize, f64)>> = vec![Vec::new(); cols];
+		self.for_each_nnz(|(i, j), value| grouped[j].push((i, value)));
+		Box::new(grouped.into_iter().map(move |values| {
+			let mut col = vec![0.0; rows];
+			for (i, value) in values {
+				col[i] = value;
+			}
+			col
+		}))
+	}
+
+	/// Produto matriz-vetor `A * v`, sem montar `v` como matriz coluna
+	/// Entra em panico se `v` nao tiver uma posiçao por coluna de `A`.
+	/// Por padrao soma `A[i][j] * v[j]` em `result[i]` para cada valor de `for_each_nnz`
+	/// Complexidade de tempo: O(k + n), onde k é o numero de elementos de `A`
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size().1, v.len(), "Incompatible vector for mul_vec");
+		let mut result = vec![0.0; self.size().0];
+		self.for_each_nnz(|(i, j), value| result[i] += value * v[j]);
+		result
+	}
+
+	/// Produto vetor-matriz `v^T * A`
+	/// Entra em panico se `v` nao tiver uma posiçao por linha de `A`.
+	/// Por padrao soma `v[i] * A[i][j]` em `result[j]` para cada valor de `for_each_nnz`
+	fn vec_mul(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size().0, v.len(), "Incompatible vector for vec_mul");
+		let mut result = vec![0.0; self.size().1];
+		self.for_each_nnz(|(i, j), value| result[j] += v[i] * value);
+		result
+	}
+
+	/// Produto matriz-vetor `A * v` com um vetor esparso, com resultado esparso
+	/// Entra em panico se `v` tiver alguma posiçao maior ou igual ao numero de colunas de `A`.
+	/// Por padrao copia `v` para um vetor denso, para consultar `v[j]` sem hash a cada valor de `for_each_nnz`,
+	/// e acumula no resultado apenas os produtos com `v[j]` nao nulo
+	/// Complexidade de tempo: O(k + m), onde k é o numero de elementos de `A` e m o numero de colunas
+	fn mul_sparse_vec(&self, v: &SparseVec) -> SparseVec {
+		assert!(v.iter().all(|(j, _)| j < self.size().1), "Incompatible vector for mul_sparse_vec");
+		let dense = v.to_dense(self.size().1);
+		let mut result = SparseVec::new();
+		self.for_each_nnz(|(i, j), value| {
+			let x = dense[j];
+			if x != 0.0 {
+				result.add_at(i, value * x);
+			}
+		});
+		result
+	}
+
+	/// Retorna o numero de valores nao nulos (com modulo maior que `EPSILON`) da matriz
+	/// Por padrao conta os valores de `iter_nnz`
+	fn nnz(&self) -> usize {
+		self.iter_nnz().filter(|(_, value)| value.abs() > EPSILON).count()
+	}
+
+	/// Fraçao das posiçoes da matriz que sao nulas, `1 - nnz / (linhas * colunas)`
+	/// Uma matriz sem posiçoes (com alguma dimensao 0) tem esparsidade 1
+	fn sparsity(&self) -> f64 {
+		let (rows, cols) = self.size();
+		if rows * cols == 0 {
+			return 1.0;
+		}
+		1.0 - self.nnz() as f64 / (rows * cols) as f64
+	}
+
+	/// Traço da matriz, a soma dos elementos da diagonal principal
+	/// Em matrizes nao quadradas soma a diagonal mais curta, de `min(linhas, colunas)` elementos
+	/// Por padrao soma `diagonal`
+	fn trace(&self) -> f64 {
+		self.diagonal().iter().sum()
+	}
+
+	/// Retorna os `min(linhas, colunas)` elementos da diagonal principal
+	/// Complexidade de tempo: O(min(linhas, colunas) * get)
+	fn diagonal(&self) -> Vec<f64> {
+		let (rows, cols) = self.size();
+		(0..rows.min(cols)).map(|i| self.get((i, i))).collect()
+	}
+
+	/// Cria a matriz identidade `n x n`
+	/// Complexidade de tempo: O(new + n * set)
+	fn eye(n: usize) -> Self {
+		let mut m = Self::new((n, n));
+		for i in 0..n {
+			m.set((i, i), 1.0);
+		}
+		m
+	}
+
+	/// Cria uma matriz quadrada com `diag` na diagonal principal e zeros no resto
+	/// Complexidade de tempo: O(new + n * set), onde n é o tamanho de `diag`
+	fn from_diagonal(diag: &[f64]) -> Self {
+		let mut m = Self::new((diag.len(), diag.len()));
+		for (i, value) in diag.iter().enumerate() {
+			m.set((i, i), *value);
+		}
+		m
+	}
+
+	/// Produto tensorial de dois vetores, `v ⊗ w = v * w^T`, resultando em uma matriz `v.len() x w.len()`
+	/// Apenas os produtos nao nulos sao definidos na matriz
+	fn tensor_product_vec(v: &[f64], w: &[f64]) -> Self {
+		let mut m = Self::new((v.len(), w.len()));
+		for (i, vi) in v.iter().enumerate() {
+			if *vi == 0.0 {
+				continue;
+			}
+			for (j, wj) in w.iter().enumerate() {
+				if *wj != 0.0 {
+					m.set((i, j), vi * wj);
+				}
+			}
+		}
+		m
+	}
+
+	/// Produto externo `u * v^T`, uma matriz `u.len() x v.len()`; o mesmo que `tensor_product_vec`
+	fn outer(u: &[f64], v: &[f64]) -> Self {
+		Self::tensor_product_vec(u, v)
+	}
+
+	/// Atualizaçao de posto um no lugar, `A = A + alpha * u * v^T`, sem montar a matriz `outer(u, v)`
+	/// Entra em panico se `u` e `v` nao tiverem o numero de linhas e de colunas de `A`.
+	/// Por padrao atualiza com `get` e `set` apenas as posiçoes com `u[i]` e `v[j]` nao nulos
+	/// Complexidade de tempo: O(nu * nv * (get + set)), onde nu e nv sao o numero de valores nao nulos de u e v
+	fn rank_one_update(a: &mut Self, alpha: f64, u: &[f64], v: &[f64]) {
+		assert_eq!(a.size(), (u.len(), v.len()), "Incompatible vectors for rank_one_update");
+		if alpha == 0.0 {
+			return;
+		}
+		for (i, ui) in u.iter().enumerate().filter(|(_, ui)| **ui != 0.0) {
+			for (j, vj) in v.iter().enumerate().filter(|(_, vj)| **vj != 0.0) {
+				a.set((i, j), a.get((i, j)) + alpha * ui * vj);
+			}
+		}
+	}
+
+	/// Produto de Kronecker `A ⊗ B`
+	/// Para `A` de tamanho `m x n` e `B` de tamanho `p x q` o resultado tem tamanho `(m*p) x (n*q)`
+	/// e o bloco `(i, j)` do resultado, de tamanho `p x q`, é `A[i][j] * B`
+	/// Percorre apenas os elementos nao nulos dos dois operandos
+	/// Complexidade de tempo: O(ka * kb * set), onde ka e kb sao o numero de elementos de a e b
+	fn kron(a: &Self, b: &Self) -> Self {
+		let (asize, bsize) = (a.size(), b.size());
+		let mut c = Self::new((asize.0 * bsize.0, asize.1 * bsize.1));
+		let bvalues: Vec<_> = b.iter_nnz().filter(|(_, vb)| *vb != 0.0).collect();
+		for (apos, va) in a.iter_nnz().filter(|(_, va)| *va != 0.0) {
+			for (bpos, vb) in bvalues.iter() {
+				c.set((apos.0 * bsize.0 + bpos.0, apos.1 * bsize.1 + bpos.1), va * vb);
+			}
+		}
+		c
+	}
+
+	/// Produto tensorial de duas matrizes, o mesmo que `kron`
+	fn tensor_product_matrix(a: &Self, b: &Self) -> Self {
+		Self::kron(a, b)
+	}
+
+	/// Verifica se a matriz pode ser escrita como um produto externo `u * v^T` nao nulo
+	///
+	/// Toma como pivo o elemento `(p, q)` de maior modulo; a matriz tem posto um se e somente se
+	/// todo elemento satisfaz `A[i][j] = A[i][q] * A[p][j] / A[p][q]`, a menos de `EPSILON`.
+	/// A matriz nula tem posto zero, e portanto retorna `false`.
+	fn is_rank_one(&self) -> bool {
+		let values: Vec<(Pair, f64)> = self.to_info().values.into_iter()
+			.filter(|(_, v)| v.abs() > EPSILON)
+			.collect();
+		let Some(&(pivot, vpivot)) = values.iter().max_by(|a, b| a.1.abs().total_cmp(&b.1.abs())) else {
+			return false;
+		};
+		let mut column = HashMap::new();
+		let mut row = HashMap::new();
+		for (pos, value) in values.iter() {
+			if pos.1 == pivot.1 {
+				column.insert(pos.0, *value);
+			}
+			if pos.0 == pivot.0 {
+				row.insert(pos.1, *value);
+			}
+		}
+		let tolerance = EPSILON * vpivot.abs().max(1.0);
+		for (pos, value) in values.iter() {
+			let (Some(ci), Some(rj)) = (column.get(&pos.0), row.get(&pos.1)) else {
+				return false;
+			};
+			if (value - ci * rj / vpivot).abs() > tolerance {
+				return false;
+			}
+		}
+		values.len() == column.len() * row.len()
+	}
+
+	/// Retorna a serie geometrica truncada `I + A + A^2 + ... + A^k`
+	///
+	/// Usa a decomposiçao `S(2m+1) = (I + A^(m+1)) * S(m)`, fazendo O(log k) chamadas de `mul`.
+	/// Em matrizes esparsas (`MapMatrix`) as potencias de `A` tendem a ficar cada vez mais densas,
+	/// de modo que os intermediarios podem ocupar ate O(n^2) de memoria.
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada.
+	fn geometric_sum(&self, k: usize) -> Result<Self, MatrixError> {
+		let size = self.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		Ok(geometric_sum_and_power(self, k).0)
+	}
+
+	/// Verifica se a matriz tem o mesmo numero de linhas e colunas
+	fn is_square(&self) -> bool {
+		let (rows, cols) = self.size();
+		rows == cols
+	}
+
+	/// Verifica se a matriz é quadrada e `|A[i][j] - A[j][i]| < eps` para todas as posiçoes
+	/// Por padrao compara cada valor de `iter_nnz` com o valor na posiçao transposta, sem percorrer as posiçoes nulas
+	fn is_symmetric(&self, eps: f64) -> bool {
+		self.is_square() && self.iter_nnz().all(|((i, j), value)| (value - self.get((j, i))).abs() < eps)
+	}
+
+	/// Verifica se a matriz é quadrada e todos os valores fora da diagonal tem modulo menor que `eps`
+	/// Por padrao percorre `iter_nnz`
+	fn is_diagonal(&self, eps: f64) -> bool {
+		self.is_square() && self.iter_nnz().all(|((i, j), value)| i == j || value.abs() < eps)
+	}
+
+	/// Maior `i - j` entre as posiçoes nao nulas abaixo da diagonal, ou 0 se nao houver nenhuma
+	/// Por padrao percorre `iter_nnz`, ignorando valores iguais a zero
+	fn lower_bandwidth(&self) -> usize {
+		self.iter_nnz()
+			.filter(|(_, value)| *value != 0.0)
+			.map(|((i, j), _)| i.saturating_sub(j))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Maior `j - i` entre as posiçoes nao nulas acima da diagonal, ou 0 se nao houver nenhuma
+	/// Por padrao percorre `iter_nnz`, ignorando valores iguais a zero
+	fn upper_bandwidth(&self) -> usize {
+		self.iter_nnz()
+			.filter(|(_, value)| *value != 0.0)
+			.map(|((i, j), _)| j.saturating_sub(i))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Maior `|i - j|` entre as posiçoes nao nulas: 0 para matrizes diagonais, 1 para tridiagonais
+	/// Por padrao percorre `iter_nnz` uma unica vez, ignorando valores iguais a zero
+	fn bandwidth(&self) -> usize {
+		self.iter_nnz()
+			.filter(|(_, value)| *value != 0.0)
+			.map(|((i, j), _)| i.abs_diff(j))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Verifica se todas as posiçoes nao nulas estao na banda com `lower_bw` diagonais abaixo e `upper_bw` acima
+	/// da diagonal principal, parando na primeira posiçao fora da banda
+	fn is_banded(&self, lower_bw: usize, upper_bw: usize) -> bool {
+		self.iter_nnz()
+			.filter(|(_, value)| *value != 0.0)
+			.all(|((i, j), _)| i <= j + lower_bw && j <= i + upper_bw)
+	}
+
+	/// Retorna `A^exp` por exponenciaçao binaria, com O(log exp) chamadas de `mul`
+	/// `power(a, 0)` é a identidade e `power(a, 1)` uma copia de `a`.
+	/// Entra em panico se a matriz nao for quadrada.
+	fn power(a: &Self, exp: u32) -> Self {
+		assert!(a.is_square(), "power requires a square matrix");
+		let n = a.size().0;
+		if exp == 0 {
+			return Self::eye(n);
+		}
+		// muls por 1.0 produz uma copia de `a`
+		let mut base = Self::muls(a, 1.0);
+		let mut result: Option<Self> = None;
+		let mut exp = exp;
+		loop {
+			if exp & 1 == 1 {
+				result = Some(match result {
+					Some(r) => Self::mul(&r, &base),
+					None => Self::muls(&base, 1.0),
+				});
+			}
+			exp >>= 1;
+			if exp == 0 {
+				break;
+			}
+			base = Self::mul(&base, &base);
+		}
+		result.expect("exp > 0 tem ao menos um bit ligado")
+	}
+
+	/// Norma de Frobenius, `sqrt(soma de A[i][j]^2)`
+	/// Por padrao é a raiz de `sum_sq`
+	fn norm_frobenius(&self) -> f64 {
+		self.sum_sq().sqrt()
+	}
+
+	/// Soma dos quadrados dos elementos, `norm_frobenius^2` sem a raiz
+	/// Por padrao percorre os valores de `iter_nnz`
+	fn sum_sq(&self) -> f64 {
+		self.iter_nnz().map(|(_, v)| v * v).sum()
+	}
+
+	/// Soma de todos os elementos da matriz
+	/// Por padrao percorre os valores de `iter_nnz`, ja que as posiçoes nulas nao mudam a soma
+	fn sum(&self) -> f64 {
+		self.iter_nnz().map(|(_, v)| v).sum()
+	}
+
+	/// Maior elemento da matriz, ou `f64::NEG_INFINITY` se ela nao tiver posiçoes
+	/// Por padrao percorre os valores de `iter_nnz`; se eles nao cobrirem todas as posiçoes, 0 tambem é candidato
+	fn max_element(&self) -> f64 {
+		let (rows, cols) = self.size();
+		let mut count = 0;
+		let max = self.iter_nnz().inspect(|_| count += 1).map(|(_, v)| v).fold(f64::NEG_INFINITY, f64::max);
+		if count < rows * cols { max.max(0.0) } else { max }
+	}
+
+	/// Menor elemento da matriz, ou `f64::INFINITY` se ela nao tiver posiçoes
+	/// Por padrao percorre os valores de `iter_nnz`; se eles nao cobrirem todas as posiçoes, 0 tambem é candidato
+	fn min_element(&self) -> f64 {
+		let (rows, cols) = self.size();
+		let mut count = 0;
+		let min = self.iter_nnz().inspect(|_| count += 1).map(|(_, v)| v).fold(f64::INFINITY, f64::min);
+		if count < rows * cols { min.min(0.0) } else { min }
+	}
+
+	/// Norma 1, a maior soma dos modulos de uma coluna
+	/// Por padrao acumula os valores de `iter_nnz` em um vetor com uma soma por coluna
+	fn norm_one(&self) -> f64 {
+		let mut sums = vec![0.0; self.size().1];
+		for ((_, j), v) in self.iter_nnz() {
+			sums[j] += v.abs();
+		}
+		sums.into_iter().fold(0.0, f64::max)
+	}
+
+	/// Norma infinito, a maior soma dos modulos de uma linha
+	/// Por padrao acumula os valores de `iter_nnz` em um vetor com uma soma por linha
+	fn norm_inf(&self) -> f64 {
+		let mut sums = vec![0.0; self.size().0];
+		for ((i, _), v) in self.iter_nnz() {
+			sums[i] += v.abs();
+		}
+		sums.into_iter().fold(0.0, f64::max)
+	}
+
+	/// Soma dos valores de cada linha, um elemento por linha
+	/// Por padrao acumula os valores de `iter_nnz` em `result[linha]`
+	fn row_sums(&self) -> Vec<f64> {
+		let mut sums = vec![0.0; self.size().0];
+		self.for_each_nnz(|(i, _), v| sums[i] += v);
+		sums
+	}
+
+	/// Soma dos valores de cada coluna, um elemento por coluna
+	/// Por padrao acumula os valores de `iter_nnz` em `result[coluna]`
+	fn col_sums(&self) -> Vec<f64> {
+		let mut sums = vec![0.0; self.size().1];
+		self.for_each_nnz(|(_, j), v| sums[j] += v);
+		sums
+	}
+
+	/// Maior modulo de cada linha, um elemento por linha (0 para linhas nulas)
+	fn row_max(&self) -> Vec<f64> {
+		let mut maxs = vec![0.0; self.size().0];
+		self.for_each_nnz(|(i, _), v| maxs[i] = f64::max(maxs[i], v.abs()));
+		maxs
+	}
+
+	/// Maior modulo de cada coluna, um elemento por coluna (0 para colunas nulas)
+	fn col_max(&self) -> Vec<f64> {
+		let mut maxs = vec![0.0; self.size().1];
+		self.for_each_nnz(|(_, j), v| maxs[j] = f64::max(maxs[j], v.abs()));
+		maxs
+	}
+
+	/// Produto interno de Frobenius `<A, B>_F`, a soma de `A[i][j] * B[i][j]` sobre todas as posiçoes
+	/// Complexidade de tempo: O(ka * get), onde ka é o numero de elementos de a
+	/// Retorna `MatrixError::DimensionMismatch` se as matrizes tiverem tamanhos diferentes.
+	fn frobenius_inner_product(&self, b: &Self) -> Result<f64, MatrixError> {
+		if self.size() != b.size() {
+			return Err(MatrixError::DimensionMismatch { expected: self.size(), got: b.size() });
+		}
+		Ok(self.iter_nnz().map(|(pos, v)| v * b.get(pos)).sum())
+	}
+
+	/// Calcula `||A + b_scale * B||_F` sem construir a soma, pela expansao
+	/// `||A||_F^2 + 2 * b_scale * <A, B>_F + b_scale^2 * ||B||_F^2`
+	///
+	/// Complexidade de tempo: O(ka + kb), sem alocar uma matriz intermediaria.
+	/// Como é uma diferença de quadrados, perde precisao relativa quando `A` e `-b_scale * B` sao quase iguais.
+	fn frobenius_norm_of_difference(&self, b: &Self, b_scale: f64) -> Result<f64, MatrixError> {
+		let ab = self.frobenius_inner_product(b)?;
+		let aa: f64 = self.iter_nnz().map(|(_, v)| v * v).sum();
+		let bb: f64 = b.iter_nnz().map(|(_, v)| v * v).sum();
+		Ok((aa + 2.0 * b_scale * ab + b_scale * b_scale * bb).max(0.0).sqrt())
+	}
+
+	/// Fatoraçao de Cholesky incompleta IC(`fill_level`), usada como precondicionador de sistemas
+	/// esparsos simetricos definidos positivos. Retorna o fator triangular inferior `L`.
+	///
+	/// Com `fill_level = 0` o padrao de `L` é o da parte inferior de `A`; com `fill_level > 0`
+	/// tambem sao mantidas as `fill_level` diagonais abaixo da principal.
+	/// Retorna `MatrixError::SingularMatrix` se algum pivo nao for positivo.
+	fn incomplete_cholesky(&self, fill_level: usize) -> Result<Self, MatrixError> {
+		factorization::incomplete_cholesky(self, fill_level)
+	}
+
+	/// Retorna uma matriz `target_size x target_size` com esta matriz no canto superior esquerdo e zeros no resto
+	/// Retorna `MatrixError::DimensionMismatch` se `target_size` for menor que alguma das dimensoes da matriz.
+	fn pad_symmetric(&self, target_size: usize) -> Result<Self, MatrixError> {
+		let (rows, cols) = self.size();
+		if target_size < rows || target_size < cols {
+			let needed = rows.max(cols);
+			return Err(MatrixError::DimensionMismatch { expected: (needed, needed), got: (target_size, target_size) });
+		}
+		self.embed_at((target_size, target_size), (0, 0))
+	}
+
+	/// Retorna uma matriz de tamanho `target_size` com esta matriz posicionada a partir de `offset`, e zeros no resto
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao couber na posiçao pedida.
+	fn embed_at(&self, target_size: Pair, offset: Pair) -> Result<Self, MatrixError> {
+		let size = self.size();
+		let needed = (offset.0 + size.0, offset.1 + size.1);
+		if needed.0 > target_size.0 || needed.1 > target_size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: needed, got: target_size });
+		}
+		let mut result = Self::new(target_size);
+		for (pos, value) in self.iter_nnz() {
+			if value != 0.0 {
+				result.set((pos.0 + offset.0, pos.1 + offset.1), value);
+			}
+		}
+		Ok(result)
+	}
+
+	/// Retorna a submatriz com as linhas `row_range` e as colunas `col_range`, de tamanho `(row_range.len(), col_range.len())`
+	/// Os indices do resultado começam em 0. Entra em panico se os intervalos passarem das dimensoes da matriz.
+	/// Por padrao filtra os valores de `iter_nnz`
+	fn submatrix(&self, row_range: Range<usize>, col_range: Range<usize>) -> Self {
+		let size = self.size();
+		assert!(row_range.end <= size.0 && col_range.end <= size.1, "submatrix out of bounds");
+		let mut c = Self::new((row_range.len(), col_range.len()));
+		for ((i, j), value) in self.iter_nnz() {
+			if row_range.contains(&i) && col_range.contains(&j) {
+				c.set((i - row_range.start, j - col_range.start), value);
+			}
+		}
+		c
+	}
+
+	/// Escreve `sub` na regiao que começa em `(row_offset, col_offset)`, substituindo todos os valores da regiao
+	/// Entra em panico se `sub` nao couber na matriz a partir dessa posiçao.
+	/// Por padrao chama `set` em todas as posiçoes da regiao, em O(linhas * colunas * set) de `sub`
+	fn set_submatrix(&mut self, row_offset: usize, col_offset: usize, sub: &Self) {
+		let (size, sub_size) = (self.size(), sub.size());
+		assert!(row_offset + sub_size.0 <= size.0 && col_offset + sub_size.1 <= size.1, "submatrix out of bounds");
+		for i in 0..sub_size.0 {
+			for j in 0..sub_size.1 {
+				self.set((row_offset + i, col_offset + j), sub.get((i, j)));
+			}
+		}
+	}
+
+	/// Troca as linhas `i` e `j` de lugar
+	/// Entra em panico se alguma das linhas estiver fora da matriz.
+	/// Por padrao troca com `get` e `set` as duas posiçoes de cada coluna, em O(colunas * (get + set))
+	fn row_swap(&mut self, i: usize, j: usize) {
+		let size = self.size();
+		assert!(i < size.0 && j < size.0, "row out of bounds");
+		if i == j {
+			return;
+		}
+		for col in 0..size.1 {
+			let (a, b) = (self.get((i, col)), self.get((j, col)));
+			if a != b {
+				self.set((i, col), b);
+				self.set((j, col), a);
+			}
+		}
+	}
+
+	/// Troca as colunas `i` e `j` de lugar
+	/// Entra em panico se alguma das colunas estiver fora da matriz.
+	/// Por padrao troca com `get` e `set` as duas posiçoes de cada linha, em O(linhas * (get + set))
+	fn col_swap(&mut self, i: usize, j: usize) {
+		let size = self.size();
+		assert!(i < size.1 && j < size.1, "column out of bounds");
+		if i == j {
+			return;
+		}
+		for row in 0..size.0 {
+			let (a, b) = (self.get((row, i)), self.get((row, j)));
+			if a != b {
+				self.set((row, i), b);
+				self.set((row, j), a);
+			}
+		}
+	}
+
+	/// Multiplica a linha `row` por `scalar`
+	/// Entra em panico se a linha estiver fora da matriz.
+	/// Por padrao atualiza com `set` os valores nao nulos da linha, em O(colunas * get)
+	fn row_scale(&mut self, row: usize, scalar: f64) {
+		let size = self.size();
+		assert!(row < size.0, "row out of bounds");
+		for col in 0..size.1 {
+			let value = self.get((row, col));
+			if value != 0.0 {
+				self.set((row, col), value * scalar);
+			}
+		}
+	}
+
+	/// Soma `scalar` vezes a linha `source` na linha `target`, `A[target] += scalar * A[source]`
+	/// Junto com `row_swap` e `row_scale`, sao as operaçoes elementares da eliminaçao de Gauss.
+	/// Entra em panico se alguma das linhas estiver fora da matriz.
+	/// Por padrao atualiza com `set` as posiçoes de `target` onde `source` é nao nula, em O(colunas * get)
+	fn add_row_multiple(&mut self, target: usize, source: usize, scalar: f64) {
+		let size = self.size();
+		assert!(target < size.0 && source < size.0, "row out of bounds");
+		for col in 0..size.1 {
+			let value = self.get((source, col));
+			if value != 0.0 {
+				self.set((target, col), self.get((target, col)) + scalar * value);
+			}
+		}
+	}
+
+	/// Concatena as matrizes lado a lado, `[A B]`, com tamanho `(linhas, a.colunas + b.colunas)`
+	/// Entra em panico se as matrizes tiverem numeros de linhas diferentes.
+	/// Por padrao copia os valores de `iter_nnz`, deslocando as colunas de `b` em `a.colunas`
+	fn hstack(a: &Self, b: &Self) -> Self {
+		let (a_size, b_size) = (a.size(), b.size());
+		assert_eq!(a_size.0, b_size.0, "Incompatible matrices for hstack");
+		let mut c = Self::new((a_size.0, a_size.1 + b_size.1));
+		for (pos, value) in a.iter_nnz() {
+			c.set(pos, value);
+		}
+		for ((i, j), value) in b.iter_nnz() {
+			c.set((i, j + a_size.1), value);
+		}
+		c
+	}
+
+	/// Concatena as matrizes uma sobre a outra, `[A; B]`, com tamanho `(a.linhas + b.linhas, colunas)`
+	/// Entra em panico se as matrizes tiverem numeros de colunas diferentes.
+	/// Por padrao copia os valores de `iter_nnz`, deslocando as linhas de `b` em `a.linhas`
+	fn vstack(a: &Self, b: &Self) -> Self {
+		let (a_size, b_size) = (a.size(), b.size());
+		assert_eq!(a_size.1, b_size.1, "Incompatible matrices for vstack");
+		let mut c = Self::new((a_size.0 + b_size.0, a_size.1));
+		for (pos, value) in a.iter_nnz() {
+			c.set(pos, value);
+		}
+		for ((i, j), value) in b.iter_nnz() {
+			c.set((i + a_size.0, j), value);
+		}
+		c
+	}
+
+	/// Zera os valores com modulo menor ou igual a `threshold`, retornando quantos valores nao nulos foram zerados
+	/// O retorno permite acompanhar quanto preenchimento (fill-in) as operaçoes estao gerando.
+	fn threshold_sparsify(&mut self, threshold: f64) -> usize {
+		let mut removed = 0;
+		for (pos, value) in self.to_info().values {
+			if value != 0.0 && value.abs() <= threshold {
+				self.set(pos, 0.0);
+				removed += 1;
+			}
+		}
+		removed
+	}
+
+	/// Zera os valores com modulo menor ou igual a `EPSILON`, retornando quantos foram zerados
+	fn auto_sparsify(&mut self) -> usize {
+		self.threshold_sparsify(EPSILON)
+	}
+
+	/// Zera os valores com modulo estritamente menor que `eps`, como os residuos (`1e-15`) deixados por somas
+	/// de ponto flutuante que deveriam dar zero; valores com modulo igual a `eps` sao mantidos
+	/// Por padrao zera com `set` as posiçoes de `iter_nnz` abaixo do limiar
+	fn apply_threshold(&mut self, eps: f64) {
+		let small: Vec<Pair> = self.iter_nnz().filter(|(_, value)| *value != 0.0 && value.abs() < eps).map(|(pos, _)| pos).collect();
+		for pos in small {
+			self.set(pos, 0.0);
+		}
+	}
+
+	/// Remove o ruido numerico da matriz: zera os valores com modulo menor que `EPSILON` com `apply_threshold`
+	/// `mul` das `MapMatrix` chama `compress` no resultado; nas outras operaçoes ele deve ser chamado explicitamente
+	fn compress(&mut self) {
+		self.apply_threshold(EPSILON);
+	}
+
+	/// Define todos os elementos da matriz como `value`, mantendo o tamanho
+	/// Por padrao zera com `set` as posiçoes de `iter_nnz` se `value` for 0, e define todas as posiçoes caso contrario
+	fn fill(&mut self, value: f64) {
+		if value == 0.0 {
+			let positions: Vec<Pair> = self.iter_nnz().map(|(pos, _)| pos).collect();
+			for pos in positions {
+				self.set(pos, 0.0);
+			}
+			return;
+		}
+		let (rows, cols) = self.size();
+		for i in 0..rows {
+			for j in 0..cols {
+				self.set((i, j), value);
+			}
+		}
+	}
+
+	/// Define os `min(linhas, colunas)` elementos da diagonal principal como `value`, sem mudar o resto da matriz
+	fn fill_diagonal(&mut self, value: f64) {
+		let (rows, cols) = self.size();
+		for i in 0..rows.min(cols) {
+			self.set((i, i), value);
+		}
+	}
+
+	/// Zera todos os elementos da matriz, equivalente a `fill(0.0)`
+	fn zero_out(&mut self) {
+		self.fill(0.0);
+	}
+
+	/// Retorna uma matriz com os valores desta matriz apenas nas posiçoes onde `mask` é nao nula
+	/// Retorna `MatrixError::DimensionMismatch` se as matrizes tiverem tamanhos diferentes.
+	fn apply_mask(&self, mask: &Self) -> Result<Self, MatrixError> {
+		if self.size() != mask.size() {
+			return Err(MatrixError::DimensionMismatch { expected: self.size(), got: mask.size() });
+		}
+		let mut result = Self::new(self.size());
+		for (pos, value) in mask.to_info().values {
+			if value != 0.0 {
+				result.set(pos, self.get(pos));
+			}
+		}
+		Ok(result)
+	}
+
+	/// Reduz as dimensoes da matriz para `new_size`, descartando os valores fora dela, e retorna quantos valores nao nulos foram removidos
+	/// Retorna `MatrixError::DimensionMismatch` se `new_size` for maior que o tamanho atual em alguma dimensao.
+	fn shrink_size(&mut self, new_size: Pair) -> Result<usize, MatrixError> {
+		let size = self.size();
+		if new_size.0 > size.0 || new_size.1 > size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: size, got: new_size });
+		}
+		let info = self.to_info();
+		let total = info.values.iter().filter(|(_, value)| *value != 0.0).count();
+		let values: Vec<(Pair, f64)> = info.values.into_iter()
+			.filter(|((i, j), value)| *i < new_size.0 && *j < new_size.1 && *value != 0.0)
+			.collect();
+		let removed = total - values.len();
+		*self = Self::from_info(&MatrixInfo { size: new_size, values });
+		Ok(removed)
+	}
+
+	/// Reinterpreta a matriz com dimensoes `new_size`, mantendo os valores na mesma ordem linha por linha:
+	/// o valor em `(i, j)` vai para a posiçao de indice linear `i * colunas + j` da nova matriz
+	/// Retorna `MatrixError::DimensionMismatch` se `new_size` nao tiver o mesmo numero de elementos que a matriz.
+	/// Por padrao remapeia os valores de `iter_nnz` e reconstroi a matriz com `from_info`
+	fn reshape(self, new_size: Pair) -> Result<Self, MatrixError> {
+		let size = self.size();
+		if new_size.0 * new_size.1 != size.0 * size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: size, got: new_size });
+		}
+		let values = self.iter_nnz()
+			.map(|((i, j), value)| {
+				let flat = i * size.1 + j;
+				((flat / new_size.1, flat % new_size.1), value)
+			})
+			.collect();
+		Ok(Self::from_info(&MatrixInfo { size: new_size, values }))
+	}
+
+	/// Retorna a combinaçao linear `w_1 * A_1 + w_2 * A_2 + ... + w_n * A_n` das matrizes em `matrices`
+	/// Evita as n - 1 matrizes intermediarias de encadear `add` e `muls`. Para uma lista vazia retorna uma matriz 0x0.
+	/// Retorna `MatrixError::DimensionMismatch` se as matrizes nao tiverem todas o mesmo tamanho.
+	fn weighted_sum(matrices: &[(&Self, f64)]) -> Result<Self, MatrixError> {
+		let Some(((first, _), rest)) = matrices.split_first() else {
+			return Ok(Self::new((0, 0)));
+		};
+		let size = first.size();
+		if let Some((m, _)) = rest.iter().find(|(m, _)| m.size() != size) {
+			return Err(MatrixError::DimensionMismatch { expected: size, got: m.size() });
+		}
+		let mut result = Self::new(size);
+		for (m, weight) in matrices {
+			for (pos, value) in m.iter_nnz() {
+				if value != 0.0 {
+					result.set(pos, result.get(pos) + weight * value);
+				}
+			}
+		}
+		Ok(result)
+	}
+
+	/// Verifica se a matriz é simetrica definida positiva tentando a fatoraçao de Cholesky
+	///
+	/// A fatoraçao é feita sobre uma copia densa (O(n^2) de memoria mesmo para matrizes esparsas) em O(n^3),
+	/// sem precisar calcular os autovalores. Matrizes nao quadradas ou nao simetricas (a menos de `EPSILON`) retornam `false`.
+	fn is_positive_definite(&self) -> bool {
+		self.is_positive_semidefinite(0.0)
+	}
+
+	/// Verifica se a matriz é simetrica semidefinida positiva a menos de `tol`, tentando a fatoraçao de Cholesky de `A + tol * I`
+	/// Se a fatoraçao funciona, o menor autovalor de `A` é maior que `-tol`. Tem o mesmo custo de `is_positive_definite`.
+	fn is_positive_semidefinite(&self, tol: f64) -> bool {
+		if !self.is_square() {
+			return false;
+		}
+		let size = self.size();
+		let mut dense = factorization::to_dense(self);
+		let symmetric = (0..size.0).all(|i| (0..i).all(|j| (dense[i][j] - dense[j][i]).abs() <= EPSILON));
+		if !symmetric {
+			return false;
+		}
+		for (i, row) in dense.iter_mut().enumerate() {
+			row[i] += tol;
+		}
+		factorization::cholesky_dense(&dense).is_ok()
+	}
+
+	/// Matriz de Gram `K[i][j] = <a_vecs[i], b_vecs[j]>`, com tamanho `a_vecs.len() x b_vecs.len()`
+	/// Complexidade de tempo: O(na * nb * d), onde d é o tamanho dos vetores
+	/// Retorna `MatrixError::DimensionMismatch` se os vetores nao tiverem todos o mesmo tamanho.
+	fn inner_product_matrix(a_vecs: &[Vec<f64>], b_vecs: &[Vec<f64>]) -> Result<Self, MatrixError> {
+		let mut result = Self::new((a_vecs.len(), b_vecs.len()));
+		for (pos, value) in inner_products(a_vecs, b_vecs)? {
+			result.set(pos, value);
+		}
+		Ok(result)
+	}
+
+	/// Distribuiçoes de uma cadeia de Markov, cuja matriz de transiçao (estocastica por linhas) é esta, em cada um dos `steps` passos
+	/// Retorna `steps + 1` distribuiçoes, começando por `initial`; cada uma é a anterior (como vetor linha) vezes a matriz.
+	/// Cada passo percorre apenas os valores definidos, em O(k + n).
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada ou se `initial` nao tiver n elementos.
+	fn step_distributions(&self, initial: &[f64], steps: usize) -> Result<Vec<Vec<f64>>, MatrixError> {
+		let size = self.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		if initial.len() != size.0 {
+			return Err(MatrixError::DimensionMismatch { expected: (1, size.0), got: (1, initial.len()) });
+		}
+		let info = self.to_info();
+		let mut distributions = Vec::with_capacity(steps + 1);
+		distributions.push(initial.to_vec());
+		for _ in 0..steps {
+			let next = info.dot_left(distributions.last().unwrap());
+			distributions.push(next);
+		}
+		Ok(distributions)
+	}
+
+	/// Decomposiçao real de Schur `A = Q * T * Q^T`, retornando `(Q, T)`
+	///
+	/// `Q` é ortogonal e `T` é quase triangular superior, com blocos 2x2 na diagonal para cada par de
+	/// autovalores complexos conjugados. O calculo é feito sobre uma copia densa da matriz,
+	/// entao usa O(n^2) de memoria mesmo para matrizes esparsas, e O(n^3) de tempo.
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada e
+	/// `MatrixError::SingularMatrix` se a iteraçao QR nao convergir.
+	fn schur_form(&self) -> Result<(Self, Self), MatrixError> {
+		let size = self.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		let (q, t) = factorization::real_schur(&factorization::to_dense(self))?;
+		Ok((factorization::from_dense(&q), factorization::from_dense(&t)))
+	}
+
+	/// Reduz a matriz a forma de Hessenberg superior (zeros abaixo da primeira subdiagonal), retornando `(Q, H)` com `A = Q * H * Q^T`
+	///
+	/// Usa refletores de Householder aplicados dos dois lados, sobre uma copia densa da matriz,
+	/// ja que `H` em geral tem O(n^2) valores nao nulos mesmo quando `A` é esparsa.
+	/// Complexidade de tempo: O(n^3)
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada.
+	fn to_hessenberg(&self) -> Result<(Self, Self), MatrixError> {
+		let size = self.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		let mut h = factorization::to_dense(self);
+		let q = factorization::hessenberg(&mut h);
+		Ok((factorization::from_dense(&q), factorization::from_dense(&h)))
+	}
+
+	/// Inversa da matriz, pela fatoraçao LU com pivoteamento parcial, resolvendo `A * X = I` coluna por coluna
+	///
+	/// O calculo é feito sobre uma copia densa da matriz, e a inversa de uma matriz esparsa em geral é densa:
+	/// para resolver sistemas prefira `conjugate_gradient`, que nao forma a inversa.
+	/// Complexidade de tempo: O(n^3)
+	/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada e
+	/// `MatrixError::SingularMatrix` se algum pivo de `U` tiver modulo menor que `EPSILON`.
+	fn inverse(a: &Self) -> Result<Self, MatrixError> {
+		let size = a.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		let inverse = factorization::inverse_dense(&factorization::to_dense(a))?;
+		Ok(factorization::from_dense(&inverse))
+	}
+
+	/// Autovalores da matriz como pares `(real, imaginario)`, na ordem da diagonal da forma de Schur
+	/// Tem o mesmo custo e os mesmos erros de `schur_form`.
+	fn eigenvalues(&self) -> Result<Vec<(f64, f64)>, MatrixError> {
+		let size = self.size();
+		if size.0 != size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+		}
+		let (_, t) = factorization::real_schur(&factorization::to_dense(self))?;
+		Ok(factorization::schur_eigenvalues(&t))
+	}
 }
 
-#[derive(Clone)]
+/// Verifica se `a + b` esta definido para matrizes de tamanhos `a` e `b`, retornando o erro de `Matrix::try_add` se nao estiver
+pub(crate) fn check_add_sizes(a: Pair, b: Pair) -> Result<(), MatrixError> {
+	if a != b {
+		return Err(MatrixError::DimensionMismatch { expected: a, got: b });
+	}
+	Ok(())
+}
+
+/// Verifica se `a * b` esta definido para matrizes de tamanhos `a` e `b`, retornando o erro de `Matrix::try_mul` se nao estiver
+pub(crate) fn check_mul_sizes(a: Pair, b: Pair) -> Result<(), MatrixError> {
+	if a.1 != b.0 {
+		return Err(MatrixError::DimensionMismatch { expected: (a.1, b.1), got: b });
+	}
+	Ok(())
+}
+
+/// Verifica que `(row_ptr, col_idx, values)` descrevem uma matriz CSR de tamanho `size`: `row_ptr` com `linhas + 1`
+/// posiçoes, começando em 0 e nao decrescente, terminando no tamanho de `col_idx` e `values`, e as colunas
+/// de cada linha estritamente crescentes e menores que o numero de colunas
+/// Entra em panico se alguma dessas condiçoes falhar
+pub(crate) fn check_csr_arrays(size: Pair, row_ptr: &[usize], col_idx: &[usize], values: &[f64]) {
+	assert_eq!(row_ptr.len(), size.0 + 1, "row_ptr must have nrows + 1 elements");
+	assert_eq!(col_idx.len(), values.len(), "col_idx and values must have the same length");
+	assert!(row_ptr[0] == 0 && row_ptr[size.0] == values.len(), "row_ptr must start at 0 and end at values.len()");
+	for i in 0..size.0 {
+		assert!(row_ptr[i] <= row_ptr[i + 1] && row_ptr[i + 1] <= values.len(), "row_ptr must be non-decreasing and at most values.len()");
+		let cols = &col_idx[row_ptr[i]..row_ptr[i + 1]];
+		assert!(cols.windows(2).all(|w| w[0] < w[1]), "col_idx must be strictly increasing within each row");
+		assert!(cols.last().is_none_or(|j| *j < size.1), "column out of bounds");
+	}
+}
+
+/// Produtos internos `((i, j), <a_vecs[i], b_vecs[j]>)` de todos os pares de vetores, verificando que tem o mesmo tamanho
+pub(crate) fn inner_products<'a>(a_vecs: &'a [Vec<f64>], b_vecs: &'a [Vec<f64>]) -> Result<impl Iterator<Item = (Pair, f64)> + 'a, MatrixError> {
+	let len = a_vecs.first().or(b_vecs.first()).map_or(0, |v| v.len());
+	if let Some(v) = a_vecs.iter().chain(b_vecs).find(|v| v.len() != len) {
+		return Err(MatrixError::DimensionMismatch { expected: (1, len), got: (1, v.len()) });
+	}
+	Ok(a_vecs.iter().enumerate().flat_map(move |(i, a)| {
+		b_vecs.iter().enumerate().map(move |(j, b)| ((i, j), a.iter().zip(b).map(|(x, y)| x * y).sum()))
+	}))
+}
+
+/// Retorna `(I + A + ... + A^k, A^(k+1))`
+fn geometric_sum_and_power<M: Matrix>(a: &M, k: usize) -> (M, M) {
+	let n = a.size().0;
+	if k == 0 {
+		// muls por 1.0 produz uma copia de `a`
+		return (M::eye(n), M::muls(a, 1.0));
+	}
+	if k % 2 == 1 {
+		let (sum, power) = geometric_sum_and_power(a, (k - 1) / 2);
+		let factor = M::add(&M::eye(n), &power);
+		(M::mul(&factor, &sum), M::mul(&power, &power))
+	} else {
+		let (sum, power) = geometric_sum_and_power(a, k - 1);
+		(M::add(&sum, &power), M::mul(&power, a))
+	}
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Metadata for a matrix: its dimensions and the stored entries.
 ///
 /// `MatrixInfo` collects the essential information needed to describe a matrix:
 /// - `size`: a `Pair` describing the matrix dimensions (for example, row and column counts).
 /// - `values`: a `Vec<(Pair, f64)>` holding entries as `(position, value)`, where `position` is a `Pair` (row, column).
 pub struct MatrixInfo {
-    /// 
     pub size: Pair,
     pub values: Vec<(Pair, f64)>
 }
 
+/// Escreve a matriz como uma grade, uma linha por linha da matriz, com as colunas alinhadas a direita
+/// pela largura do maior valor de cada coluna. `get` retorna o valor de cada posiçao.
+/// Usado pelo `Display` de `MatrixInfo` e pelo `Debug` de `TableMatrix`.
+pub(crate) fn fmt_grid(f: &mut fmt::Formatter<'_>, size: Pair, get: impl Fn(Pair) -> f64) -> fmt::Result {
+	let cells: Vec<Vec<String>> = (0..size.0)
+		.map(|i| (0..size.1).map(|j| get((i, j)).to_string()).collect())
+		.collect();
+	let widths: Vec<usize> = (0..size.1)
+		.map(|j| cells.iter().map(|row| row[j].len()).max().unwrap_or(0))
+		.collect();
+	for row in cells {
+		let line: Vec<String> = row.iter().zip(&widths)
+			.map(|(cell, width)| format!("{:>width$}", cell, width = width))
+			.collect();
+		writeln!(f, "{}", line.join(" "))?;
+	}
+	Ok(())
+}
+
+/// Igualdade exata: mesmo tamanho e os mesmos valores, bit a bit, na mesma ordem
+/// Para comparar com tolerancia, use `approx_eq`
+impl PartialEq for MatrixInfo {
+	fn eq(&self, other: &Self) -> bool {
+		self.size == other.size
+			&& self.values.len() == other.values.len()
+			&& self.values.iter().zip(other.values.iter())
+				.all(|((pa, va), (pb, vb))| pa == pb && va.to_bits() == vb.to_bits())
+	}
+}
+
+/// Grade com todas as posiçoes da matriz, com `0` nas posiçoes sem valor
+impl fmt::Display for MatrixInfo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let values: HashMap<Pair, f64> = self.values.iter().copied().collect();
+		fmt_grid(f, self.size, |pos| values.get(&pos).copied().unwrap_or(0.0))
+	}
+}
+
+/// Conversao para JSON no formato `{"size": [linhas, colunas], "values": [[[linha, coluna], valor], ...]}`
+#[cfg(feature = "serde")]
+impl MatrixInfo {
+	/// Le uma matriz escrita por `to_json_string`
+	pub fn from_json_str(s: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(s)
+	}
+
+	/// Escreve a matriz como JSON
+	pub fn to_json_string(&self) -> String {
+		serde_json::to_string(self).expect("MatrixInfo sempre pode ser escrita como JSON")
+	}
+}
+
 impl MatrixInfo {
+	/// Imprime a matriz como uma grade (ver o `Display` de `MatrixInfo`)
 	pub fn print_values(&self) {
+		print!("{}", self);
+	}
+
+	/// Ordena os valores por posiçao (linha, coluna), em ordem lexicografica
+	pub fn sort_by_position(&mut self) {
+		self.values.sort_by_key(|(pos, _)| *pos);
+	}
+
+	/// Junta os valores com a mesma posiçao em um só, somando-os, e deixa os valores ordenados por posiçao
+	/// Complexidade de tempo: O(k log k)
+	pub fn dedup(&mut self) {
+		self.sort_by_position();
+		let mut values: Vec<(Pair, f64)> = Vec::with_capacity(self.values.len());
+		for (pos, value) in self.values.drain(..) {
+			match values.last_mut() {
+				Some((last, sum)) if *last == pos => *sum += value,
+				_ => values.push((pos, value)),
+			}
+		}
+		self.values = values;
+	}
+
+	/// Le uma matriz densa dada linha por linha, guardando apenas os valores com modulo maior que `EPSILON`,
+	/// em ordem de linha e coluna. O tamanho é `(linhas, colunas da primeira linha)`.
+	/// Retorna `MatrixError::DimensionMismatch` se alguma linha tiver um numero diferente de colunas.
+	/// Complexidade de tempo: O(n * m)
+	pub fn from_dense(data: &[Vec<f64>]) -> Result<MatrixInfo, MatrixError> {
+		let cols = data.first().map_or(0, Vec::len);
+		let mut values = Vec::new();
+		for (i, row) in data.iter().enumerate() {
+			if row.len() != cols {
+				return Err(MatrixError::DimensionMismatch { expected: (1, cols), got: (1, row.len()) });
+			}
+			for (j, value) in row.iter().enumerate() {
+				if value.abs() > EPSILON {
+					values.push(((i, j), *value));
+				}
+			}
+		}
+		Ok(MatrixInfo { size: (data.len(), cols), values })
+	}
+
+	/// Escreve os valores em uma grade densa de zeros; para posiçoes repetidas vale o ultimo valor
+	/// Entra em panico se algum valor estiver fora do tamanho da matriz.
+	/// Complexidade de tempo: O(n * m + k)
+	pub fn to_dense(&self) -> Vec<Vec<f64>> {
+		let mut data = vec![vec![0.0; self.size.1]; self.size.0];
+		for ((i, j), value) in self.values.iter() {
+			data[*i][*j] = *value;
+		}
+		data
+	}
+
+	/// Monta uma matriz a partir de uma grade de blocos, onde `None` representa um bloco nulo
+	///
+	/// Todas as linhas da grade devem ter o mesmo numero de blocos, todos os blocos de uma linha da grade
+	/// o mesmo numero de linhas e todos os blocos de uma coluna da grade o mesmo numero de colunas.
+	/// Uma linha ou coluna da grade só com `None` tem tamanho 0.
+	/// Cada valor é deslocado pela posiçao (linha inicial, coluna inicial) do seu bloco.
+	/// Retorna `MatrixError::DimensionMismatch` se a grade ou algum bloco tiver tamanho incompativel.
+	/// Complexidade de tempo: O(b + k), onde b é o numero de blocos e k o numero total de valores
+	pub fn from_block_matrix(blocks: &[Vec<Option<MatrixInfo>>]) -> Result<MatrixInfo, MatrixError> {
+		let grid_cols = blocks.first().map_or(0, |row| row.len());
+		let mut heights = vec![None; blocks.len()];
+		let mut widths = vec![None; grid_cols];
+		for (bi, row) in blocks.iter().enumerate() {
+			if row.len() != grid_cols {
+				return Err(MatrixError::DimensionMismatch { expected: (blocks.len(), grid_cols), got: (blocks.len(), row.len()) });
+			}
+			for (bj, block) in row.iter().enumerate() {
+				let Some(block) = block else { continue };
+				let expected = (*heights[bi].get_or_insert(block.size.0), *widths[bj].get_or_insert(block.size.1));
+				if block.size != expected {
+					return Err(MatrixError::DimensionMismatch { expected, got: block.size });
+				}
+			}
+		}
+		let offsets = |sizes: &[Option<usize>]| -> Vec<usize> {
+			sizes.iter().scan(0, |start, size| {
+				let current = *start;
+				*start += size.unwrap_or(0);
+				Some(current)
+			}).collect()
+		};
+		let (row_starts, col_starts) = (offsets(&heights), offsets(&widths));
+		let size = (
+			heights.iter().map(|h| h.unwrap_or(0)).sum(),
+			widths.iter().map(|w| w.unwrap_or(0)).sum(),
+		);
+		let mut values = Vec::with_capacity(blocks.iter().flatten().flatten().map(|b| b.values.len()).sum());
+		for (bi, row) in blocks.iter().enumerate() {
+			for (bj, block) in row.iter().enumerate() {
+				let Some(block) = block else { continue };
+				values.extend(block.values.iter().map(|((i, j), value)| ((row_starts[bi] + i, col_starts[bj] + j), *value)));
+			}
+		}
+		Ok(MatrixInfo { size, values })
+	}
+
+	/// Verifica se a matriz é estocastica por linhas: quadrada, sem valores negativos e com cada linha somando 1
+	pub fn is_stochastic(&self) -> bool {
+		if self.size.0 != self.size.1 {
+			return false;
+		}
+		let mut sums = vec![0.0; self.size.0];
 		for (pos, value) in self.values.iter() {
-			println!("{:?} = {}", pos, value);
+			if *value < 0.0 {
+				return false;
+			}
+			sums[pos.0] += value;
+		}
+		sums.iter().all(|sum| (sum - 1.0).abs() < EPSILON)
+	}
+
+	/// Produto `v * A` do vetor linha `v` (com `rows` elementos) pela matriz, percorrendo apenas os valores definidos
+	/// Complexidade de tempo: O(k + cols)
+	pub(crate) fn dot_left(&self, v: &[f64]) -> Vec<f64> {
+		let mut result = vec![0.0; self.size.1];
+		for ((i, j), value) in self.values.iter() {
+			result[*j] += v[*i] * value;
+		}
+		result
+	}
+
+	/// Distribuiçao estacionaria `pi = pi * P` da cadeia de Markov cuja matriz de transiçao (estocastica por linhas) é esta
+	///
+	/// Antes de iterar verifica que a distribuiçao é unica, com `nullity(P^T - I) = 1`, sobre uma copia densa da matriz
+	/// em O(n^3). Depois parte da distribuiçao uniforme e aplica a matriz ate que `||pi_novo - pi||_1 < EPSILON`, por no
+	/// maximo `steps` passos, cada um percorrendo apenas os valores definidos, em O(k).
+	/// Retorna `MatrixError::SingularMatrix` se o nucleo de `P^T - I` tiver dimensao maior que 1 (a cadeia nao é ergodica)
+	/// ou se a iteraçao nao convergir (por exemplo, em cadeias periodicas), e `MatrixError::DimensionMismatch` se a matriz
+	/// nao for quadrada.
+	pub fn random_walk_stationary_distribution(&self, steps: usize) -> Result<Vec<f64>, MatrixError> {
+		let n = self.size.0;
+		if n != self.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (n, n), got: self.size });
+		}
+		let p_t = TableMatrix::from_info(self).transposed();
+		if linalg::nullity(&TableMatrix::sub(&p_t, &TableMatrix::eye(n)), EPSILON) > 1 {
+			return Err(MatrixError::SingularMatrix);
+		}
+		let mut pi = vec![1.0 / n as f64; n];
+		for _ in 0..steps {
+			let next = self.dot_left(&pi);
+			let change: f64 = next.iter().zip(pi.iter()).map(|(a, b)| (a - b).abs()).sum();
+			pi = next;
+			if change < EPSILON {
+				return Ok(pi);
+			}
+		}
+		Err(MatrixError::SingularMatrix)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::*;
+
+	#[test]
+	fn stationary_distribution_of_cycle_is_uniform() {
+		let n = 5;
+		let cycle = MatrixInfo { size: (n, n), values: (0..n).map(|i| ((i, (i + 1) % n), 1.0)).collect() };
+		let pi = cycle.random_walk_stationary_distribution(100).unwrap();
+		for p in pi {
+			assert!((p - 1.0 / n as f64).abs() < EPSILON);
+		}
+	}
+
+	#[test]
+	fn stationary_distribution_of_reducible_chain_is_singular() {
+		let identity = MatrixInfo { size: (2, 2), values: [((0, 0), 1.0), ((1, 1), 1.0)].into_iter().collect() };
+		assert_eq!(identity.random_walk_stationary_distribution(100), Err(MatrixError::SingularMatrix));
+	}
+
+	#[test]
+	fn product_of_rank_one_matrices_is_rank_one() {
+		let (v, w, x, y) = ([1.0, -2.0, 3.0], [0.5, 4.0], [2.0, -1.0], [1.0, 0.0, -3.0, 2.0]);
+		let product = TableMatrix::mul(&TableMatrix::tensor_product_vec(&v, &w), &TableMatrix::tensor_product_vec(&x, &y));
+		let dot_wx: f64 = w.iter().zip(x.iter()).map(|(a, b)| a * b).sum();
+		let expected = TableMatrix::muls(&TableMatrix::tensor_product_vec(&v, &y), dot_wx);
+		assert_eq!(product.size(), (3, 4));
+		for i in 0..3 {
+			for j in 0..4 {
+				assert!((product.get((i, j)) - expected.get((i, j))).abs() < EPSILON);
+			}
+		}
+		assert!(product.is_rank_one());
+	}
+
+	#[test]
+	fn try_mul_reports_dimension_mismatch() {
+		let expected = Some(MatrixError::DimensionMismatch { expected: (3, 3), got: (4, 3) });
+		assert_eq!(TableMatrix::try_mul(&TableMatrix::new((3, 3)), &TableMatrix::new((4, 3))).err(), expected);
+		assert_eq!(crate::HashMapMatrix::try_mul(&crate::HashMapMatrix::new((3, 3)), &crate::HashMapMatrix::new((4, 3))).err(), expected);
+		assert_eq!(TableMatrix::try_add(&TableMatrix::new((3, 3)), &TableMatrix::new((4, 3))).err(), expected);
+	}
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for multiplication")]
+	fn mul_panics_on_dimension_mismatch() {
+		TableMatrix::mul(&TableMatrix::new((3, 3)), &TableMatrix::new((4, 3)));
+	}
+
+	#[test]
+	fn to_csr_arrays_matches_known_row_pointers() {
+		let a = crate::HashMapMatrix::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((2, 1), 5.0), ((0, 2), 2.0), ((0, 0), 1.0), ((2, 0), 4.0), ((2, 2), 6.0)],
+		});
+		let (row_ptr, col_idx, values) = a.to_csr_arrays();
+		assert_eq!(row_ptr, vec![0, 2, 2, 5]);
+		assert_eq!(col_idx, vec![0, 2, 0, 1, 2]);
+		assert_eq!(values, vec![1.0, 2.0, 4.0, 5.0, 6.0]);
+		let b = crate::HashMapMatrix::from_csr_arrays(3, 3, row_ptr, col_idx, values);
+		assert_eq!(b.to_csr_arrays(), a.to_csr_arrays());
+		assert!((0..3).all(|i| (0..3).all(|j| a.get((i, j)) == b.get((i, j)))));
+	}
+
+	#[test]
+	#[should_panic(expected = "row_ptr must be non-decreasing and at most values.len()")]
+	fn from_csr_arrays_rejects_row_pointer_past_values() {
+		TableMatrix::from_csr_arrays(2, 3, vec![0, 5, 3], vec![0, 1, 2], vec![1.0, 2.0, 3.0]);
+	}
+
+	fn assert_bandwidths<M: Matrix>() {
+		let n = 6;
+		let identity = M::eye(n);
+		assert_eq!((identity.lower_bandwidth(), identity.upper_bandwidth(), identity.bandwidth()), (0, 0, 0));
+		let laplacian: M = tridiagonal(n, 2.0, -1.0);
+		assert_eq!(laplacian.bandwidth(), 1);
+		assert!(laplacian.is_banded(1, 1) && !laplacian.is_banded(0, 1));
+		let mut full = M::new((n, n));
+		full.fill(1.0);
+		assert_eq!((full.lower_bandwidth(), full.upper_bandwidth(), full.bandwidth()), (n - 1, n - 1, n - 1));
+	}
+
+	matrix_tests!(bandwidth_of_identity_tridiagonal_and_full => assert_bandwidths);
+
+	fn contraction<M: Matrix>() -> M {
+		M::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 0.2), ((0, 1), 0.1), ((1, 1), 0.3), ((1, 2), 0.1), ((2, 0), 0.1), ((2, 2), 0.2)],
+		})
+	}
+
+	fn assert_geometric_sum<M: Matrix>() {
+		let a: M = contraction();
+		let expected = M::add(&M::add(&M::eye(3), &a), &M::mul(&a, &a));
+		assert_matrix_close(&a.geometric_sum(2).unwrap(), &expected, 1e-15);
+		let limit = M::inverse(&M::sub(&M::eye(3), &a)).unwrap();
+		assert_matrix_close(&a.geometric_sum(60).unwrap(), &limit, 1e-12);
+		assert_eq!(M::new((2, 3)).geometric_sum(3).err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	matrix_tests!(geometric_sum_converges_to_inverse_of_i_minus_a => assert_geometric_sum);
+
+	fn assert_frobenius_norm_of_difference<M: Matrix>() {
+		let a: M = contraction();
+		let b = M::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 1.5), ((0, 2), -2.0), ((1, 1), 0.3), ((2, 1), 4.0)],
+		});
+		for scale in [-1.0, 0.5, 3.0] {
+			let expected = M::add(&a, &M::map_elements(&b, |v| v * scale)).norm_frobenius();
+			assert!((a.frobenius_norm_of_difference(&b, scale).unwrap() - expected).abs() < EPSILON);
+		}
+		assert!((a.frobenius_norm_of_difference(&b, -1.0).unwrap() - M::sub(&a, &b).norm_frobenius()).abs() < EPSILON);
+		assert_eq!(a.frobenius_norm_of_difference(&M::new((3, 2)), 1.0), Err(MatrixError::DimensionMismatch { expected: (3, 3), got: (3, 2) }));
+	}
+
+	matrix_tests!(frobenius_norm_of_difference_matches_norm_of_sum => assert_frobenius_norm_of_difference);
+
+	#[test]
+	fn frobenius_norm_of_difference_monitors_jacobi_convergence() {
+		let a = TableMatrix::from(vec![vec![4.0, 1.0, 0.0], vec![1.0, 5.0, 2.0], vec![0.0, 2.0, 6.0]]);
+		let b = [1.0, 2.0, 3.0];
+		let column = |x: &[f64]| TableMatrix::from(x.iter().map(|&v| vec![v]).collect::<Vec<_>>());
+		let mut x = vec![0.0; 3];
+		let mut last_step = f64::INFINITY;
+		let mut iterations = 0;
+		// a expansao perde precisao para passos muito pequenos, entao a tolerancia fica bem acima de EPSILON
+		while last_step >= 1e-6 {
+			assert!(iterations < 200, "Jacobi did not converge");
+			let ax = a.mul_vec(&x);
+			let next: Vec<f64> = (0..3).map(|i| (b[i] - (ax[i] - a[(i, i)] * x[i])) / a[(i, i)]).collect();
+			let step = column(&next).frobenius_norm_of_difference(&column(&x), -1.0).unwrap();
+			assert!(iterations == 0 || step < last_step);
+			last_step = step;
+			x = next;
+			iterations += 1;
+		}
+		let ax = a.mul_vec(&x);
+		for i in 0..3 {
+			assert!((ax[i] - b[i]).abs() < 1e-5);
+		}
+	}
+
+	fn assert_padding<M: Matrix>() {
+		let info = MatrixInfo {
+			size: (2, 3),
+			values: vec![((0, 0), 1.0), ((0, 2), -2.0), ((1, 1), 3.5)],
+		};
+		let a = M::from_info(&info);
+		let padded = a.pad_symmetric(5).unwrap();
+		assert_eq!(padded.size(), (5, 5));
+		assert_eq!(padded.nnz(), 3);
+		assert_matrix_close(&padded.submatrix(0..2, 0..3), &a, 1e-15);
+		assert_eq!(a.pad_symmetric(3).unwrap().size(), (3, 3));
+		assert_eq!(a.pad_symmetric(2).err(), Some(MatrixError::DimensionMismatch { expected: (3, 3), got: (2, 2) }));
+
+		let embedded = a.embed_at((4, 6), (2, 3)).unwrap();
+		assert_eq!(embedded.nnz(), 3);
+		assert_matrix_close(&embedded.submatrix(2..4, 3..6), &a, 1e-15);
+		assert_eq!(a.embed_at((4, 5), (2, 3)).err(), Some(MatrixError::DimensionMismatch { expected: (4, 6), got: (4, 5) }));
+	}
+
+	matrix_tests!(pad_symmetric_keeps_the_matrix_in_the_top_left_corner => assert_padding);
+
+	fn assert_apply_mask<M: Matrix>() {
+		let a = M::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 4.0), ((0, 1), 1.0), ((1, 1), 5.0), ((1, 2), 2.0), ((2, 0), -1.0), ((2, 2), 8.0)],
+		});
+		let diagonal = a.apply_mask(&M::eye(3)).unwrap();
+		assert_matrix_close(&diagonal, &M::from_diagonal(&a.diagonal()), 1e-15);
+		assert_eq!(diagonal.nnz(), 3);
+
+		let pattern = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 1), 9.0), ((1, 0), 9.0), ((2, 0), -3.0)] });
+		let masked = a.apply_mask(&pattern).unwrap();
+		assert_eq!(masked.to_info().values.iter().filter(|(_, v)| *v != 0.0).count(), 2);
+		assert_eq!((masked.get((0, 1)), masked.get((1, 0)), masked.get((2, 0))), (1.0, 0.0, -1.0));
+
+		// precondicionador de Jacobi: D^-1 * A tem diagonal unitaria
+		let jacobi = M::mul(&M::inverse(&diagonal).unwrap(), &a);
+		assert_eq!(jacobi.diagonal(), vec![1.0; 3]);
+		assert_eq!(a.apply_mask(&M::eye(2)).err(), Some(MatrixError::DimensionMismatch { expected: (3, 3), got: (2, 2) }));
+	}
+
+	matrix_tests!(apply_mask_of_identity_is_the_diagonal => assert_apply_mask);
+
+	fn assert_weighted_sum<M: Matrix>() {
+		let a: M = contraction();
+		let b = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 0), 1.0), ((1, 2), -0.1), ((2, 1), 6.0)] });
+		let mut half = M::add(&a, &b);
+		M::muls_assign(&mut half, 0.5);
+		assert_matrix_close(&M::weighted_sum(&[(&a, 0.5), (&b, 0.5)]).unwrap(), &half, EPSILON);
+
+		let c = M::eye(3);
+		let expected = M::add(&M::add(&M::map_elements(&a, |v| 2.0 * v), &M::map_elements(&b, |v| -v)), &M::map_elements(&c, |v| 0.25 * v));
+		assert_matrix_close(&M::weighted_sum(&[(&a, 2.0), (&b, -1.0), (&c, 0.25)]).unwrap(), &expected, EPSILON);
+		assert_eq!(M::weighted_sum(&[]).unwrap().size(), (0, 0));
+		let wrong = M::new((3, 2));
+		assert_eq!(M::weighted_sum(&[(&a, 1.0), (&wrong, 1.0)]).err(), Some(MatrixError::DimensionMismatch { expected: (3, 3), got: (3, 2) }));
+	}
+
+	matrix_tests!(weighted_sum_of_halves_is_the_average => assert_weighted_sum);
+
+	fn block(size: Pair, values: &[(Pair, f64)]) -> Option<MatrixInfo> {
+		Some(MatrixInfo { size, values: values.to_vec() })
+	}
+
+	#[test]
+	fn from_block_matrix_2x2() {
+		let blocks = vec![
+			vec![block((2, 2), &[((0, 0), 1.0), ((1, 1), 2.0)]), None],
+			vec![None, block((1, 3), &[((0, 2), 3.0)])],
+		];
+		let info = MatrixInfo::from_block_matrix(&blocks).unwrap();
+		assert_eq!(info.size, (3, 5));
+		assert_eq!(sorted(info).values, vec![((0, 0), 1.0), ((1, 1), 2.0), ((2, 4), 3.0)]);
+	}
+
+	#[test]
+	fn from_block_matrix_3x3() {
+		let blocks = vec![
+			vec![block((1, 1), &[((0, 0), 1.0)]), None, block((1, 2), &[((0, 1), 2.0)])],
+			vec![None, block((2, 3), &[((1, 0), 3.0), ((0, 2), 4.0)]), None],
+			vec![block((2, 1), &[((1, 0), 5.0)]), None, block((2, 2), &[((0, 0), 6.0)])],
+		];
+		let info = MatrixInfo::from_block_matrix(&blocks).unwrap();
+		assert_eq!(info.size, (5, 6));
+		assert_eq!(sorted(info).values, vec![((0, 0), 1.0), ((0, 5), 2.0), ((1, 3), 4.0), ((2, 1), 3.0), ((3, 4), 6.0), ((4, 0), 5.0)]);
+	}
+
+	#[test]
+	fn from_block_matrix_rejects_incompatible_layouts() {
+		let ragged = vec![vec![block((1, 1), &[]), None], vec![block((1, 1), &[])]];
+		assert_eq!(MatrixInfo::from_block_matrix(&ragged).err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 1) }));
+		let heights = vec![vec![block((2, 1), &[]), block((3, 1), &[])]];
+		assert_eq!(MatrixInfo::from_block_matrix(&heights).err(), Some(MatrixError::DimensionMismatch { expected: (2, 1), got: (3, 1) }));
+		let widths = vec![vec![block((1, 2), &[])], vec![block((1, 4), &[])]];
+		assert_eq!(MatrixInfo::from_block_matrix(&widths).err(), Some(MatrixError::DimensionMismatch { expected: (1, 2), got: (1, 4) }));
+	}
+
+	fn assert_definiteness<M: Matrix>() {
+		let outer = M::outer(&[1.0, 2.0, -1.0], &[1.0, 2.0, -1.0]);
+		assert!(!outer.is_positive_definite());
+		assert!(outer.is_positive_semidefinite(1e-8));
+		let gram = M::add(&outer, &M::outer(&[0.0, 1.0, 3.0], &[0.0, 1.0, 3.0]));
+		assert!(gram.is_positive_semidefinite(1e-8));
+
+		let spd = M::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 2.0), ((0, 1), -1.0), ((1, 0), -1.0), ((1, 1), 2.0), ((1, 2), -1.0), ((2, 1), -1.0), ((2, 2), 2.0)],
+		});
+		assert!(spd.is_positive_definite());
+		assert!(spd.is_positive_semidefinite(0.0));
+
+		let indefinite = M::from_diagonal(&[1.0, -1.0, 1.0]);
+		assert!(!indefinite.is_positive_definite());
+		assert!(!indefinite.is_positive_semidefinite(1e-8));
+		let mut nonsymmetric = M::eye(3);
+		nonsymmetric.set((0, 2), 0.5);
+		assert!(!nonsymmetric.is_positive_definite());
+		assert!(!M::new((2, 3)).is_positive_semidefinite(1.0));
+	}
+
+	matrix_tests!(positive_definiteness_of_outer_products_and_indefinite_matrices => assert_definiteness);
+
+	fn assert_step_distributions<M: Matrix>() {
+		let uniform = M::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 0), 0.5), ((0, 1), 0.5), ((1, 0), 0.5), ((1, 1), 0.5)] });
+		let distributions = uniform.step_distributions(&[1.0, 0.0], 4).unwrap();
+		assert_eq!(distributions.len(), 5);
+		assert_eq!(distributions[0], vec![1.0, 0.0]);
+		for distribution in &distributions[1..] {
+			assert_eq!(distribution, &vec![0.5, 0.5]);
+		}
+
+		// cadeia deterministica 0 -> 1 -> 2 -> 0
+		let cycle = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 1), 1.0), ((1, 2), 1.0), ((2, 0), 1.0)] });
+		let distributions = cycle.step_distributions(&[0.2, 0.3, 0.5], 3).unwrap();
+		assert_eq!(distributions, vec![vec![0.2, 0.3, 0.5], vec![0.5, 0.2, 0.3], vec![0.3, 0.5, 0.2], vec![0.2, 0.3, 0.5]]);
+		assert_eq!(cycle.step_distributions(&[1.0, 0.0, 0.0], 0).unwrap(), vec![vec![1.0, 0.0, 0.0]]);
+		assert_eq!(cycle.step_distributions(&[1.0, 0.0], 1).err(), Some(MatrixError::DimensionMismatch { expected: (1, 3), got: (1, 2) }));
+		assert_eq!(M::new((2, 3)).step_distributions(&[1.0, 0.0], 1).err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	matrix_tests!(step_distributions_of_uniform_chain_settle_after_one_step => assert_step_distributions);
+
+	fn assert_inner_product_matrix<M: Matrix>() {
+		let a = vec![1.0, -2.0, 3.0];
+		let gram = M::inner_product_matrix(std::slice::from_ref(&a), std::slice::from_ref(&a)).unwrap();
+		assert_eq!(gram.size(), (1, 1));
+		assert_eq!(gram.get((0, 0)), 14.0);
+
+		let a_vecs = vec![a, vec![0.0, 1.0, 0.0]];
+		let b_vecs = vec![vec![2.0, 1.0, 0.0], vec![1.0, 0.0, 0.0], vec![0.0, 0.0, 1.0]];
+		let k = M::inner_product_matrix(&a_vecs, &b_vecs).unwrap();
+		let by_mul = M::mul(&M::from_info(&MatrixInfo::from_dense(&a_vecs).unwrap()), &M::from_info(&MatrixInfo::from_dense(&b_vecs).unwrap()).transposed());
+		assert_matrix_close(&k, &by_mul, 1e-15);
+		assert_eq!(k.get((0, 0)), 0.0);
+		assert_eq!(M::inner_product_matrix(&a_vecs, &[vec![1.0, 2.0]]).err(), Some(MatrixError::DimensionMismatch { expected: (1, 3), got: (1, 2) }));
+	}
+
+	fn assert_sparse_inner_product_matrix_skips_zeros<M: Matrix>() {
+		let k = M::inner_product_matrix(&[vec![1.0, 1.0]], &[vec![1.0, -1.0], vec![1.0, 0.0]]).unwrap();
+		assert_eq!(k.nnz(), 1);
+	}
+
+	matrix_tests!(inner_product_matrix_of_a_vector_with_itself_is_its_squared_norm => assert_inner_product_matrix);
+	matrix_tests!(sparse: sparse_inner_product_matrix_skips_zeros => assert_sparse_inner_product_matrix_skips_zeros);
+
+	fn assert_sub_and_neg<M: Matrix>() {
+		let a = M::from_info(&MatrixInfo { size: (2, 3), values: vec![((0, 0), 1.5), ((0, 2), -2.0), ((1, 1), 4.0)] });
+		let b = M::from_info(&MatrixInfo { size: (2, 3), values: vec![((0, 0), 0.5), ((1, 0), 3.0), ((1, 1), 4.0)] });
+		let zero = M::sub(&a, &a);
+		assert!(zero.to_info().values.iter().all(|(_, v)| *v == 0.0));
+		assert_matrix_close(&zero, &M::new((2, 3)), 1e-15);
+		assert_matrix_close(&M::sub(&a, &b), &M::add(&a, &M::neg(&b)), 1e-15);
+		assert_eq!((M::sub(&a, &b).get((0, 0)), M::sub(&a, &b).get((1, 0))), (1.0, -3.0));
+		assert_matrix_close(&M::neg(&M::neg(&a)), &a, 1e-15);
+		assert_eq!(M::neg(&a).get((0, 2)), 2.0);
+	}
+
+	fn assert_sparse_sub_of_itself_is_empty<M: Matrix>() {
+		let a = M::from_diagonal(&[1.0, 2.0]);
+		assert_eq!(M::sub(&a, &a).nnz(), 0);
+	}
+
+	matrix_tests!(sub_and_neg_identities => assert_sub_and_neg);
+	matrix_tests!(sparse: sparse_sub_of_itself_is_empty => assert_sparse_sub_of_itself_is_empty);
+
+	#[test]
+	fn sub_and_neg_identities_of_csr_matrix() {
+		assert_sub_and_neg::<crate::CsrMatrix>();
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn json_has_the_documented_shape_and_round_trips() {
+		let json = sample_info().to_json_string();
+		assert_eq!(json, r#"{"size":[3,4],"values":[[[2,3],-0.125],[[0,1],2.5],[[2,0],0.001],[[0,3],7.0]]}"#);
+		assert_eq!(MatrixInfo::from_json_str(&json).unwrap(), sample_info());
+		let empty = MatrixInfo { size: (0, 0), values: vec![] };
+		assert_eq!(MatrixInfo::from_json_str(&empty.to_json_string()).unwrap(), empty);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn from_json_str_rejects_malformed_input() {
+		for json in ["", "{", r#"{"size":[3,4]}"#, r#"{"size":[3],"values":[]}"#, r#"{"size":[3,4],"values":[[[0,1]]]}"#, r#"{"size":[-1,4],"values":[]}"#] {
+			assert!(MatrixInfo::from_json_str(json).is_err(), "{json}");
+		}
+	}
+
+	#[test]
+	fn display_of_matrix_info_is_an_aligned_grid() {
+		let info = MatrixInfo { size: (2, 3), values: vec![((1, 2), -12.5), ((0, 0), 3.0), ((1, 0), 10.0)] };
+		assert_eq!(info.to_string(), " 3 0     0\n10 0 -12.5\n");
+		assert_eq!(MatrixInfo { size: (0, 0), values: vec![] }.to_string(), "");
+	}
+
+	fn assert_trace<M: Matrix + Clone>() {
+		let a: M = contraction();
+		let b = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 0), 2.0), ((1, 0), 7.0), ((2, 2), -1.0)] });
+		assert!((a.trace() - 0.7).abs() < 1e-15);
+		assert!((M::add(&a, &b).trace() - (a.trace() + b.trace())).abs() < 1e-15);
+		assert_eq!(a.clone().transposed().trace(), a.trace());
+		assert_eq!(b.diagonal(), vec![2.0, 0.0, -1.0]);
+
+		let wide = M::from_info(&MatrixInfo { size: (2, 4), values: vec![((0, 0), 1.0), ((1, 1), 2.0), ((1, 3), 5.0)] });
+		assert_eq!(wide.trace(), 3.0);
+		assert_eq!(wide.diagonal(), vec![1.0, 2.0]);
+		assert_eq!(wide.clone().transposed().diagonal(), vec![1.0, 2.0]);
+	}
+
+	matrix_tests!(trace_is_linear_and_invariant_under_transpose => assert_trace);
+
+	fn assert_eye<M: Matrix>() {
+		let a = M::from_info(&MatrixInfo { size: (3, 2), values: vec![((0, 0), 1.0), ((1, 1), -2.0), ((2, 0), 3.5)] });
+		assert_matrix_close(&M::mul(&M::eye(3), &a), &a, 1e-15);
+		assert_matrix_close(&M::mul(&a, &M::eye(2)), &a, 1e-15);
+		let eye = M::eye(3);
+		assert_eq!(eye.size(), (3, 3));
+		assert_eq!(eye.diagonal(), vec![1.0; 3]);
+		assert_eq!(eye.trace(), 3.0);
+		let d = M::from_diagonal(&[2.0, 0.0, -1.0]);
+		assert_eq!(d.size(), (3, 3));
+		assert_eq!(d.diagonal(), vec![2.0, 0.0, -1.0]);
+		assert_eq!(M::mul(&d, &a).get((2, 0)), -3.5);
+		assert_eq!(M::eye(0).size(), (0, 0));
+	}
+
+	fn assert_sparse_eye_stores_only_the_diagonal<M: Matrix>() {
+		assert_eq!(M::eye(1000).nnz(), 1000);
+		assert_eq!(M::from_diagonal(&[2.0, 0.0, -1.0]).nnz(), 2);
+	}
+
+	matrix_tests!(eye_is_the_multiplicative_identity => assert_eye);
+	matrix_tests!(sparse: sparse_eye_stores_only_the_diagonal => assert_sparse_eye_stores_only_the_diagonal);
+
+	fn assert_hadamard<M: Matrix>() {
+		let a: M = contraction();
+		let b = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 0), 2.0), ((0, 1), -3.0), ((2, 1), 6.0), ((2, 2), 0.5)] });
+		let ab = M::hadamard(&a, &b);
+		assert_matrix_close(&ab, &M::hadamard(&b, &a), 1e-15);
+		let expected = M::from_info(&MatrixInfo { size: (3, 3), values: vec![((0, 0), 0.4), ((0, 1), -0.3), ((2, 2), 0.1)] });
+		assert_matrix_close(&ab, &expected, 1e-15);
+		assert_matrix_close(&M::hadamard(&a, &M::eye(3)), &M::from_diagonal(&a.diagonal()), 1e-15);
+	}
+
+	matrix_tests!(hadamard_is_commutative_and_extracts_the_diagonal => assert_hadamard);
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for hadamard product")]
+	fn hadamard_panics_on_dimension_mismatch() {
+		crate::HashMapMatrix::hadamard(&Matrix::new((2, 3)), &Matrix::new((3, 2)));
+	}
+
+	fn assert_stacks<M: Matrix + Clone>() {
+		let a = M::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 0), 1.0), ((1, 0), 2.0)] });
+		let b = M::from_info(&MatrixInfo { size: (2, 3), values: vec![((0, 2), 3.0), ((1, 1), 4.0)] });
+		let c = M::from_info(&MatrixInfo { size: (3, 2), values: vec![((0, 1), 5.0), ((2, 0), 6.0)] });
+		for a in [a.clone(), a.transposed()] {
+			let h = M::hstack(&a, &b);
+			assert_eq!(h.size(), (2, 5));
+			assert_matrix_close(&h.submatrix(0..2, 0..2), &a, 1e-15);
+			assert_matrix_close(&h.submatrix(0..2, 2..5), &b, 1e-15);
+			assert_eq!(h.nnz(), a.nnz() + b.nnz());
+
+			let v = M::vstack(&a, &c);
+			assert_eq!(v.size(), (5, 2));
+			assert_matrix_close(&v.submatrix(0..2, 0..2), &a, 1e-15);
+			assert_matrix_close(&v.submatrix(2..5, 0..2), &c, 1e-15);
+		}
+	}
+
+	matrix_tests!(stacked_blocks_are_recovered_by_submatrix => assert_stacks);
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for hstack")]
+	fn hstack_panics_on_row_mismatch() {
+		TableMatrix::hstack(&TableMatrix::new((2, 2)), &TableMatrix::new((3, 2)));
+	}
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for vstack")]
+	fn vstack_panics_on_column_mismatch() {
+		crate::TreeMatrix::vstack(&Matrix::new((2, 2)), &Matrix::new((2, 3)));
+	}
+
+	fn assert_submatrix<M: Matrix + Clone>() {
+		let mut a = M::new((4, 5));
+		for i in 0..4 {
+			for j in 0..5 {
+				if (i + j) % 3 != 0 {
+					a.set((i, j), (i * 5 + j) as f64);
+				}
+			}
+		}
+		let sub = a.submatrix(1..3, 2..5);
+		assert_eq!(sub.size(), (2, 3));
+		assert_eq!((sub.get((0, 1)), sub.get((1, 0)), sub.get((0, 0))), (8.0, 12.0, 0.0));
+		assert_eq!(a.submatrix(2..2, 0..5).size(), (0, 5));
+
+		for k in 0..=4 {
+			let mut b = a.clone();
+			b.set_submatrix(0, 0, &a.submatrix(0..k, 0..k));
+			assert_matrix_close(&b, &a, 1e-15);
+		}
+
+		let patch = M::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 0), -1.0)] });
+		let mut b = a.clone();
+		b.set_submatrix(2, 3, &patch);
+		assert_matrix_close(&b.submatrix(2..4, 3..5), &patch, 1e-15);
+		assert_matrix_close(&b.submatrix(0..2, 0..5), &a.submatrix(0..2, 0..5), 1e-15);
+		assert_matrix_close(&b.submatrix(2..4, 0..3), &a.submatrix(2..4, 0..3), 1e-15);
+	}
+
+	matrix_tests!(set_submatrix_of_own_submatrix_is_a_no_op => assert_submatrix);
+
+	#[test]
+	#[should_panic(expected = "submatrix out of bounds")]
+	fn submatrix_out_of_bounds_panics() {
+		crate::HashMapMatrix::new((3, 3)).submatrix(0..2, 1..4);
+	}
+
+	#[test]
+	#[should_panic(expected = "submatrix out of bounds")]
+	fn set_submatrix_out_of_bounds_panics() {
+		TableMatrix::new((3, 3)).set_submatrix(2, 0, &TableMatrix::new((2, 2)));
+	}
+
+	fn assert_norms<M: Matrix + Clone>() {
+		for n in [1, 4, 9] {
+			let eye = M::eye(n);
+			assert!((eye.norm_frobenius() - (n as f64).sqrt()).abs() < 1e-15);
+			assert_eq!(eye.norm_one(), 1.0);
+			assert_eq!(eye.norm_inf(), 1.0);
+		}
+		let a = M::from_info(&MatrixInfo { size: (2, 3), values: vec![((0, 0), 1.0), ((0, 2), -2.0), ((1, 0), 3.0), ((1, 1), 4.0)] });
+		assert_eq!(a.norm_frobenius(), 30f64.sqrt());
+		assert_eq!(a.norm_one(), 4.0);
+		assert_eq!(a.norm_inf(), 7.0);
+		let t = a.transposed();
+		assert_eq!((t.norm_one(), t.norm_inf()), (7.0, 4.0));
+		assert_eq!(M::new((3, 3)).norm_inf(), 0.0);
+	}
+
+	matrix_tests!(norms_of_identity_and_a_known_matrix => assert_norms);
+
+	fn assert_map_elements<M: Matrix + Clone>() {
+		let a: M = contraction();
+		assert_matrix_close(&M::map_elements(&a, |x| x * 2.0), &M::muls(&a, 2.0), 1e-15);
+		let clamped = M::map_elements(&a, |x| if x > 0.15 { x } else { 0.0 });
+		assert_eq!(clamped.get((0, 1)), 0.0);
+		assert_eq!(clamped.get((1, 1)), 0.3);
+		assert_eq!(clamped.to_info().values.iter().filter(|(_, v)| *v != 0.0).count(), 3);
+
+		let t = a.clone().transposed();
+		let by_position = M::map_nnz(&t, |(i, j), x| if i == j { x } else { -x });
+		assert_eq!((by_position.get((0, 0)), by_position.get((1, 0)), by_position.get((0, 2))), (0.2, -0.1, -0.1));
+	}
+
+	fn assert_sparse_map_elements_drops_zeros<M: Matrix>() {
+		let sparse: M = contraction();
+		assert_eq!(M::map_elements(&sparse, |x| if x < 0.25 { 0.0 } else { x }).nnz(), 1);
+	}
+
+	matrix_tests!(map_elements_matches_muls => assert_map_elements);
+	matrix_tests!(sparse: sparse_map_elements_drops_zeros => assert_sparse_map_elements_drops_zeros);
+
+	#[test]
+	fn map_elements_fills_zeros_of_a_dense_matrix() {
+		let dense = TableMatrix::map_elements(&TableMatrix::new((2, 2)), |x| x + 1.0);
+		assert_eq!(dense, TableMatrix::from(vec![vec![1.0; 2]; 2]));
+	}
+
+	#[test]
+	#[should_panic(expected = "f(0.0) != 0.0")]
+	fn map_elements_that_fills_zeros_panics_on_sparse_matrices() {
+		let sparse: crate::HashMapMatrix = contraction();
+		crate::HashMapMatrix::map_elements(&sparse, |x| x + 1.0);
+	}
+
+	fn assert_line_sums<M: Matrix + Clone>() {
+		assert_eq!(M::eye(4).row_sums(), vec![1.0; 4]);
+		assert_eq!(M::eye(4).col_sums(), vec![1.0; 4]);
+		assert_eq!(M::new((3, 5)).col_sums(), vec![0.0; 5]);
+		assert_eq!(M::new((3, 5)).row_max(), vec![0.0; 3]);
+
+		let a = M::from_info(&MatrixInfo { size: (2, 3), values: vec![((0, 0), 1.0), ((0, 2), -4.0), ((1, 0), 3.0), ((1, 1), 2.0)] });
+		assert_eq!(a.row_sums(), vec![-3.0, 5.0]);
+		assert_eq!(a.col_sums(), vec![4.0, 2.0, -4.0]);
+		assert_eq!(a.row_max(), vec![4.0, 3.0]);
+		assert_eq!(a.col_max(), vec![3.0, 2.0, 4.0]);
+		let t = a.transposed();
+		assert_eq!(t.row_sums(), vec![4.0, 2.0, -4.0]);
+		assert_eq!(t.col_max(), vec![4.0, 3.0]);
+	}
+
+	matrix_tests!(row_and_column_sums_and_maxima => assert_line_sums);
+
+	fn assert_power<M: Matrix>() {
+		for seed in 0..5 {
+			let a: M = random_square(5, seed);
+			assert_matrix_close(&M::power(&a, 0), &M::eye(5), 1e-15);
+			assert_matrix_close(&M::power(&a, 1), &a, 1e-15);
+			assert_matrix_close(&M::power(&a, 3), &M::mul(&M::mul(&a, &a), &a), 1e-12);
+			let a2 = M::mul(&a, &a);
+			assert_matrix_close(&M::power(&a, 6), &M::mul(&M::mul(&a2, &a2), &a2), 1e-12);
+		}
+	}
+
+	matrix_tests!(power_matches_repeated_multiplication => assert_power);
+
+	#[test]
+	#[should_panic(expected = "power requires a square matrix")]
+	fn power_of_rectangular_matrix_panics() {
+		TableMatrix::power(&TableMatrix::new((2, 3)), 2);
+	}
+
+	fn assert_structure<M: Matrix + Clone>() {
+		let eps = 1e-8;
+		for seed in 0..5 {
+			let r: M = random_square(6, seed);
+			let symmetric = M::add(&r, &r.clone().transposed());
+			assert!(symmetric.is_symmetric(eps));
+			assert!(!r.is_symmetric(eps));
+			let mut nearly = symmetric.clone();
+			nearly.set((0, 5), symmetric.get((0, 5)) + eps / 10.0);
+			assert!(nearly.is_symmetric(eps));
+			nearly.set((0, 5), symmetric.get((0, 5)) + eps * 10.0);
+			assert!(!nearly.is_symmetric(eps));
+			assert!(!symmetric.is_diagonal(eps));
+		}
+		let mut d = M::from_diagonal(&[1.0, -2.0, 3.0]);
+		assert!(d.is_diagonal(eps) && d.is_symmetric(eps) && d.is_square());
+		d.set((2, 0), eps / 10.0);
+		assert!(d.is_diagonal(eps));
+		d.set((2, 0), 1.0);
+		assert!(!d.is_diagonal(eps) && !d.is_symmetric(eps));
+		let wide = M::new((2, 3));
+		assert!(!wide.is_square() && !wide.is_symmetric(eps) && !wide.is_diagonal(eps));
+	}
+
+	matrix_tests!(symmetric_and_diagonal_predicates => assert_structure);
+
+	#[test]
+	fn dedup_sums_repeated_positions() {
+		let mut info = MatrixInfo { size: (2, 2), values: vec![((0, 0), 1.0), ((0, 0), 2.0)] };
+		info.dedup();
+		assert_eq!(info.values, vec![((0, 0), 3.0)]);
+
+		let mut info = MatrixInfo { size: (3, 3), values: vec![((2, 1), 1.0), ((0, 2), 4.0), ((2, 1), -0.5), ((1, 0), 2.0), ((0, 2), 1.0)] };
+		info.dedup();
+		assert_eq!(info.values, vec![((0, 2), 5.0), ((1, 0), 2.0), ((2, 1), 0.5)]);
+	}
+
+	#[test]
+	fn sort_by_position_is_canonical() {
+		let mut a = MatrixInfo { size: (3, 3), values: vec![((2, 0), 1.0), ((0, 2), 2.0), ((1, 1), 3.0), ((0, 0), 4.0)] };
+		let mut b = MatrixInfo { size: (3, 3), values: vec![((1, 1), 3.0), ((0, 0), 4.0), ((2, 0), 1.0), ((0, 2), 2.0)] };
+		assert_ne!(a, b);
+		a.sort_by_position();
+		b.sort_by_position();
+		assert_eq!(a, b);
+		assert_eq!(a.values.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), vec![(0, 0), (0, 2), (1, 1), (2, 0)]);
+		let sorted = a.clone();
+		a.sort_by_position();
+		a.sort_by_position();
+		assert_eq!(a, sorted);
+		assert_eq!(a, b);
+	}
+
+	#[test]
+	fn dense_conversions_round_trip() {
+		let info = MatrixInfo { size: (3, 4), values: vec![((0, 1), 2.5), ((1, 3), -1.0), ((2, 0), 1e-3)] };
+		let dense = info.to_dense();
+		assert_eq!(dense, vec![vec![0.0, 2.5, 0.0, 0.0], vec![0.0, 0.0, 0.0, -1.0], vec![1e-3, 0.0, 0.0, 0.0]]);
+		assert_eq!(MatrixInfo::from_dense(&dense).unwrap(), info);
+		assert_eq!(MatrixInfo::from_dense(&[vec![1.0, 1e-12], vec![0.0, 0.0]]).unwrap().values, vec![((0, 0), 1.0)]);
+		assert_eq!(MatrixInfo::from_dense(&[vec![1.0, 2.0], vec![3.0]]).err(), Some(MatrixError::DimensionMismatch { expected: (1, 2), got: (1, 1) }));
+		assert_eq!(MatrixInfo::from_dense(&[]).unwrap().size, (0, 0));
+	}
+
+	#[test]
+	fn from_dense_of_identity_matches_eye() {
+		let n = 4;
+		let identity: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+		let info = MatrixInfo::from_dense(&identity).unwrap();
+		assert_eq!(info, crate::TreeMatrix::from_info(&crate::TreeMatrix::eye(n).to_info()).to_info());
+		let from_map = crate::HashMapMatrix::from_info(&crate::HashMapMatrix::eye(n).to_info()).to_info();
+		assert_eq!(from_map.size, info.size);
+		assert_eq!(sorted(from_map).values, info.values);
+	}
+
+	fn assert_kron<M: Matrix>() {
+		let a_info = MatrixInfo { size: (2, 3), values: vec![((0, 0), 1.0), ((0, 2), -2.0), ((1, 1), 3.0)] };
+		let a = M::from_info(&a_info);
+		let block_diag = MatrixInfo::from_block_matrix(&[vec![Some(a_info.clone()), None], vec![None, Some(a_info.clone())]]).unwrap();
+		assert_matrix_close(&M::kron(&M::eye(2), &a), &M::from_info(&block_diag), 1e-15);
+		assert_matrix_close(&M::kron(&a, &M::eye(1)), &a, 1e-15);
+
+		let b = M::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 1), 4.0), ((1, 0), 5.0)] });
+		let k = M::kron(&a, &b);
+		assert_eq!(k.size(), (2 * 2, 3 * 2));
+		// A[i][j] * B[r][s] fica em (i * 2 + r, j * 2 + s)
+		assert_eq!(k.get((1, 4)), -2.0 * 5.0);
+		assert_eq!(k.get((2, 3)), 3.0 * 4.0);
+		assert_eq!(k.to_info().values.iter().filter(|(_, v)| *v != 0.0).count(), 3 * 2);
+		assert_matrix_close(&M::tensor_product_matrix(&a, &b), &k, 1e-15);
+	}
+
+	matrix_tests!(kron_with_identity_is_block_diagonal => assert_kron);
+
+	fn assert_outer<M: Matrix>() {
+		let e = |k: usize, n: usize| -> Vec<f64> { (0..n).map(|i| if i == k { 1.0 } else { 0.0 }).collect() };
+		for i in 0..3 {
+			for j in 0..4 {
+				let m = M::outer(&e(i, 3), &e(j, 4));
+				assert_eq!(m.size(), (3, 4));
+				let single = M::from_info(&MatrixInfo { size: (3, 4), values: vec![((i, j), 1.0)] });
+				assert_matrix_close(&m, &single, 1e-15);
+				assert_eq!(m.to_info().values.iter().filter(|(_, v)| *v != 0.0).count(), 1);
+			}
+		}
+
+		let (u, v) = ([1.0, 0.0, -2.0], [3.0, 0.5]);
+		let mut a = M::from_info(&MatrixInfo { size: (3, 2), values: vec![((0, 0), 1.0), ((1, 1), 7.0)] });
+		let expected = M::add(&a, &M::muls(&M::outer(&u, &v), 2.0));
+		M::rank_one_update(&mut a, 2.0, &u, &v);
+		assert_matrix_close(&a, &expected, 1e-15);
+		assert_eq!((a.get((0, 0)), a.get((1, 1)), a.get((2, 1))), (7.0, 7.0, -2.0));
+	}
+
+	fn assert_sparse_outer_skips_zeros<M: Matrix>() {
+		assert_eq!(M::outer(&[1.0, 0.0, 2.0], &[0.0, 3.0]).nnz(), 2);
+	}
+
+	matrix_tests!(outer_of_unit_vectors_has_a_single_one => assert_outer);
+	matrix_tests!(sparse: sparse_outer_skips_zeros => assert_sparse_outer_skips_zeros);
+
+	#[test]
+	#[should_panic(expected = "Incompatible vectors for rank_one_update")]
+	fn rank_one_update_panics_on_wrong_vector_sizes() {
+		let mut a = crate::HashMapMatrix::new((2, 2));
+		crate::HashMapMatrix::rank_one_update(&mut a, 1.0, &[1.0, 2.0, 3.0], &[1.0, 2.0]);
+	}
+
+	fn assert_reshape<M: Matrix + Clone>() {
+		let mut a = M::new((4, 6));
+		for i in 0..4 {
+			for j in 0..6 {
+				a.set((i, j), (i * 6 + j) as f64);
+			}
+		}
+		let r = a.clone().reshape((6, 4)).unwrap();
+		assert_eq!(r.size(), (6, 4));
+		for i in 0..4 {
+			for j in 0..6 {
+				let flat = i * 6 + j;
+				assert_eq!(r.get((flat / 4, flat % 4)), flat as f64);
+			}
+		}
+		let column = a.clone().transposed().reshape((24, 1)).unwrap();
+		assert_eq!((column.get((1, 0)), column.get((4, 0)), column.get((23, 0))), (6.0, 1.0, 23.0));
+		assert_eq!(a.reshape((5, 5)).err(), Some(MatrixError::DimensionMismatch { expected: (4, 6), got: (5, 5) }));
+	}
+
+	matrix_tests!(reshape_keeps_the_row_major_order => assert_reshape);
+
+	#[test]
+	fn reshape_of_csr_matrix_keeps_the_row_major_order() {
+		assert_reshape::<crate::CsrMatrix>();
+	}
+
+	fn assert_elementary_operations<M: Matrix + Clone>() {
+		let info = MatrixInfo { size: (3, 4), values: vec![((0, 0), 1.0), ((0, 3), 2.0), ((1, 1), 3.0), ((2, 0), 4.0), ((2, 2), 5.0)] };
+		let a = M::from_info(&info);
+
+		let mut b = a.clone();
+		b.row_swap(0, 2);
+		assert_eq!((b.get((0, 0)), b.get((0, 2)), b.get((0, 3))), (4.0, 5.0, 0.0));
+		assert_eq!((b.get((2, 0)), b.get((2, 2)), b.get((2, 3))), (1.0, 0.0, 2.0));
+		b.row_swap(0, 2);
+		assert_eq!(sorted(b.to_info()).values, sorted(a.to_info()).values);
+		b.row_swap(1, 1);
+		assert_eq!(sorted(b.to_info()).values, sorted(a.to_info()).values);
+
+		b.col_swap(0, 3);
+		assert_eq!((b.get((0, 0)), b.get((0, 3)), b.get((2, 0)), b.get((2, 3))), (2.0, 1.0, 0.0, 4.0));
+		b.col_swap(3, 0);
+		assert_eq!(sorted(b.to_info()).values, sorted(a.to_info()).values);
+
+		b.row_scale(2, -2.0);
+		assert_eq!((b.get((2, 0)), b.get((2, 1)), b.get((2, 2))), (-8.0, 0.0, -10.0));
+		b.add_row_multiple(2, 0, 8.0);
+		assert_eq!((b.get((2, 0)), b.get((2, 2)), b.get((2, 3))), (0.0, -10.0, 16.0));
+		assert_eq!((b.get((0, 0)), b.get((0, 3))), (1.0, 2.0));
+	}
+
+	matrix_tests!(elementary_row_and_column_operations => assert_elementary_operations);
+
+	#[test]
+	#[should_panic(expected = "row out of bounds")]
+	fn row_swap_out_of_bounds_panics() {
+		crate::HashMapMatrix::new((2, 2)).row_swap(0, 2);
+	}
+
+	fn assert_aggregates<M: Matrix>() {
+		for n in [1, 3, 6] {
+			let eye = M::eye(n);
+			assert_eq!(eye.sum(), n as f64);
+			assert_eq!(eye.sum_sq(), n as f64);
+			let negated = M::muls(&eye, -1.0);
+			assert_eq!(negated.min_element(), -1.0);
+			assert_eq!(negated.max_element(), if n == 1 { -1.0 } else { 0.0 });
+		}
+		let full = M::from_info(&MatrixInfo { size: (1, 2), values: vec![((0, 0), 2.0), ((0, 1), 5.0)] });
+		assert_eq!((full.min_element(), full.max_element()), (2.0, 5.0));
+		let a: M = random_square(5, 1057);
+		assert!((a.sum_sq() - a.norm_frobenius().powi(2)).abs() < 1e-12);
+		let empty = M::new((0, 3));
+		assert_eq!((empty.sum(), empty.max_element(), empty.min_element()), (0.0, f64::NEG_INFINITY, f64::INFINITY));
+	}
+
+	matrix_tests!(element_aggregates_count_the_implicit_zeros => assert_aggregates);
+
+	fn assert_fill<M: Matrix>() {
+		let (rows, cols) = (4, 6);
+		let mut a = M::new((rows, cols));
+		a.set((1, 2), -3.0);
+		a.fill(1.0);
+		assert_eq!(a.sum(), (rows * cols) as f64);
+		assert_eq!((a.min_element(), a.max_element()), (1.0, 1.0));
+
+		a.fill_diagonal(5.0);
+		assert_eq!(a.sum(), (rows * cols) as f64 + 4.0 * 4.0);
+		assert_eq!((a.get((3, 3)), a.get((3, 4))), (5.0, 1.0));
+
+		a.zero_out();
+		assert_eq!(a.size(), (rows, cols));
+		assert_eq!(a.sum_sq(), 0.0);
+		assert_eq!(a.iter_nnz().filter(|(_, v)| *v != 0.0).count(), 0);
+		a.fill(-2.5);
+		assert_eq!(a.sum(), -2.5 * (rows * cols) as f64);
+	}
+
+	fn assert_sparse_fill_with_zero_clears<M: Matrix>() {
+		let mut sparse = M::eye(5);
+		sparse.fill(0.0);
+		assert_eq!(sparse.nnz(), 0);
+	}
+
+	matrix_tests!(fill_sets_every_position => assert_fill);
+	matrix_tests!(sparse: sparse_fill_with_zero_clears => assert_sparse_fill_with_zero_clears);
+
+	fn assert_inverse<M: Matrix>() {
+		for seed in 0..5 {
+			let mut a: M = random_square(10, 1048 + seed);
+			for i in 0..10 {
+				a.set((i, i), a.get((i, i)) + 10.0);
+			}
+			let inverse = M::inverse(&a).unwrap();
+			assert_matrix_close(&M::mul(&a, &inverse), &M::eye(10), 1e-9);
+			assert_matrix_close(&M::mul(&inverse, &a), &M::eye(10), 1e-9);
 		}
 	}
-}
\ No newline at end of file
+
+	matrix_tests!(inverse_times_matrix_is_identity => assert_inverse);
+
+	fn unit(n: usize, k: usize) -> Vec<f64> {
+		let mut e = vec![0.0; n];
+		e[k] = 1.0;
+		e
+	}
+
+	/// `A * e_j` é a coluna `j`, `e_i^T * A` é a linha `i` e `v^T * A` é a combinaçao das linhas com os pesos de `v`
+	fn assert_unit_vectors_extract_lines<M: Matrix>(a: &M) {
+		let (rows, cols) = a.size();
+		for j in 0..cols {
+			assert_eq!(a.mul_vec(&unit(cols, j)), (0..rows).map(|i| a.get((i, j))).collect::<Vec<_>>(), "column {j}");
+		}
+		for i in 0..rows {
+			assert_eq!(a.vec_mul(&unit(rows, i)), (0..cols).map(|j| a.get((i, j))).collect::<Vec<_>>(), "row {i}");
+		}
+		let v: Vec<f64> = (0..rows).map(|i| i as f64 - 1.5).collect();
+		let expected = (0..cols).map(|j| (0..rows).map(|i| v[i] * a.get((i, j))).sum::<f64>());
+		for (got, expected) in a.vec_mul(&v).iter().zip(expected) {
+			assert!((got - expected).abs() < 1e-12, "{got} != {expected}");
+		}
+	}
+
+	fn assert_mul_vec_and_vec_mul<M: Matrix + Clone>() {
+		let a: M = random((6, 4), 1031);
+		assert_unit_vectors_extract_lines(&a);
+		assert_unit_vectors_extract_lines(&a.transposed());
+		assert_unit_vectors_extract_lines(&sample::<M>());
+	}
+
+	matrix_tests!(mul_vec_of_unit_vectors_extracts_columns_and_vec_mul_rows => assert_mul_vec_and_vec_mul);
+
+	/// A matriz `6 x 6` aleatoria restrita as posiçoes aceitas por `keep`, no tipo `M`
+	fn structured<M: Matrix>(keep: impl Fn(usize, usize) -> bool) -> M {
+		let mut info = random::<TableMatrix>((6, 6), 1031).to_info();
+		info.values.retain(|((i, j), _)| keep(*i, *j));
+		M::from_info(&info)
+	}
+
+	#[test]
+	fn mul_vec_and_vec_mul_of_the_structured_matrices() {
+		assert_unit_vectors_extract_lines(&crate::CsrMatrix::from_info(&sample_info()));
+		assert_unit_vectors_extract_lines(&crate::CscMatrix::from_info(&sample_info()));
+		assert_unit_vectors_extract_lines(&structured::<crate::BandMatrix>(|i, j| i.abs_diff(j) <= 1 || j == i + 2));
+		assert_unit_vectors_extract_lines(&structured::<crate::LowerTriMatrix>(|i, j| i >= j));
+		assert_unit_vectors_extract_lines(&structured::<crate::UpperTriMatrix>(|i, j| i <= j));
+		assert_unit_vectors_extract_lines(&structured::<crate::DiagonalMatrix>(|i, j| i == j));
+		assert_unit_vectors_extract_lines(&structured::<crate::SymmetricHashMapMatrix>(|i, j| i <= j));
+	}
+}
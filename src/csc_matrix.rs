@@ -0,0 +1,266 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::{basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_mul_sizes}, csr_matrix::CsrMatrix, sparse_vec::SparseVec};
+
+/// Matriz esparsa no formato CSC (Compressed Sparse Column), a versao por colunas do `CsrMatrix`
+///
+/// Os valores nao nulos ficam em `values`, coluna por coluna e, dentro de cada coluna, em ordem crescente de linha.
+/// `row_idx[k]` é a linha de `values[k]` e os valores da coluna `j` sao `values[col_ptr[j]..col_ptr[j + 1]]`.
+/// Os tres vetores de uma matriz `A` em CSC sao exatamente os vetores de `A^T` em CSR, entao a conversao
+/// para a transposta em CSR (`into_transposed_csr`) nao copia nada.
+#[derive(Clone, Debug)]
+pub struct CscMatrix {
+	size: Pair,
+	col_ptr: Vec<usize>,
+	row_idx: Vec<usize>,
+	values: Vec<f64>,
+}
+
+impl CscMatrix {
+	/// Intervalo de `row_idx` e `values` ocupado pela coluna `j`
+	fn col_range(&self, j: usize) -> std::ops::Range<usize> {
+		self.col_ptr[j]..self.col_ptr[j + 1]
+	}
+
+	/// Valores nao nulos da coluna `j`, em ordem crescente de linha (as linhas estao em `col_indices`)
+	pub fn col_slice(&self, j: usize) -> &[f64] {
+		&self.values[self.col_range(j)]
+	}
+
+	/// Linhas dos valores nao nulos da coluna `j`, na mesma ordem de `col_slice`
+	pub fn col_indices(&self, j: usize) -> &[usize] {
+		&self.row_idx[self.col_range(j)]
+	}
+
+	/// Linhas e valores da coluna `j`, em ordem crescente de linha
+	fn col(&self, j: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+		self.col_indices(j).iter().copied().zip(self.col_slice(j).iter().copied())
+	}
+
+	/// Converte uma matriz CSR para CSC, contando os valores de cada coluna
+	/// Complexidade de tempo: O(k + n)
+	pub fn from_csr(csr: &CsrMatrix) -> CscMatrix {
+		// a transposta em CSR tem os mesmos vetores que a matriz original em CSC
+		CscMatrix::from_transposed_csr(csr.clone().transposed())
+	}
+
+	/// Converte para CSR, contando os valores de cada linha
+	/// Complexidade de tempo: O(k + n)
+	pub fn to_csr(&self) -> CsrMatrix {
+		self.clone().into_transposed_csr().transposed()
+	}
+
+	/// Reinterpreta os vetores como a matriz transposta em CSR, sem copia-los
+	/// Complexidade de tempo: O(1)
+	pub fn into_transposed_csr(self) -> CsrMatrix {
+		CsrMatrix::from_parts((self.size.1, self.size.0), self.col_ptr, self.row_idx, self.values)
+	}
+
+	/// Inverso de `into_transposed_csr`: le os vetores CSR de `A^T` como `A` em CSC
+	fn from_transposed_csr(csr: CsrMatrix) -> CscMatrix {
+		let ((rows, cols), col_ptr, row_idx, values) = csr.into_parts();
+		CscMatrix {
+			size: (cols, rows),
+			col_ptr,
+			row_idx,
+			values,
+		}
+	}
+}
+
+impl Matrix for CscMatrix {
+	fn new(size: Pair) -> Self {
+		CscMatrix {
+			size,
+			col_ptr: vec![0; size.1 + 1],
+			row_idx: Vec::new(),
+			values: Vec::new(),
+		}
+	}
+
+	fn size(&self) -> Pair {
+		self.size
+	}
+
+	fn nnz(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Define o valor na posiçao, inserindo em ordem de linha e deslocando os valores seguintes
+	/// Entra em panico se a posiçao estiver fora da matriz.
+	/// Complexidade de tempo: O(log c + k) no pior caso, onde c é o numero de valores da coluna e k o numero total de valores
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		let range = self.col_range(pos.1);
+		let start = range.start;
+		match self.row_idx[range].binary_search(&pos.0) {
+			Ok(offset) if value == 0.0 => {
+				self.row_idx.remove(start + offset);
+				self.values.remove(start + offset);
+				for ptr in self.col_ptr[pos.1 + 1..].iter_mut() {
+					*ptr -= 1;
+				}
+			}
+			Ok(offset) => self.values[start + offset] = value,
+			Err(_) if value == 0.0 => {}
+			Err(offset) => {
+				self.row_idx.insert(start + offset, pos.0);
+				self.values.insert(start + offset, value);
+				for ptr in self.col_ptr[pos.1 + 1..].iter_mut() {
+					*ptr += 1;
+				}
+			}
+		}
+	}
+
+	/// Busca binaria entre as linhas da coluna
+	/// Entra em panico se a posiçao estiver fora da matriz.
+	/// Complexidade de tempo: O(log c), onde c é o numero de valores da coluna
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		let range = self.col_range(pos.1);
+		match self.row_idx[range.clone()].binary_search(&pos.0) {
+			Ok(offset) => self.values[range.start + offset],
+			Err(_) => 0.0,
+		}
+	}
+
+	/// Os vetores CSC de `A^T` sao os vetores CSR de `A`: le a matriz como CSR de `A^T` e a transpoe em CSR
+	/// Para apenas trocar o tipo, sem reordenar os valores, use `into_transposed_csr`
+	/// Complexidade de tempo: O(k + n)
+	fn transposed(self) -> Self {
+		CscMatrix::from_transposed_csr(self.into_transposed_csr().transposed())
+	}
+
+	/// `(A + B)^T = A^T + B^T`: soma as matrizes como CSR das transpostas, intercalando as colunas ordenadas
+	/// Complexidade de tempo: O(ka + kb + n), mais a copia dos vetores de `a` e `b`
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size, b.size)?;
+		let sum = CsrMatrix::add(&a.clone().into_transposed_csr(), &b.clone().into_transposed_csr());
+		Ok(CscMatrix::from_transposed_csr(sum))
+	}
+
+	/// `(A B)^T = B^T A^T`: a coluna `j` do resultado é a soma das colunas `k` de `a` escaladas por `b[k][j]`,
+	/// que é a multiplicaçao de Gustavson em CSR aplicada as transpostas
+	/// Complexidade de tempo: O(m + sum_j(sum_k(c_a(k))) + kc log kc), mais a copia dos vetores de `a` e `b`
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size, b.size)?;
+		let product = CsrMatrix::mul(&b.clone().into_transposed_csr(), &a.clone().into_transposed_csr());
+		Ok(CscMatrix::from_transposed_csr(product))
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		let mut c = a.clone();
+		CscMatrix::muls_assign(&mut c, scalar);
+		c
+	}
+
+	/// Multiplica o vetor `values` no lugar, sem mudar a estrutura
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		if scalar == 0.0 {
+			*a = CscMatrix::new(a.size);
+			return;
+		}
+		for value in a.values.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Monta a transposta em CSR e reinterpreta os vetores; zeros sao ignorados
+	/// e, para posiçoes repetidas, vale o ultimo valor
+	/// Complexidade de tempo: O(k log k + n)
+	fn from_info(info: &MatrixInfo) -> Self {
+		let transposed = MatrixInfo {
+			size: (info.size.1, info.size.0),
+			values: info.values.iter().map(|((i, j), value)| ((*j, *i), *value)).collect(),
+		};
+		CscMatrix::from_transposed_csr(CsrMatrix::from_info(&transposed))
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size,
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Espalha cada coluna `j`, escalada por `v[j]`, no resultado
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.1, v.len(), "Incompatible vector for mul_vec");
+		let mut result = vec![0.0; self.size.0];
+		for (j, vj) in v.iter().enumerate() {
+			for (i, value) in self.col(j) {
+				result[i] += value * vj;
+			}
+		}
+		result
+	}
+
+	/// Espalha no resultado apenas as colunas `j` com `v[j]` nao nulo, aproveitando a esparsidade dos dois lados
+	/// Complexidade de tempo: O(sum_j(c(j))) sobre as posiçoes `j` de `v`, onde c(j) é o numero de valores da coluna `j`
+	fn mul_sparse_vec(&self, v: &SparseVec) -> SparseVec {
+		assert!(v.iter().all(|(j, _)| j < self.size.1), "Incompatible vector for mul_sparse_vec");
+		let mut result = SparseVec::new();
+		for (j, vj) in v.iter() {
+			for (i, value) in self.col(j) {
+				result.add_at(i, value * vj);
+			}
+		}
+		result
+	}
+
+	/// Produto escalar esparso de cada coluna com `v`, lendo `values` em sequencia
+	fn vec_mul(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.0, v.len(), "Incompatible vector for vec_mul");
+		(0..self.size.1).map(|j| self.col(j).map(|(i, value)| v[i] * value).sum()).collect()
+	}
+
+	/// Espalha os valores de cada coluna, ja separados em `col_ptr`, em um vetor denso
+	fn iter_cols<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		Box::new((0..self.size.1).map(|j| {
+			let mut col = vec![0.0; self.size.0];
+			for (i, value) in self.col(j) {
+				col[i] = value;
+			}
+			col
+		}))
+	}
+
+	/// Percorre as colunas em ordem, e cada coluna em ordem de linha
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new((0..self.size.1).flat_map(|j| self.col(j).map(move |(i, value)| ((i, j), value))))
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&CscMatrix> for CscMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for CscMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn get_row_out_of_bounds_panics() {
+		let mut m = CscMatrix::new((2, 2));
+		m.set((1, 0), 3.0);
+		m.get((2, 0));
+	}
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn set_row_out_of_bounds_panics() {
+		CscMatrix::new((2, 2)).set((2, 1), 3.0);
+	}
+}
@@ -0,0 +1,343 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::{basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_csr_arrays, check_mul_sizes}, sparse_vec::SparseVec};
+
+/// Matriz esparsa no formato CSR (Compressed Sparse Row)
+///
+/// Os valores nao nulos ficam em `values`, linha por linha e, dentro de cada linha, em ordem crescente de coluna.
+/// `col_idx[k]` é a coluna de `values[k]` e os valores da linha `i` sao `values[row_ptr[i]..row_ptr[i + 1]]`.
+/// Percorrer a matriz por linhas acessa a memoria sequencialmente, mas inserir ou remover um valor
+/// desloca todos os valores seguintes.
+#[derive(Clone, Debug)]
+pub struct CsrMatrix {
+	size: Pair,
+	row_ptr: Vec<usize>,
+	col_idx: Vec<usize>,
+	values: Vec<f64>,
+}
+
+impl CsrMatrix {
+	/// Intervalo de `col_idx` e `values` ocupado pela linha `i`
+	fn row_range(&self, i: usize) -> std::ops::Range<usize> {
+		self.row_ptr[i]..self.row_ptr[i + 1]
+	}
+
+	/// Colunas e valores da linha `i`, em ordem crescente de coluna
+	fn row(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+		let range = self.row_range(i);
+		self.col_idx[range.clone()].iter().copied().zip(self.values[range].iter().copied())
+	}
+
+	/// Monta a matriz direto dos tres vetores, que precisam estar no formato descrito em `CsrMatrix`
+	pub(crate) fn from_parts(size: Pair, row_ptr: Vec<usize>, col_idx: Vec<usize>, values: Vec<f64>) -> Self {
+		CsrMatrix { size, row_ptr, col_idx, values }
+	}
+
+	/// Separa a matriz em tamanho, `row_ptr`, `col_idx` e `values`, sem copiar os vetores
+	pub(crate) fn into_parts(self) -> (Pair, Vec<usize>, Vec<usize>, Vec<f64>) {
+		(self.size, self.row_ptr, self.col_idx, self.values)
+	}
+
+	/// Monta a matriz a partir de linhas ja ordenadas por coluna e sem zeros
+	fn from_rows(size: Pair, rows: impl IntoIterator<Item = Vec<(usize, f64)>>) -> Self {
+		let mut m = CsrMatrix {
+			size,
+			row_ptr: Vec::with_capacity(size.0 + 1),
+			col_idx: Vec::new(),
+			values: Vec::new(),
+		};
+		m.row_ptr.push(0);
+		for row in rows {
+			for (j, value) in row {
+				m.col_idx.push(j);
+				m.values.push(value);
+			}
+			m.row_ptr.push(m.values.len());
+		}
+		m
+	}
+}
+
+impl Matrix for CsrMatrix {
+	fn new(size: Pair) -> Self {
+		CsrMatrix {
+			size,
+			row_ptr: vec![0; size.0 + 1],
+			col_idx: Vec::new(),
+			values: Vec::new(),
+		}
+	}
+
+	fn size(&self) -> Pair {
+		self.size
+	}
+
+	fn nnz(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Define o valor na posiçao, inserindo em ordem de coluna e deslocando os valores seguintes
+	/// Entra em panico se a posiçao estiver fora da matriz.
+	/// Complexidade de tempo: O(log r + k) no pior caso, onde r é o numero de valores da linha e k o numero total de valores
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		let range = self.row_range(pos.0);
+		let start = range.start;
+		match self.col_idx[range].binary_search(&pos.1) {
+			Ok(offset) if value == 0.0 => {
+				self.col_idx.remove(start + offset);
+				self.values.remove(start + offset);
+				for ptr in self.row_ptr[pos.0 + 1..].iter_mut() {
+					*ptr -= 1;
+				}
+			}
+			Ok(offset) => self.values[start + offset] = value,
+			Err(_) if value == 0.0 => {}
+			Err(offset) => {
+				self.col_idx.insert(start + offset, pos.1);
+				self.values.insert(start + offset, value);
+				for ptr in self.row_ptr[pos.0 + 1..].iter_mut() {
+					*ptr += 1;
+				}
+			}
+		}
+	}
+
+	/// Busca binaria entre as colunas da linha
+	/// Entra em panico se a posiçao estiver fora da matriz.
+	/// Complexidade de tempo: O(log r), onde r é o numero de valores da linha
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		let range = self.row_range(pos.0);
+		match self.col_idx[range.clone()].binary_search(&pos.1) {
+			Ok(offset) => self.values[range.start + offset],
+			Err(_) => 0.0,
+		}
+	}
+
+	/// Converte para o formato CSC (contando os valores de cada coluna), que lido como CSR é a transposta
+	/// Complexidade de tempo: O(k + n)
+	fn transposed(self) -> Self {
+		let mut row_ptr = vec![0; self.size.1 + 1];
+		for j in self.col_idx.iter() {
+			row_ptr[j + 1] += 1;
+		}
+		for j in 0..self.size.1 {
+			row_ptr[j + 1] += row_ptr[j];
+		}
+		let mut next = row_ptr.clone();
+		let mut col_idx = vec![0; self.values.len()];
+		let mut values = vec![0.0; self.values.len()];
+		for i in 0..self.size.0 {
+			for (j, value) in self.row(i) {
+				col_idx[next[j]] = i;
+				values[next[j]] = value;
+				next[j] += 1;
+			}
+		}
+		CsrMatrix {
+			size: (self.size.1, self.size.0),
+			row_ptr,
+			col_idx,
+			values,
+		}
+	}
+
+	/// Soma intercalando as linhas ordenadas de `a` e `b`
+	/// Complexidade de tempo: O(ka + kb + n)
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size, b.size)?;
+		let rows = (0..a.size.0).map(|i| {
+			let mut row = Vec::with_capacity(a.row_range(i).len() + b.row_range(i).len());
+			let (mut ra, mut rb) = (a.row(i).peekable(), b.row(i).peekable());
+			loop {
+				let entry = match (ra.peek(), rb.peek()) {
+					(Some(&(ja, va)), Some(&(jb, vb))) if ja == jb => {
+						ra.next();
+						rb.next();
+						(ja, va + vb)
+					}
+					(Some(&(ja, _)), Some(&(jb, _))) if ja < jb => ra.next().unwrap(),
+					(Some(_), None) => ra.next().unwrap(),
+					(_, Some(_)) => rb.next().unwrap(),
+					(None, None) => break,
+				};
+				if entry.1 != 0.0 {
+					row.push(entry);
+				}
+			}
+			row
+		});
+		Ok(CsrMatrix::from_rows(a.size, rows))
+	}
+
+	/// Multiplicaçao esparsa linha por linha (SpGEMM de Gustavson): a linha `i` do resultado é a soma das
+	/// linhas `k` de `b` escaladas por `a[i][k]`, acumuladas em um vetor denso e depois ordenadas por coluna
+	/// Complexidade de tempo: O(n + sum_i(sum_k(r_b(k))) + kc log kc)
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size, b.size)?;
+		let mut accumulator = vec![0.0; b.size.1];
+		let mut used = vec![false; b.size.1];
+		let rows = (0..a.size.0).map(|i| {
+			let mut columns = Vec::new();
+			for (k, va) in a.row(i) {
+				for (j, vb) in b.row(k) {
+					if !used[j] {
+						used[j] = true;
+						columns.push(j);
+					}
+					accumulator[j] += va * vb;
+				}
+			}
+			columns.sort_unstable();
+			let mut row = Vec::with_capacity(columns.len());
+			for j in columns {
+				if accumulator[j] != 0.0 {
+					row.push((j, accumulator[j]));
+				}
+				accumulator[j] = 0.0;
+				used[j] = false;
+			}
+			row
+		}).collect::<Vec<_>>();
+		Ok(CsrMatrix::from_rows((a.size.0, b.size.1), rows))
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		if scalar == 0.0 {
+			return CsrMatrix::new(a.size);
+		}
+		let mut c = a.clone();
+		for value in c.values.iter_mut() {
+			*value *= scalar;
+		}
+		c
+	}
+
+	/// Multiplica o vetor `values` no lugar, sem mudar a estrutura
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		if scalar == 0.0 {
+			*a = CsrMatrix::new(a.size);
+			return;
+		}
+		for value in a.values.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Ordena os valores por linha e coluna antes de montar os ponteiros; zeros sao ignorados
+	/// e, para posiçoes repetidas, vale o ultimo valor
+	/// Complexidade de tempo: O(k log k + n)
+	fn from_info(info: &MatrixInfo) -> Self {
+		let mut entries: Vec<(Pair, f64)> = info.values.clone();
+		entries.sort_by_key(|(pos, _)| *pos);
+		let mut rows = vec![Vec::new(); info.size.0];
+		for ((i, j), value) in entries {
+			let row: &mut Vec<(usize, f64)> = &mut rows[i];
+			if row.last().is_some_and(|(last, _)| *last == j) {
+				row.pop();
+			}
+			if value != 0.0 {
+				row.push((j, value));
+			}
+		}
+		CsrMatrix::from_rows(info.size, rows)
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size,
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// A linha `i * p + r` do resultado junta as linhas `i` de `a` e `r` de `b`; percorrendo as duas em ordem,
+	/// as colunas `j * q + s` ja saem ordenadas e as linhas sao montadas sem inserçoes no meio
+	/// Complexidade de tempo: O(ka * kb + m * p)
+	fn kron(a: &Self, b: &Self) -> Self {
+		let (p, q) = b.size;
+		let rows = (0..a.size.0 * p).map(|row| {
+			let (i, r) = (row / p, row % p);
+			a.row(i)
+				.flat_map(|(j, va)| b.row(r).map(move |(s, vb)| (j * q + s, va * vb)))
+				.filter(|(_, value)| *value != 0.0)
+				.collect()
+		});
+		CsrMatrix::from_rows((a.size.0 * p, a.size.1 * q), rows)
+	}
+
+	/// Produto escalar esparso de cada linha com `v`, lendo `values` em sequencia
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.1, v.len(), "Incompatible vector for mul_vec");
+		(0..self.size.0).map(|i| self.row(i).map(|(j, value)| value * v[j]).sum()).collect()
+	}
+
+	/// As linhas nao dao acesso direto as colunas de `v`: copia `v` para denso, usa `mul_vec` e guarda os resultados nao nulos
+	/// Complexidade de tempo: O(k + n + m)
+	fn mul_sparse_vec(&self, v: &SparseVec) -> SparseVec {
+		assert!(v.iter().all(|(j, _)| j < self.size.1), "Incompatible vector for mul_sparse_vec");
+		SparseVec::from_dense(&self.mul_vec(&v.to_dense(self.size.1)))
+	}
+
+	/// Os vetores da matriz ja estao no formato CSR: apenas os copia, sem ordenar os valores
+	/// Complexidade de tempo: O(k + n)
+	fn to_csr_arrays(&self) -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+		(self.row_ptr.clone(), self.col_idx.clone(), self.values.clone())
+	}
+
+	/// Usa os vetores como a propria matriz, sem copia-los, depois de verificar o formato
+	/// Complexidade de tempo: O(k + n)
+	fn from_csr_arrays(nrows: usize, ncols: usize, row_ptr: Vec<usize>, col_idx: Vec<usize>, values: Vec<f64>) -> Self {
+		check_csr_arrays((nrows, ncols), &row_ptr, &col_idx, &values);
+		CsrMatrix::from_parts((nrows, ncols), row_ptr, col_idx, values)
+	}
+
+	/// Percorre as linhas em ordem, e cada linha em ordem de coluna
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new((0..self.size.0).flat_map(|i| self.row(i).map(move |(j, value)| ((i, j), value))))
+	}
+
+	/// Espalha os valores de cada linha, ja separados em `row_ptr`, em um vetor denso
+	fn iter_rows<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		Box::new((0..self.size.0).map(|i| {
+			let mut row = vec![0.0; self.size.1];
+			for (j, value) in self.row(i) {
+				row[j] = value;
+			}
+			row
+		}))
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&CsrMatrix> for CsrMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for CsrMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn get_column_out_of_bounds_panics() {
+		let mut m = CsrMatrix::new((2, 2));
+		m.set((0, 1), 3.0);
+		m.get((0, 2));
+	}
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn set_column_out_of_bounds_panics() {
+		CsrMatrix::new((2, 2)).set((1, 2), 3.0);
+	}
+}
@@ -0,0 +1,208 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::{HashMapMatrix, basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_mul_sizes}};
+
+/// Matriz diagonal, guardando apenas os `min(linhas, colunas)` elementos da diagonal principal
+///
+/// Ocupa O(n) de memoria, em vez de O(n^2) de uma `TableMatrix`. Como apenas a diagonal pode ter valores,
+/// `set` e `from_info` entram em panico com valores nao nulos fora dela, assim como as operaçoes padrao
+/// do `Matrix` cujo resultado nao é diagonal (por exemplo `submatrix` fora da diagonal).
+#[derive(Clone, Debug)]
+pub struct DiagonalMatrix {
+	size: Pair,
+	diag: Vec<f64>,
+}
+
+impl DiagonalMatrix {
+	/// Cria a matriz quadrada `n x n` com diagonal `v`, sem copiar o vetor
+	pub fn from_diagonal_vec(v: Vec<f64>) -> DiagonalMatrix {
+		DiagonalMatrix { size: (v.len(), v.len()), diag: v }
+	}
+
+	/// Produto `D * A` com uma matriz de qualquer tipo: cada valor de `A` é escalado pelo elemento da diagonal da sua linha
+	/// Entra em panico se o numero de colunas de `D` for diferente do numero de linhas de `A`.
+	/// Complexidade de tempo: O(A::new + k * A::set), onde k é o numero de elementos de `A`
+	pub fn mul_matrix<M: Matrix>(&self, a: &M) -> M {
+		assert_eq!(self.size.1, a.size().0, "Incompatible matrices for multiplication");
+		let mut c = M::new((self.size.0, a.size().1));
+		for ((i, j), value) in a.iter_nnz() {
+			let d = self.diag.get(i).copied().unwrap_or(0.0);
+			if d * value != 0.0 {
+				c.set((i, j), d * value);
+			}
+		}
+		c
+	}
+}
+
+impl Matrix for DiagonalMatrix {
+	fn new(size: Pair) -> Self {
+		DiagonalMatrix { size, diag: vec![0.0; size.0.min(size.1)] }
+	}
+
+	fn size(&self) -> Pair {
+		self.size
+	}
+
+	/// Entra em panico se `value` nao for 0 e a posiçao estiver fora da diagonal
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		if pos.0 == pos.1 {
+			self.diag[pos.0] = value;
+		} else {
+			assert!(value == 0.0, "DiagonalMatrix only stores values on the diagonal");
+		}
+	}
+
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		if pos.0 == pos.1 { self.diag[pos.0] } else { 0.0 }
+	}
+
+	/// A diagonal nao muda, apenas o tamanho
+	/// Complexidade de tempo: O(1)
+	fn transposed(mut self) -> Self {
+		self.size = (self.size.1, self.size.0);
+		self
+	}
+
+	/// Complexidade de tempo: O(n)
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size, b.size)?;
+		Ok(DiagonalMatrix {
+			size: a.size,
+			diag: a.diag.iter().zip(b.diag.iter()).map(|(x, y)| x + y).collect(),
+		})
+	}
+
+	/// O produto de duas diagonais é diagonal, com os elementos multiplicados um a um
+	/// Complexidade de tempo: O(n)
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size, b.size)?;
+		let mut c = DiagonalMatrix::new((a.size.0, b.size.1));
+		for (value, (x, y)) in c.diag.iter_mut().zip(a.diag.iter().zip(b.diag.iter())) {
+			*value = x * y;
+		}
+		Ok(c)
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		DiagonalMatrix {
+			size: a.size,
+			diag: a.diag.iter().map(|value| value * scalar).collect(),
+		}
+	}
+
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		for value in a.diag.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Entra em panico se houver algum valor nao nulo fora da diagonal
+	fn from_info(info: &MatrixInfo) -> Self {
+		let mut m = DiagonalMatrix::new(info.size);
+		for (pos, value) in info.values.iter() {
+			m.set(*pos, *value);
+		}
+		m
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size,
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Percorre a diagonal em ordem, pulando os zeros
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new(self.diag.iter().enumerate()
+			.filter(|(_, value)| **value != 0.0)
+			.map(|(i, value)| ((i, i), *value)))
+	}
+
+	fn diagonal(&self) -> Vec<f64> {
+		self.diag.clone()
+	}
+
+	/// Complexidade de tempo: O(n)
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.1, v.len(), "Incompatible vector for mul_vec");
+		let mut result = vec![0.0; self.size.0];
+		for (r, (d, x)) in result.iter_mut().zip(self.diag.iter().zip(v)) {
+			*r = d * x;
+		}
+		result
+	}
+}
+
+/// Copia os valores nao nulos da diagonal para uma `HashMapMatrix`
+impl From<DiagonalMatrix> for HashMapMatrix {
+	fn from(d: DiagonalMatrix) -> Self {
+		HashMapMatrix::from_info(&d.to_info())
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&DiagonalMatrix> for DiagonalMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for DiagonalMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::TableMatrix;
+
+	#[test]
+	fn product_with_identity_and_trace() {
+		let diag = vec![2.0, -1.0, 0.0, 3.5];
+		let d = DiagonalMatrix::from_diagonal_vec(diag.clone());
+		let product = DiagonalMatrix::mul(&d, &DiagonalMatrix::eye(4));
+		assert_eq!(product.size(), d.size());
+		assert_eq!(product.diagonal(), diag);
+		assert_eq!(d.trace(), diag.iter().sum::<f64>());
+		assert_eq!(d.nnz(), 3);
+		assert_eq!((d.get((1, 1)), d.get((1, 2))), (-1.0, 0.0));
+	}
+
+	#[test]
+	fn mul_matrix_scales_the_rows() {
+		let d = DiagonalMatrix::from_diagonal_vec(vec![2.0, 0.0, -1.0]);
+		let a = TableMatrix::from(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]);
+		assert_eq!(d.mul_matrix(&a), TableMatrix::from(vec![vec![2.0, 4.0], vec![0.0, 0.0], vec![-5.0, -6.0]]));
+		let sparse = HashMapMatrix::from_info(&a.to_info());
+		let scaled = d.mul_matrix(&sparse);
+		assert_eq!(scaled.nnz(), 4);
+		assert_eq!(scaled.get((2, 1)), -6.0);
+		let d_eye = d.mul_matrix(&HashMapMatrix::eye(3));
+		assert_eq!(d_eye, HashMapMatrix::from(d));
+	}
+
+	#[test]
+	fn rectangular_diagonal_and_transpose() {
+		let mut d = DiagonalMatrix::new((2, 4));
+		d.set((1, 1), 5.0);
+		d.set((0, 3), 0.0);
+		assert_eq!(d.diagonal(), vec![0.0, 5.0]);
+		let t = d.transposed();
+		assert_eq!(t.size(), (4, 2));
+		assert_eq!(t.get((1, 1)), 5.0);
+		assert_eq!(DiagonalMatrix::try_add(&t, &DiagonalMatrix::new((2, 4))).err(), Some(MatrixError::DimensionMismatch { expected: (4, 2), got: (2, 4) }));
+	}
+
+	#[test]
+	#[should_panic(expected = "DiagonalMatrix only stores values on the diagonal")]
+	fn set_off_the_diagonal_panics() {
+		DiagonalMatrix::new((3, 3)).set((0, 1), 1.0);
+	}
+}
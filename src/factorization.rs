@@ -0,0 +1,673 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{EPSILON, basic::{Matrix, MatrixError}};
+
+/// Fatoraçao de Cholesky incompleta IC(k) de uma matriz simetrica definida positiva
+///
+/// Calcula `L` triangular inferior com `A ≈ L * L^T`, mantendo apenas as posiçoes `(i, j)`, `j <= i`,
+/// que sao nao nulas em `A` ou que estao a no maximo `fill_level` diagonais abaixo da principal.
+/// O preenchimento (fill-in) fora desse padrao é descartado.
+/// Apenas a parte triangular inferior de `A` é lida.
+///
+/// As linhas de `L` sao guardadas em mapas ordenados, de modo que o padrao de esparsidade do
+/// resultado é mantido explicitamente e a memoria é proporcional ao numero de elementos de `L`.
+pub(crate) fn incomplete_cholesky<M: Matrix>(a: &M, fill_level: usize) -> Result<M, MatrixError> {
+	let size = a.size();
+	if size.0 != size.1 {
+		return Err(MatrixError::DimensionMismatch { expected: (size.0, size.0), got: size });
+	}
+	let n = size.0;
+	let mut lower: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); n];
+	for (pos, value) in a.to_info().values {
+		if pos.1 <= pos.0 && value != 0.0 {
+			lower[pos.0].insert(pos.1, value);
+		}
+	}
+
+	let mut l: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); n];
+	for i in 0..n {
+		let mut columns: BTreeSet<usize> = lower[i].keys().copied().collect();
+		columns.extend(i.saturating_sub(fill_level)..i);
+		columns.insert(i);
+		for j in columns {
+			// soma de L[i][k] * L[j][k] para k < j
+			let sum: f64 = l[i].range(..j)
+				.map(|(k, lik)| lik * l[j].get(k).unwrap_or(&0.0))
+				.sum();
+			let aij = lower[i].get(&j).copied().unwrap_or(0.0);
+			if j == i {
+				let pivot = aij - sum;
+				if pivot <= 0.0 {
+					return Err(MatrixError::SingularMatrix);
+				}
+				l[i].insert(i, pivot.sqrt());
+			} else {
+				let value = (aij - sum) / l[j][&j];
+				if value != 0.0 {
+					l[i].insert(j, value);
+				}
+			}
+		}
+	}
+
+	let mut result = M::new(size);
+	for (i, row) in l.into_iter().enumerate() {
+		for (j, value) in row {
+			result.set((i, j), value);
+		}
+	}
+	Ok(result)
+}
+
+/// Matriz densa, guardada como um vetor de linhas
+pub(crate) type Dense = Vec<Vec<f64>>;
+
+/// Converte a matriz para a forma densa, usando O(n * m) de memoria
+pub(crate) fn to_dense<M: Matrix>(a: &M) -> Dense {
+	let size = a.size();
+	let mut dense = vec![vec![0.0; size.1]; size.0];
+	for ((i, j), value) in a.to_info().values {
+		dense[i][j] = value;
+	}
+	dense
+}
+
+/// Converte uma matriz densa para o tipo `M`, definindo apenas os valores nao nulos
+pub(crate) fn from_dense<M: Matrix>(dense: &Dense) -> M {
+	let cols = dense.first().map_or(0, |row| row.len());
+	let mut m = M::new((dense.len(), cols));
+	for (i, row) in dense.iter().enumerate() {
+		for (j, value) in row.iter().enumerate() {
+			if *value != 0.0 {
+				m.set((i, j), *value);
+			}
+		}
+	}
+	m
+}
+
+/// Fatoraçao de Cholesky `A = L * L^T` de uma matriz densa simetrica definida positiva
+/// Apenas a parte triangular inferior de `a` é lida.
+/// Retorna `MatrixError::SingularMatrix` se algum pivo nao for positivo, ou seja, se a matriz nao for definida positiva.
+/// Complexidade de tempo: O(n^3)
+pub(crate) fn cholesky_dense(a: &Dense) -> Result<Dense, MatrixError> {
+	let n = a.len();
+	let mut l = vec![vec![0.0; n]; n];
+	for i in 0..n {
+		for j in 0..=i {
+			let sum: f64 = l[i][..j].iter().zip(&l[j][..j]).map(|(x, y)| x * y).sum();
+			if j == i {
+				let pivot = a[i][i] - sum;
+				if pivot <= 0.0 || !pivot.is_finite() {
+					return Err(MatrixError::SingularMatrix);
+				}
+				l[i][i] = pivot.sqrt();
+			} else {
+				l[i][j] = (a[i][j] - sum) / l[j][j];
+			}
+		}
+	}
+	Ok(l)
+}
+
+/// Fatoraçao LU com pivoteamento parcial `P * A = L * U` de uma matriz densa quadrada
+///
+/// Retorna `(lu, perm)`: `lu` guarda `U` na diagonal e acima dela e `L` abaixo (a diagonal de `L`, toda 1, nao é guardada),
+/// e a linha `i` de `P * A` é a linha `perm[i]` de `A`. Em cada coluna o pivo é o valor de maior modulo ainda nao usado.
+/// Retorna `MatrixError::SingularMatrix` se algum pivo tiver modulo menor que `EPSILON`.
+/// Complexidade de tempo: O(n^3)
+pub(crate) fn lu_dense(a: &Dense) -> Result<(Dense, Vec<usize>), MatrixError> {
+	let n = a.len();
+	let mut lu = a.clone();
+	let mut perm: Vec<usize> = (0..n).collect();
+	for k in 0..n {
+		let pivot_row = (k..n)
+			.max_by(|x, y| lu[*x][k].abs().total_cmp(&lu[*y][k].abs()))
+			.unwrap();
+		let pivot = lu[pivot_row][k];
+		if pivot.abs() < EPSILON || !pivot.is_finite() {
+			return Err(MatrixError::SingularMatrix);
+		}
+		lu.swap(k, pivot_row);
+		perm.swap(k, pivot_row);
+		let (top, bottom) = lu.split_at_mut(k + 1);
+		let pivot_values = &top[k];
+		for row in bottom.iter_mut() {
+			let factor = row[k] / pivot_values[k];
+			row[k] = factor;
+			for (x, p) in row[k + 1..].iter_mut().zip(&pivot_values[k + 1..]) {
+				*x -= factor * p;
+			}
+		}
+	}
+	Ok((lu, perm))
+}
+
+/// Resolve `A * x = b` a partir da fatoraçao `(lu, perm)` de `lu_dense`, por substituiçao direta e reversa
+/// Complexidade de tempo: O(n^2)
+pub(crate) fn lu_solve(lu: &Dense, perm: &[usize], b: &[f64]) -> Vec<f64> {
+	let n = lu.len();
+	let mut x: Vec<f64> = perm.iter().map(|i| b[*i]).collect();
+	for i in 0..n {
+		let sum: f64 = lu[i][..i].iter().zip(&x[..i]).map(|(l, y)| l * y).sum();
+		x[i] -= sum;
+	}
+	for i in (0..n).rev() {
+		let sum: f64 = lu[i][i + 1..].iter().zip(&x[i + 1..]).map(|(u, y)| u * y).sum();
+		x[i] = (x[i] - sum) / lu[i][i];
+	}
+	x
+}
+
+/// Inversa de uma matriz densa quadrada, resolvendo `A * X = I` uma coluna da identidade por vez com a fatoraçao LU
+/// Retorna `MatrixError::SingularMatrix` nos mesmos casos que `lu_dense`.
+/// Complexidade de tempo: O(n^3)
+pub(crate) fn inverse_dense(a: &Dense) -> Result<Dense, MatrixError> {
+	let n = a.len();
+	let (lu, perm) = lu_dense(a)?;
+	let mut inverse = vec![vec![0.0; n]; n];
+	let mut e = vec![0.0; n];
+	for j in 0..n {
+		e[j] = 1.0;
+		for (row, value) in inverse.iter_mut().zip(lu_solve(&lu, &perm, &e)) {
+			row[j] = value;
+		}
+		e[j] = 0.0;
+	}
+	Ok(inverse)
+}
+
+/// Reduz `h` a forma de Hessenberg superior com refletores de Householder, retornando `Q` tal que `A = Q * H * Q^T`
+///
+/// Para cada coluna `k` é aplicado, dos dois lados, o refletor que zera os elementos abaixo de `k + 1`.
+/// Complexidade de tempo: O(n^3)
+pub(crate) fn hessenberg(h: &mut Dense) -> Dense {
+	let n = h.len();
+	let mut q = identity(n);
+	for k in 0..n.saturating_sub(2) {
+		let scale: f64 = (k + 1..n).map(|i| h[i][k].abs()).sum();
+		if scale == 0.0 {
+			continue;
+		}
+		// v = x - alpha * e1, com x a coluna k abaixo da diagonal, normalizada por scale
+		let mut v: Vec<f64> = (k + 1..n).map(|i| h[i][k] / scale).collect();
+		let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+		let alpha = if v[0] > 0.0 { -norm } else { norm };
+		v[0] -= alpha;
+		let vv: f64 = v.iter().map(|x| x * x).sum();
+		if vv == 0.0 {
+			continue;
+		}
+		// H = P * H, com P = I - 2 v v^T / (v^T v)
+		let f: Vec<f64> = (0..n)
+			.map(|j| 2.0 * (0..v.len()).map(|r| v[r] * h[k + 1 + r][j]).sum::<f64>() / vv)
+			.collect();
+		for (r, vr) in v.iter().enumerate() {
+			for (x, fj) in h[k + 1 + r].iter_mut().zip(&f) {
+				*x -= fj * vr;
+			}
+		}
+		// H = H * P e Q = Q * P
+		for m in [&mut *h, &mut q] {
+			for row in m.iter_mut() {
+				let f = 2.0 * (0..v.len()).map(|r| v[r] * row[k + 1 + r]).sum::<f64>() / vv;
+				for r in 0..v.len() {
+					row[k + 1 + r] -= f * v[r];
+				}
+			}
+		}
+		h[k + 1][k] = alpha * scale;
+		for row in h.iter_mut().skip(k + 2) {
+			row[k] = 0.0;
+		}
+	}
+	q
+}
+
+fn identity(n: usize) -> Dense {
+	let mut m = vec![vec![0.0; n]; n];
+	for (i, row) in m.iter_mut().enumerate() {
+		row[i] = 1.0;
+	}
+	m
+}
+
+/// Aplica a rotaçao `[[q, p], [-p, q]]` nas linhas `a` e `b` de `t` (a partir da coluna `from`)
+/// e nas colunas `a` e `b` de `t` (ate a linha `to`) e de `z`
+fn rotate(t: &mut Dense, z: &mut Dense, (a, b): (usize, usize), (p, q): (f64, f64), from: usize, to: usize) {
+	let (top, bottom) = t.split_at_mut(b);
+	for (x, y) in top[a].iter_mut().zip(bottom[0].iter_mut()).skip(from) {
+		(*x, *y) = (q * *x + p * *y, q * *y - p * *x);
+	}
+	for row in t.iter_mut().take(to + 1) {
+		let x = row[a];
+		row[a] = q * x + p * row[b];
+		row[b] = q * row[b] - p * x;
+	}
+	for row in z.iter_mut() {
+		let x = row[a];
+		row[a] = q * x + p * row[b];
+		row[b] = q * row[b] - p * x;
+	}
+}
+
+/// Decomposiçao real de Schur `A = Z * T * Z^T`, com `Z` ortogonal e `T` quase triangular superior
+///
+/// `T` tem blocos 1x1 para autovalores reais e blocos 2x2 para pares de autovalores complexos conjugados.
+/// Primeiro reduz `A` a forma de Hessenberg e depois aplica passos duplos de QR de Francis ate que
+/// todos os elementos da subdiagonal fora dos blocos 2x2 sejam desprezaveis
+/// (segue o `hqr2` do EISPACK, sem o calculo dos autovetores).
+/// Retorna `MatrixError::SingularMatrix` se a iteraçao nao convergir.
+pub(crate) fn real_schur(a: &Dense) -> Result<(Dense, Dense), MatrixError> {
+	let mut t = a.clone();
+	let mut z = hessenberg(&mut t);
+	let size = t.len();
+	let eps = f64::EPSILON;
+	let norm: f64 = (0..size)
+		.map(|i| (i.saturating_sub(1)..size).map(|j| t[i][j].abs()).sum::<f64>())
+		.sum();
+	// inicio de cada bloco 2x2 de autovalores complexos
+	let mut complex_blocks = Vec::new();
+	let mut exshift = 0.0;
+	let mut iter = 0;
+	let mut total_iter = 0;
+	let mut n = size as isize - 1;
+
+	while n >= 0 {
+		let nu = n as usize;
+		// procura um elemento da subdiagonal desprezavel
+		let mut l = nu;
+		while l > 0 {
+			let mut s = t[l - 1][l - 1].abs() + t[l][l].abs();
+			if s == 0.0 {
+				s = norm;
+			}
+			if t[l][l - 1].abs() < eps * s {
+				break;
+			}
+			l -= 1;
+		}
+
+		if l == nu {
+			// um autovalor real
+			t[nu][nu] += exshift;
+			n -= 1;
+			iter = 0;
+		} else if l + 1 == nu {
+			// bloco 2x2
+			let w = t[nu][nu - 1] * t[nu - 1][nu];
+			let p = (t[nu - 1][nu - 1] - t[nu][nu]) / 2.0;
+			let q = p * p + w;
+			let zz = q.abs().sqrt();
+			t[nu][nu] += exshift;
+			t[nu - 1][nu - 1] += exshift;
+			if q >= 0.0 {
+				// dois autovalores reais: uma rotaçao triangulariza o bloco
+				let zz = if p >= 0.0 { p + zz } else { p - zz };
+				let x = t[nu][nu - 1];
+				let s = x.abs() + zz.abs();
+				let (p, q) = (x / s, zz / s);
+				let r = (p * p + q * q).sqrt();
+				rotate(&mut t, &mut z, (nu - 1, nu), (p / r, q / r), nu - 1, nu);
+			} else {
+				complex_blocks.push(nu - 1);
+			}
+			n -= 2;
+			iter = 0;
+		} else {
+			total_iter += 1;
+			if total_iter > 100 * size {
+				return Err(MatrixError::SingularMatrix);
+			}
+			// deslocamento
+			let mut x = t[nu][nu];
+			let mut y = t[nu - 1][nu - 1];
+			let mut w = t[nu][nu - 1] * t[nu - 1][nu];
+			if iter == 10 {
+				// deslocamento excepcional de Wilkinson
+				exshift += x;
+				for (i, row) in t.iter_mut().enumerate().take(nu + 1) {
+					row[i] -= x;
+				}
+				let s = t[nu][nu - 1].abs() + t[nu - 1][nu - 2].abs();
+				x = 0.75 * s;
+				y = x;
+				w = -0.4375 * s * s;
+			}
+			if iter == 30 {
+				let mut s = (y - x) / 2.0;
+				s = s * s + w;
+				if s > 0.0 {
+					s = s.sqrt();
+					if y < x {
+						s = -s;
+					}
+					s = x - w / ((y - x) / 2.0 + s);
+					for (i, row) in t.iter_mut().enumerate().take(nu + 1) {
+						row[i] -= s;
+					}
+					exshift += s;
+					x = 0.964;
+					y = x;
+					w = x;
+				}
+			}
+			iter += 1;
+
+			// procura dois elementos consecutivos pequenos na subdiagonal
+			let mut m = nu - 2;
+			let (mut p, mut q, mut r);
+			loop {
+				let zm = t[m][m];
+				let rm = x - zm;
+				let sm = y - zm;
+				p = (rm * sm - w) / t[m + 1][m] + t[m][m + 1];
+				q = t[m + 1][m + 1] - zm - rm - sm;
+				r = t[m + 2][m + 1];
+				let s = p.abs() + q.abs() + r.abs();
+				p /= s;
+				q /= s;
+				r /= s;
+				if m == l {
+					break;
+				}
+				if t[m][m - 1].abs() * (q.abs() + r.abs())
+					< eps * (p.abs() * (t[m - 1][m - 1].abs() + zm.abs() + t[m + 1][m + 1].abs())) {
+					break;
+				}
+				m -= 1;
+			}
+			for i in m + 2..=nu {
+				t[i][i - 2] = 0.0;
+				if i > m + 2 {
+					t[i][i - 3] = 0.0;
+				}
+			}
+
+			// passo duplo de QR nas linhas l..=n e colunas m..=n
+			for k in m..nu {
+				let notlast = k != nu - 1;
+				let mut xk = 0.0;
+				if k != m {
+					p = t[k][k - 1];
+					q = t[k + 1][k - 1];
+					r = if notlast { t[k + 2][k - 1] } else { 0.0 };
+					xk = p.abs() + q.abs() + r.abs();
+					if xk == 0.0 {
+						continue;
+					}
+					p /= xk;
+					q /= xk;
+					r /= xk;
+				}
+				let mut s = (p * p + q * q + r * r).sqrt();
+				if p < 0.0 {
+					s = -s;
+				}
+				if s == 0.0 {
+					continue;
+				}
+				if k != m {
+					t[k][k - 1] = -s * xk;
+				} else if l != m {
+					t[k][k - 1] = -t[k][k - 1];
+				}
+				p += s;
+				let (xr, yr, zr) = (p / s, q / s, r / s);
+				q /= p;
+				r /= p;
+				let (head, tail) = t.split_at_mut(k + 1);
+				let row_k = &mut head[k];
+				let (row_k1, rest) = tail.split_first_mut().expect("k + 1 < n");
+				let mut row_k2 = if notlast { rest.first_mut() } else { None };
+				for j in k..size {
+					let mut pj = row_k[j] + q * row_k1[j];
+					if let Some(row_k2) = row_k2.as_deref_mut() {
+						pj += r * row_k2[j];
+						row_k2[j] -= pj * zr;
+					}
+					row_k[j] -= pj * xr;
+					row_k1[j] -= pj * yr;
+				}
+				for m in [&mut t[..=nu.min(k + 3)], &mut z[..]] {
+					for row in m.iter_mut() {
+						let mut pi = xr * row[k] + yr * row[k + 1];
+						if notlast {
+							pi += zr * row[k + 2];
+							row[k + 2] -= pi * r;
+						}
+						row[k] -= pi;
+						row[k + 1] -= pi * q;
+					}
+				}
+			}
+		}
+	}
+
+	// zera o que esta abaixo da diagonal, exceto a subdiagonal dos blocos 2x2
+	for (i, row) in t.iter_mut().enumerate() {
+		for (j, x) in row.iter_mut().enumerate().take(i) {
+			if !(j + 1 == i && complex_blocks.contains(&j)) {
+				*x = 0.0;
+			}
+		}
+	}
+	Ok((z, t))
+}
+
+/// Autovalores `(real, imaginario)` lidos dos blocos diagonais da forma real de Schur `t`
+pub(crate) fn schur_eigenvalues(t: &Dense) -> Vec<(f64, f64)> {
+	let n = t.len();
+	let mut eigenvalues = Vec::with_capacity(n);
+	let mut i = 0;
+	while i < n {
+		if i + 1 < n && t[i + 1][i] != 0.0 {
+			let (a, b, c, d) = (t[i][i], t[i][i + 1], t[i + 1][i], t[i + 1][i + 1]);
+			let p = (a - d) / 2.0;
+			let im = (-(p * p + b * c)).max(0.0).sqrt();
+			eigenvalues.push(((a + d) / 2.0, im));
+			eigenvalues.push(((a + d) / 2.0, -im));
+			i += 2;
+		} else {
+			eigenvalues.push((t[i][i], 0.0));
+			i += 1;
+		}
+	}
+	eigenvalues
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MatrixInfo, TableMatrix, cholesky, conjugate_gradient, test_utils::*};
+
+	/// Laplaciano 2D de uma grade `m x m`, com no maximo 5 elementos por linha e banda `m`
+	fn poisson_2d<M: Matrix>(m: usize) -> M {
+		let n = m * m;
+		let mut a = M::new((n, n));
+		for i in 0..n {
+			a.set((i, i), 4.0);
+			if i % m != 0 {
+				a.set((i, i - 1), -1.0);
+				a.set((i - 1, i), -1.0);
+			}
+			if i >= m {
+				a.set((i, i - m), -1.0);
+				a.set((i - m, i), -1.0);
+			}
+		}
+		a
+	}
+
+	fn cg_iterations(a: &TableMatrix, b: &[f64]) -> usize {
+		(1..=4 * b.len()).find(|&k| conjugate_gradient(a, b, k, 1e-10).is_ok()).expect("CG did not converge")
+	}
+
+	fn assert_matches_full_cholesky<M: Matrix>() {
+		let a: M = poisson_2d(4);
+		let full = cholesky(&TableMatrix::from_info(&a.to_info())).unwrap();
+		let nnz_per_row = 5;
+		assert_matrix_close(&incomplete_cholesky(&a, nnz_per_row - 1).unwrap(), &full, 1e-12);
+		let ic0 = TableMatrix::from_info(&incomplete_cholesky(&a, 0).unwrap().to_info());
+		assert_ne!(ic0, full);
+	}
+
+	fn assert_keeps_the_pattern<M: Matrix>() {
+		let a: M = poisson_2d(4);
+		let l = incomplete_cholesky(&a, 0).unwrap();
+		for (pos, _) in l.iter_nnz() {
+			assert!(pos.1 <= pos.0 && a.get(pos) != 0.0, "{pos:?}");
+		}
+	}
+
+	matrix_tests!(incomplete_cholesky_with_full_fill_matches_cholesky => assert_matches_full_cholesky);
+	matrix_tests!(sparse: incomplete_cholesky_keeps_the_pattern_of_a => assert_keeps_the_pattern);
+
+	#[test]
+	fn incomplete_cholesky_rejects_non_square_and_indefinite() {
+		assert_eq!(incomplete_cholesky(&TableMatrix::new((2, 3)), 0), Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+		let indefinite = TableMatrix::from(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+		assert_eq!(incomplete_cholesky(&indefinite, 0), Err(MatrixError::SingularMatrix));
+	}
+
+	#[test]
+	fn incomplete_cholesky_preconditioning_speeds_up_cg() {
+		let a: TableMatrix = poisson_2d(8);
+		let n = a.size().0;
+		let b: Vec<f64> = (0..n).map(|i| 1.0 + (i % 7) as f64).collect();
+		let l_inv = TableMatrix::inverse(&incomplete_cholesky(&a, 0).unwrap()).unwrap();
+		let preconditioned = TableMatrix::mul(&TableMatrix::mul(&l_inv, &a), &l_inv.clone().transposed());
+		let preconditioned_b = l_inv.mul_vec(&b);
+		let plain = cg_iterations(&a, &b);
+		let with_ic = cg_iterations(&preconditioned, &preconditioned_b);
+		assert!(with_ic < plain, "IC(0): {with_ic} iterations, plain: {plain}");
+	}
+
+	fn assert_hessenberg<M: Matrix + Clone>() {
+		let n = 5;
+		let mut a = M::new((n, n));
+		for i in 0..n {
+			for j in 0..n {
+				a.set((i, j), ((i * 7 + j * 3) % 11) as f64 - 5.0);
+			}
+		}
+		let (q, h) = a.to_hessenberg().unwrap();
+		let qt = q.clone().transposed();
+		assert_matrix_close(&M::mul(&M::mul(&qt, &a), &q), &h, 1e-10);
+		assert_matrix_close(&M::mul(&qt, &q), &M::eye(n), 1e-12);
+		for i in 0..n {
+			for j in 0..i.saturating_sub(1) {
+				assert!(h.get((i, j)).abs() < 1e-12, "H[{i}][{j}] = {}", h.get((i, j)));
+			}
+		}
+		assert_eq!(M::new((2, 3)).to_hessenberg().err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	matrix_tests!(to_hessenberg_is_a_similarity_with_zeros_below_the_subdiagonal => assert_hessenberg);
+
+	/// Autovalores ordenados pela parte real e depois pela imaginaria, para comparar espectros
+	fn sorted_eigenvalues(mut eigenvalues: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+		eigenvalues.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		eigenvalues
+	}
+
+	fn assert_eigenvalues_close(got: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+		let got = sorted_eigenvalues(got);
+		assert_eq!(got.len(), expected.len());
+		for (g, e) in got.iter().zip(expected) {
+			assert!((g.0 - e.0).abs() < 1e-10 && (g.1 - e.1).abs() < 1e-10, "{got:?} != {expected:?}");
+		}
+	}
+
+	fn assert_schur_form<M: Matrix + Clone>() {
+		for n in [5, 20, 60] {
+			let a: M = random_square(n, 947 + n as u64);
+			let (q, t) = a.schur_form().unwrap();
+			let qt = q.clone().transposed();
+			assert_matrix_close(&M::mul(&qt, &q), &M::eye(n), 1e-10);
+			assert_matrix_close(&M::mul(&M::mul(&q, &t), &qt), &a, 1e-9);
+			for i in 0..n {
+				for j in 0..i.saturating_sub(1) {
+					assert!(t.get((i, j)) == 0.0, "T[{i}][{j}] = {}", t.get((i, j)));
+				}
+				// blocos 2x2 nao se sobrepoem: dois elementos seguidos da subdiagonal nunca sao ambos nao nulos
+				if i >= 2 {
+					assert!(t.get((i, i - 1)) == 0.0 || t.get((i - 1, i - 2)) == 0.0, "blocks overlap at {i}");
+				}
+			}
+			let eigenvalues = a.eigenvalues().unwrap();
+			assert_eq!(eigenvalues.len(), n);
+			let (re, im) = eigenvalues.iter().fold((0.0, 0.0), |(re, im), (r, i)| (re + r, im + i));
+			assert!((re - a.trace()).abs() < 1e-9, "{re} != {}", a.trace());
+			assert!(im.abs() < 1e-9);
+		}
+	}
+
+	fn assert_known_spectra<M: Matrix>() {
+		let rotation = M::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 1), -1.0), ((1, 0), 1.0)] });
+		assert_eigenvalues_close(rotation.eigenvalues().unwrap(), &[(0.0, -1.0), (0.0, 1.0)]);
+		let block = M::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 2.0), ((1, 1), 3.0), ((1, 2), 4.0), ((2, 1), -4.0), ((2, 2), 3.0)],
+		});
+		assert_eigenvalues_close(block.eigenvalues().unwrap(), &[(2.0, 0.0), (3.0, -4.0), (3.0, 4.0)]);
+	}
+
+	fn assert_schur_edge_cases<M: Matrix>() {
+		let (q, t) = M::new((0, 0)).schur_form().unwrap();
+		assert_eq!((q.size(), t.size()), ((0, 0), (0, 0)));
+		assert_eq!(M::new((0, 0)).eigenvalues().unwrap(), vec![]);
+		let (q, h) = M::new((0, 0)).to_hessenberg().unwrap();
+		assert_eq!((q.size(), h.size()), ((0, 0), (0, 0)));
+
+		let scalar = M::from_info(&MatrixInfo { size: (1, 1), values: vec![((0, 0), -2.5)] });
+		let (q, t) = scalar.schur_form().unwrap();
+		assert_eq!((q.get((0, 0)).abs(), t.get((0, 0))), (1.0, -2.5));
+		assert_eq!(scalar.eigenvalues().unwrap(), vec![(-2.5, 0.0)]);
+		let (q, h) = scalar.to_hessenberg().unwrap();
+		assert_eq!((q.get((0, 0)).abs(), h.get((0, 0))), (1.0, -2.5));
+	}
+
+	fn assert_schur_rejects_non_square<M: Matrix>() {
+		let expected = Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) });
+		assert_eq!(M::new((2, 3)).schur_form().err(), expected);
+		assert_eq!(M::new((2, 3)).eigenvalues().err(), expected);
+		assert_eq!(M::new((2, 3)).to_hessenberg().err(), expected);
+	}
+
+	matrix_tests!(
+		schur_form_is_an_orthogonal_similarity_with_trace_preserving_eigenvalues => assert_schur_form,
+		eigenvalues_of_known_spectra => assert_known_spectra,
+		schur_form_of_empty_and_scalar_matrices => assert_schur_edge_cases,
+		schur_form_rejects_non_square => assert_schur_rejects_non_square,
+	);
+
+	#[test]
+	fn dense_hessenberg_and_real_schur_reconstruct_a() {
+		let n = 8;
+		let a = to_dense(&random_square::<TableMatrix>(n, 947));
+		let product = |x: &Dense, y: &Dense| -> Dense {
+			(0..n).map(|i| (0..n).map(|j| (0..n).map(|k| x[i][k] * y[k][j]).sum()).collect()).collect()
+		};
+		let transpose = |x: &Dense| -> Dense { (0..n).map(|i| (0..n).map(|j| x[j][i]).collect()).collect() };
+		let assert_similar = |q: &Dense, h: &Dense| {
+			let back = product(&product(q, h), &transpose(q));
+			for i in 0..n {
+				for j in 0..n {
+					assert!((back[i][j] - a[i][j]).abs() < 1e-10, "({i}, {j}): {} != {}", back[i][j], a[i][j]);
+				}
+			}
+		};
+
+		let mut h = a.clone();
+		let q = hessenberg(&mut h);
+		assert_similar(&q, &h);
+		assert!((0..n).all(|i| (0..i.saturating_sub(1)).all(|j| h[i][j].abs() < 1e-12)));
+
+		let (z, t) = real_schur(&a).unwrap();
+		assert_similar(&z, &t);
+		let trace: f64 = (0..n).map(|i| a[i][i]).sum();
+		let sum: f64 = schur_eigenvalues(&t).iter().map(|(re, _)| re).sum();
+		assert!((sum - trace).abs() < 1e-10);
+	}
+}
@@ -0,0 +1,185 @@
+use std::{fs, num::{ParseFloatError, ParseIntError}, path::Path};
+
+use crate::{basic::{MatrixError, MatrixInfo, Pair}, sparse_format::SparseFormat};
+
+/// Erros de leitura e escrita de arquivos Matrix Market (`.mtx`)
+#[derive(Debug)]
+pub enum MatrixMarketError {
+	/// Falha ao acessar o arquivo
+	Io(std::io::Error),
+	/// O conteudo do arquivo nao descreve uma matriz valida
+	Parse(MatrixError),
+	/// O cabeçalho descreve uma variante do formato que nao é suportada, como `array`, `complex` ou `skew-symmetric`
+	UnsupportedFormat(String),
+}
+
+impl From<std::io::Error> for MatrixMarketError {
+	fn from(err: std::io::Error) -> Self {
+		MatrixMarketError::Io(err)
+	}
+}
+
+impl From<MatrixError> for MatrixMarketError {
+	fn from(err: MatrixError) -> Self {
+		MatrixMarketError::Parse(err)
+	}
+}
+
+/// Le uma matriz de um arquivo Matrix Market no formato `coordinate`, com valores `real` ou `integer`
+/// e simetria `general` ou `symmetric`. Em matrizes simetricas o arquivo guarda apenas um dos triangulos,
+/// e cada elemento fora da diagonal é espelhado na posiçao transposta.
+/// Linhas de comentario (começando com `%`) depois do cabeçalho sao ignoradas.
+pub fn read_matrix_market(path: &Path) -> Result<MatrixInfo, MatrixMarketError> {
+	let text = fs::read_to_string(path)?;
+	let banner = text.lines().next().unwrap_or("").to_lowercase();
+	let fields: Vec<&str> = banner.split_whitespace().collect();
+	let ["%%matrixmarket", "matrix", "coordinate", field, symmetry] = fields[..] else {
+		return Err(MatrixMarketError::UnsupportedFormat(banner));
+	};
+	if !matches!(field, "real" | "integer") || !matches!(symmetry, "general" | "symmetric") {
+		return Err(MatrixMarketError::UnsupportedFormat(banner));
+	}
+	let mut info = MatrixInfo::from_format_string(&text, SparseFormat::MatrixMarket)?;
+	if symmetry == "symmetric" {
+		let mirrored: Vec<_> = info.values.iter()
+			.filter(|((row, col), _)| row != col)
+			.map(|((row, col), value)| ((*col, *row), *value))
+			.collect();
+		info.values.extend(mirrored);
+	}
+	Ok(info)
+}
+
+/// Escreve a matriz em um arquivo Matrix Market `coordinate real general`, com os elementos ordenados por posiçao
+pub fn write_matrix_market(info: &MatrixInfo, path: &Path) -> Result<(), MatrixMarketError> {
+	fs::write(path, info.to_format_string(SparseFormat::MatrixMarket))?;
+	Ok(())
+}
+
+/// Erros de leitura de matrizes em CSV (`from_csv` e `from_csv_sparse`)
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+	/// O texto nao tem nenhuma linha com valores
+	EmptyInput,
+	/// A linha `row` (contando a partir de 0, sem as linhas em branco) tem um numero de colunas diferente das anteriores,
+	/// ou, no formato esparso, nao tem exatamente 3 campos
+	InconsistentColumns { row: usize },
+	/// A linha `row` do formato esparso tem uma posiçao fora do tamanho da matriz
+	OutOfBounds { row: usize },
+	/// Algum valor nao é um numero
+	ParseFloat(ParseFloatError),
+	/// Alguma posiçao do formato esparso nao é um inteiro nao negativo
+	ParseInt(ParseIntError),
+}
+
+impl From<ParseFloatError> for ParseError {
+	fn from(err: ParseFloatError) -> Self {
+		ParseError::ParseFloat(err)
+	}
+}
+
+impl From<ParseIntError> for ParseError {
+	fn from(err: ParseIntError) -> Self {
+		ParseError::ParseInt(err)
+	}
+}
+
+/// Linhas do CSV sem os espaços e o `\r` do fim de linha do Windows, ignorando as linhas em branco
+fn csv_rows(s: &str) -> impl Iterator<Item = Vec<&str>> {
+	s.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty())
+		.map(|line| line.split(',').map(str::trim).collect())
+}
+
+/// Escreve a matriz como um CSV denso, uma linha da matriz por linha do texto, com `0.0` nas posiçoes sem valor
+/// Os valores sao escritos com a menor representaçao que le de volta o mesmo `f64`.
+/// Complexidade de tempo: O(n * m + k)
+pub fn to_csv(info: &MatrixInfo) -> String {
+	info.to_dense().iter()
+		.map(|row| row.iter().map(|value| format!("{value:?}")).collect::<Vec<_>>().join(",") + "\n")
+		.collect()
+}
+
+/// Le um CSV denso, em que todas as linhas tem o mesmo numero de colunas, guardando apenas os valores
+/// com modulo maior que `EPSILON`. Aceita espaços em volta dos valores, linhas em branco e fins de linha `\r\n`.
+/// Complexidade de tempo: O(n * m)
+pub fn from_csv(s: &str) -> Result<MatrixInfo, ParseError> {
+	let mut data: Vec<Vec<f64>> = Vec::new();
+	for (row, fields) in csv_rows(s).enumerate() {
+		if data.first().is_some_and(|first| first.len() != fields.len()) {
+			return Err(ParseError::InconsistentColumns { row });
+		}
+		data.push(fields.into_iter().map(str::parse).collect::<Result<_, _>>()?);
+	}
+	if data.is_empty() {
+		return Err(ParseError::EmptyInput);
+	}
+	Ok(MatrixInfo::from_dense(&data).expect("rows were checked to have the same length"))
+}
+
+/// Escreve os valores como triplas `linha,coluna,valor` (formato COO), uma por linha do texto, na ordem de `values`
+/// O tamanho da matriz nao é escrito; `from_csv_sparse` o recebe como parametro.
+pub fn to_csv_sparse(info: &MatrixInfo) -> String {
+	info.values.iter()
+		.map(|((row, col), value)| format!("{row},{col},{value:?}\n"))
+		.collect()
+}
+
+/// Le as triplas `linha,coluna,valor` escritas por `to_csv_sparse`, para uma matriz de tamanho `size`
+/// Um texto sem triplas é a matriz nula.
+pub fn from_csv_sparse(s: &str, size: Pair) -> Result<MatrixInfo, ParseError> {
+	let mut values = Vec::new();
+	for (row, fields) in csv_rows(s).enumerate() {
+		let [i, j, value] = fields[..] else {
+			return Err(ParseError::InconsistentColumns { row });
+		};
+		let pos: Pair = (i.parse()?, j.parse()?);
+		if pos.0 >= size.0 || pos.1 >= size.1 {
+			return Err(ParseError::OutOfBounds { row });
+		}
+		values.push((pos, value.parse()?));
+	}
+	Ok(MatrixInfo { size, values })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::{sample_info, sorted};
+
+	#[test]
+	fn matrix_market_write_then_read_roundtrips() {
+		let path = std::env::temp_dir().join(format!("projeto-io-roundtrip-{}.mtx", std::process::id()));
+		let info = sample_info();
+		write_matrix_market(&info, &path).unwrap();
+		let read = read_matrix_market(&path);
+		fs::remove_file(&path).unwrap();
+		assert_eq!(sorted(read.unwrap()), sorted(info));
+	}
+
+	#[test]
+	fn dense_csv_roundtrips() {
+		let info = sample_info();
+		assert_eq!(sorted(from_csv(&to_csv(&info)).unwrap()), sorted(info));
+	}
+
+	#[test]
+	fn sparse_csv_roundtrips() {
+		let info = sample_info();
+		assert_eq!(sorted(from_csv_sparse(&to_csv_sparse(&info), info.size).unwrap()), sorted(info));
+	}
+
+	#[test]
+	fn from_csv_accepts_crlf_and_trailing_whitespace() {
+		let info = from_csv("1.0, 0.0 ,2.5  \r\n0.0,0,-3\t\r\n\r\n").unwrap();
+		assert_eq!(sorted(info), sorted(MatrixInfo { size: (2, 3), values: vec![((0, 0), 1.0), ((0, 2), 2.5), ((1, 2), -3.0)] }));
+	}
+
+	#[test]
+	fn from_csv_reports_errors() {
+		assert_eq!(from_csv(" \n").unwrap_err(), ParseError::EmptyInput);
+		assert_eq!(from_csv("1,2\n3\n").unwrap_err(), ParseError::InconsistentColumns { row: 1 });
+		assert!(matches!(from_csv("1,x\n").unwrap_err(), ParseError::ParseFloat(_)));
+	}
+}
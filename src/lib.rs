@@ -1,47 +1,92 @@
 #![allow(dead_code)]
 mod map_matrix;
 mod table_matrix;
+mod csr_matrix;
+mod csc_matrix;
+mod diagonal_matrix;
+mod band_matrix;
+mod symmetric_matrix;
+mod triangular_matrix;
 mod basic;
+mod sparse_format;
+mod sparse_vec;
+mod factorization;
+mod solvers;
+mod linalg;
+#[cfg(test)]
+mod test_utils;
 pub mod alloc;
+pub mod io;
+#[cfg(feature = "testing")]
+mod matrix_generator;
 use std::{collections::{HashMap}};
-pub use crate::{basic::{Matrix, MatrixInfo, Pair}, map_matrix::{HashMapStore, MapMatrix, TreeStore}};
+#[cfg(feature = "testing")]
+pub use crate::matrix_generator::MatrixGenerator;
+#[cfg(feature = "ahash")]
+pub use crate::map_matrix::AHashMapStore;
+pub use crate::{band_matrix::{BandMatrix, solve_banded}, basic::{Matrix, MatrixError, MatrixInfo, Pair, Scalar}, csc_matrix::CscMatrix, csr_matrix::CsrMatrix, linalg::{cholesky, condition_number_est, inverse_power_iteration, least_squares, nullity, power_iteration, qr_decompose, rank, solve_cholesky}, map_matrix::{HashMapStore, MapMatrix, OpenAddrHashStore, TreeStore}, solvers::{conjugate_gradient, jacobi}, sparse_format::SparseFormat, sparse_vec::SparseVec, symmetric_matrix::SymmetricMapMatrix, table_matrix::{MatrixLayout, lu_decompose, solve, solve_lu}, triangular_matrix::{LowerTriMatrix, UpperTriMatrix, solve_lower, solve_upper}};
 
 // Type aliases para facilitar o uso das diferentes implementações de matrizes
 
 /// Matriz baseada em HashMap
 pub type HashMapMatrix = MapMatrix<HashMapStore<Pair, f64>, HashMapStore<usize, Vec<(Pair, f64)>>>;
+/// Matriz baseada em HashMap com o hasher AHash, em vez do SipHash padrao
+#[cfg(feature = "ahash")]
+pub type AHashMapMatrix = MapMatrix<AHashMapStore<Pair, f64>, AHashMapStore<usize, Vec<(Pair, f64)>>>;
 /// Matriz baseada em BTreeMap
 pub type TreeMatrix = MapMatrix<TreeStore<Pair, f64>, TreeStore<usize, Vec<(Pair, f64)>>>;
-/// Matriz baseada em tabela (vetor de vetores)
+/// Matriz baseada em uma tabela hash de enderecamento aberto com sondagem linear
+pub type OpenAddrMatrix = MapMatrix<OpenAddrHashStore, HashMapStore<usize, Vec<(Pair, f64)>>>;
+/// Matriz simetrica baseada em HashMap, guardando apenas o triangulo superior
+pub type SymmetricHashMapMatrix = SymmetricMapMatrix<HashMapStore<Pair, f64>, HashMapStore<usize, Vec<(Pair, f64)>>>;
+/// Matriz simetrica baseada em BTreeMap, guardando apenas o triangulo superior
+pub type SymmetricTreeMatrix = SymmetricMapMatrix<TreeStore<Pair, f64>, TreeStore<usize, Vec<(Pair, f64)>>>;
+/// Matriz densa baseada em uma tabela guardada em um unico vetor
 pub type TableMatrix = table_matrix::TableMatrix;
+/// Matriz diagonal, que guarda apenas a diagonal principal
+pub type DiagonalMatrix = diagonal_matrix::DiagonalMatrix;
+
+impl From<HashMapMatrix> for TreeMatrix {
+	fn from(m: HashMapMatrix) -> Self {
+		m.clone_as()
+	}
+}
+
+impl From<TreeMatrix> for HashMapMatrix {
+	fn from(m: TreeMatrix) -> Self {
+		m.clone_as()
+	}
+}
 
 /// Epsilon para comparações de ponto flutuante
 pub const EPSILON : f64 = 1e-8;
 
 
 
-fn info_eq(expected: &MatrixInfo, current: &MatrixInfo) -> bool {
-    if expected.size != current.size {
+/// Compara duas matrizes, possivelmente de tipos diferentes, posiçao a posiçao
+/// Sao iguais se tem o mesmo tamanho e, em toda posiçao definida em alguma delas, os valores diferem no maximo `eps`
+/// (posiçoes sem valor valem 0)
+pub fn approx_eq(a: &impl Matrix, b: &impl Matrix, eps: f64) -> bool {
+    if a.size() != b.size() {
         return false;
     }
-    let mut exp_map = HashMap::new();
-    for (pos, value) in expected.values.iter() {
-        exp_map.insert(pos, value);
-    }
-    for (pos, value) in current.values.iter() {
-        match exp_map.get(pos) {
-            Some(v) => {
-                if (*v - value).abs() > EPSILON {
-                    return false;
-                }
-            },
-            None => return false,
-        }
-    }
-    true
+    let a_values: HashMap<Pair, f64> = a.to_info().values.into_iter().collect();
+    let b_values: HashMap<Pair, f64> = b.to_info().values.into_iter().collect();
+    a_values.keys().chain(b_values.keys()).all(|pos| {
+        let va = a_values.get(pos).copied().unwrap_or(0.0);
+        let vb = b_values.get(pos).copied().unwrap_or(0.0);
+        (va - vb).abs() <= eps
+    })
 }
 
-fn diff(expected: &MatrixInfo, current: &MatrixInfo) -> Vec<(Pair, (Option<f64>, Option<f64>))> {
+/// Diferença em uma posiçao: (valor esperado, valor atual)
+type DiffEntry = (Pair, (Option<f64>, Option<f64>));
+
+/// Posiçoes de `current` cujo valor nao esta em `expected` (a menos de `EPSILON`), em ordem de posiçao
+fn diff(expected: &MatrixInfo, current: &MatrixInfo) -> Vec<DiffEntry> {
+    let (mut expected, mut current) = (expected.clone(), current.clone());
+    expected.sort_by_position();
+    current.sort_by_position();
     let mut exp_map = HashMap::new();
     for (pos, value) in expected.values.iter() {
         exp_map.insert(pos, value);
@@ -66,3 +111,19 @@ fn mul<M :  Matrix>(ainfo: &MatrixInfo, binfo: &MatrixInfo)  -> MatrixInfo {
     
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_does_not_depend_on_the_order_of_the_values() {
+        let expected = MatrixInfo { size: (2, 2), values: vec![((1, 1), 4.0), ((0, 0), 1.0)] };
+        let current = MatrixInfo { size: (2, 2), values: vec![((1, 1), 5.0), ((0, 1), 2.0), ((0, 0), 1.0)] };
+        let mut reversed = current.clone();
+        reversed.values.reverse();
+        let entries = vec![((0, 1), (None, Some(2.0))), ((1, 1), (Some(4.0), Some(5.0)))];
+        assert_eq!(diff(&expected, &current), entries);
+        assert_eq!(diff(&expected, &reversed), entries);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{EPSILON, basic::{Matrix, MatrixError}, factorization, solvers::vec_ops::{dot, norm}, table_matrix::TableMatrix};
+
+/// Vetor `v` unitario do refletor de Householder `H = I - 2 v v^T` que leva `x` a um multiplo de `e_1`
+///
+/// O sinal é escolhido para que `H x = -sign(x_0) ||x|| e_1`, evitando o cancelamento de `x_0 + sign(x_0) ||x||`.
+/// Se `x` for nulo nao ha o que refletir e o vetor retornado é nulo (`H = I`).
+fn householder_vector(x: &[f64]) -> Vec<f64> {
+	let x_norm = norm(x);
+	let mut v = x.to_vec();
+	if x_norm == 0.0 {
+		return v;
+	}
+	v[0] += x[0].signum() * x_norm;
+	let v_norm = norm(&v);
+	for vi in v.iter_mut() {
+		*vi /= v_norm;
+	}
+	v
+}
+
+/// Decomposiçao QR `A = Q * R` por refletores de Householder, retornando `(Q, R)`
+///
+/// Para uma matriz `m x n`, `Q` é ortogonal `m x m` e `R` é triangular superior `m x n`.
+/// Cada refletor zera a coluna `k` de uma copia de `A` abaixo da diagonal e é acumulado em `Q`, que começa como a identidade.
+/// Retorna `MatrixError::DimensionMismatch` se `A` tiver mais colunas que linhas.
+/// Complexidade de tempo: O(m^2 n)
+pub fn qr_decompose(a: &TableMatrix) -> Result<(TableMatrix, TableMatrix), MatrixError> {
+	let (m, n) = a.size;
+	if n > m {
+		return Err(MatrixError::DimensionMismatch { expected: (n, n), got: a.size });
+	}
+	let mut r = a.clone();
+	let mut q = TableMatrix::eye(m);
+	for k in 0..n.min(m.saturating_sub(1)) {
+		let x: Vec<f64> = (k..m).map(|i| r[(i, k)]).collect();
+		let v = householder_vector(&x);
+		// R = H R, nas linhas k.. (as colunas antes de k ja sao nulas nessas linhas)
+		for j in k..n {
+			let s: f64 = v.iter().enumerate().map(|(i, vi)| vi * r[(k + i, j)]).sum();
+			for (i, vi) in v.iter().enumerate() {
+				r[(k + i, j)] -= 2.0 * vi * s;
+			}
+		}
+		// Q = Q H, nas colunas k..
+		for row in 0..m {
+			let s: f64 = v.iter().enumerate().map(|(i, vi)| q[(row, k + i)] * vi).sum();
+			for (i, vi) in v.iter().enumerate() {
+				q[(row, k + i)] -= 2.0 * s * vi;
+			}
+		}
+		for i in k + 1..m {
+			r[(i, k)] = 0.0;
+		}
+	}
+	Ok((q, r))
+}
+
+/// Soluçao de minimos quadrados `x` que minimiza `||A x - b||_2`, para `A` `m x n` com `m >= n`
+///
+/// Usa a decomposiçao QR em vez das equaçoes normais `A^T A x = A^T b`, que elevariam ao quadrado o
+/// condicionamento de `A`: com `A = Q R`, `x` resolve o sistema triangular `R_1 x = (Q^T b)_1` nas `n` primeiras linhas.
+/// Retorna `MatrixError::DimensionMismatch` se `A` tiver mais colunas que linhas ou `b` nao tiver m elementos, e
+/// `MatrixError::SingularMatrix` se as colunas de `A` forem linearmente dependentes (algum `|R[i][i]| < EPSILON`).
+/// Complexidade de tempo: O(m^2 n)
+pub fn least_squares(a: &TableMatrix, b: &[f64]) -> Result<Vec<f64>, MatrixError> {
+	let (m, n) = a.size;
+	if b.len() != m {
+		return Err(MatrixError::DimensionMismatch { expected: (m, 1), got: (b.len(), 1) });
+	}
+	let (q, r) = qr_decompose(a)?;
+	let qtb: Vec<f64> = q.iter_cols().take(n).map(|col| dot(&col, b)).collect();
+	let mut x = vec![0.0; n];
+	for i in (0..n).rev() {
+		if r[(i, i)].abs() < EPSILON {
+			return Err(MatrixError::SingularMatrix);
+		}
+		let sum: f64 = (i + 1..n).map(|j| r[(i, j)] * x[j]).sum();
+		x[i] = (qtb[i] - sum) / r[(i, i)];
+	}
+	Ok(x)
+}
+
+/// Posto de `A`, o numero de linhas (ou colunas) linearmente independentes
+///
+/// Faz a eliminaçao de Gauss com pivoteamento total sobre uma copia densa da matriz: em cada passo o pivo é o
+/// valor de maior modulo da submatriz restante, e a eliminaçao para quando ele tem modulo ate `eps`.
+/// A copia usa O(n * m) de memoria mesmo para matrizes esparsas, e a eliminaçao em geral a preenche por completo.
+/// Complexidade de tempo: O(n * m * min(n, m))
+pub fn rank(a: &impl Matrix, eps: f64) -> usize {
+	let mut dense = factorization::to_dense(a);
+	let (rows, cols) = a.size();
+	for k in 0..rows.min(cols) {
+		let Some((p, q)) = (k..rows)
+			.flat_map(|i| (k..cols).map(move |j| (i, j)))
+			.max_by(|x, y| dense[x.0][x.1].abs().total_cmp(&dense[y.0][y.1].abs()))
+			.filter(|(i, j)| dense[*i][*j].abs() > eps)
+		else {
+			return k;
+		};
+		dense.swap(k, p);
+		for row in dense.iter_mut() {
+			row.swap(k, q);
+		}
+		let (top, bottom) = dense.split_at_mut(k + 1);
+		let pivot_row = &top[k];
+		for row in bottom.iter_mut() {
+			let factor = row[k] / pivot_row[k];
+			for (x, p) in row[k..].iter_mut().zip(&pivot_row[k..]) {
+				*x -= factor * p;
+			}
+		}
+	}
+	rows.min(cols)
+}
+
+/// Deficiencia de posto `min(linhas, colunas) - rank(A)`, com o mesmo `eps` e o mesmo custo de `rank`
+/// Para matrizes quadradas é a dimensao do nucleo de `A`.
+pub fn nullity(a: &impl Matrix, eps: f64) -> usize {
+	let (rows, cols) = a.size();
+	rows.min(cols) - rank(a, eps)
+}
+
+/// Fatoraçao de Cholesky `A = L * L^T` de uma matriz simetrica definida positiva, retornando o fator triangular inferior `L`
+///
+/// Apenas o triangulo inferior de `A` é lido, entao a simetria nao é verificada (ver `Matrix::is_positive_definite`).
+/// Retorna `MatrixError::DimensionMismatch` se `A` nao for quadrada e `MatrixError::SingularMatrix` se algum pivo
+/// nao for positivo, ou seja, se `A` nao for definida positiva.
+/// Complexidade de tempo: O(n^3)
+pub fn cholesky(a: &TableMatrix) -> Result<TableMatrix, MatrixError> {
+	let (n, m) = a.size;
+	if n != m {
+		return Err(MatrixError::DimensionMismatch { expected: (n, n), got: a.size });
+	}
+	factorization::cholesky_dense(&factorization::to_dense(a)).map(TableMatrix::from)
+}
+
+/// Resolve `A * x = b` a partir do fator `L` de `cholesky`, por substituiçao direta em `L * y = b`
+/// e reversa em `L^T * x = y`
+/// Entra em panico se `L` nao for quadrada ou `b` nao tiver n elementos.
+/// Complexidade de tempo: O(n^2)
+pub fn solve_cholesky(l: &TableMatrix, b: &[f64]) -> Vec<f64> {
+	let n = l.size.0;
+	assert!(l.size.1 == n && b.len() == n, "solve_cholesky dimension mismatch");
+	let mut y = vec![0.0; n];
+	for i in 0..n {
+		let sum: f64 = (0..i).map(|j| l[(i, j)] * y[j]).sum();
+		y[i] = (b[i] - sum) / l[(i, i)];
+	}
+	let mut x = vec![0.0; n];
+	for i in (0..n).rev() {
+		let sum: f64 = (i + 1..n).map(|j| l[(j, i)] * x[j]).sum();
+		x[i] = (y[i] - sum) / l[(i, i)];
+	}
+	x
+}
+
+/// Autovalor de maior modulo, e o autovetor unitario correspondente, do operador linear `apply` de dimensao `n`,
+/// pelo metodo da potencia
+///
+/// Parte de um vetor unitario aleatorio e a cada iteraçao aplica o operador e normaliza o resultado.
+/// O autovalor é o quociente de Rayleigh `v . (A v)`, e o metodo para quando dois quocientes seguidos diferem menos
+/// que `tol` ou apos `max_iter` iteraçoes, retornando a ultima estimativa.
+fn dominant_eigenpair(apply: impl Fn(&[f64]) -> Vec<f64>, n: usize, max_iter: usize, tol: f64) -> (f64, Vec<f64>) {
+	let mut v: Vec<f64> = (0..n).map(|_| rand::random_range(-1.0..1.0)).collect();
+	let v_norm = norm(&v);
+	for vi in v.iter_mut() {
+		*vi /= v_norm;
+	}
+	let mut lambda = f64::NAN;
+	for _ in 0..max_iter {
+		let w = apply(&v);
+		let rayleigh = dot(&v, &w);
+		let w_norm = norm(&w);
+		if w_norm == 0.0 {
+			return (0.0, v);
+		}
+		let converged = (rayleigh - lambda).abs() < tol;
+		lambda = rayleigh;
+		if converged {
+			break;
+		}
+		v = w.into_iter().map(|x| x / w_norm).collect();
+	}
+	(lambda, v)
+}
+
+/// Estimativa do numero de condiçao `||A||_2 * ||A^-1||_2 = sigma_max / sigma_min` de uma matriz quadrada
+///
+/// É uma estimativa, nao um calculo exato: `sigma_max^2` vem do metodo da potencia em `A^T A` e `1 / sigma_min^2`
+/// do metodo da potencia em `(A^T A)^-1 = A^-1 A^-T`, aplicado com as fatoraçoes LU de `A` e de `A^T`,
+/// cada um com ate `max_iter` iteraçoes. O resultado tende a ser menor que o valor real quando as iteraçoes nao convergem.
+/// Para matrizes com ate 2 linhas o valor é exato: os valores singulares de uma matriz 2x2 saem de
+/// `sigma_max * sigma_min = |det(A)|` e `sigma_max^2 + sigma_min^2 = ||A||_F^2`.
+/// Retorna `f64::INFINITY` para matrizes singulares (algum pivo da LU, ou o determinante, com modulo menor que `EPSILON`).
+/// Entra em panico se a matriz nao for quadrada.
+/// Complexidade de tempo: O(n^3 + max_iter * n^2)
+pub fn condition_number_est(a: &TableMatrix, max_iter: usize) -> f64 {
+	let n = a.size.0;
+	assert_eq!(n, a.size.1, "condition_number_est requires a square matrix");
+	match n {
+		0 => return 1.0,
+		1 => return if a[(0, 0)].abs() < EPSILON { f64::INFINITY } else { 1.0 },
+		2 => {
+			let det = (a[(0, 0)] * a[(1, 1)] - a[(0, 1)] * a[(1, 0)]).abs();
+			if det < EPSILON {
+				return f64::INFINITY;
+			}
+			let frobenius_sq = a.sum_sq();
+			// sigma_max^2 e sigma_min^2 sao as raizes de x^2 - ||A||_F^2 x + det^2
+			let root = (frobenius_sq * frobenius_sq - 4.0 * det * det).max(0.0).sqrt();
+			return (frobenius_sq + root) / (2.0 * det);
+		}
+		_ => {}
+	}
+	let dense = factorization::to_dense(a);
+	let dense_t = factorization::to_dense(&a.clone().transposed());
+	let (Ok((lu, perm)), Ok((lu_t, perm_t))) = (factorization::lu_dense(&dense), factorization::lu_dense(&dense_t)) else {
+		return f64::INFINITY;
+	};
+	let (sigma_max_sq, _) = dominant_eigenpair(|v| a.vec_mul(&a.mul_vec(v)), n, max_iter, EPSILON);
+	let (inv_sigma_min_sq, _) = dominant_eigenpair(
+		|v| factorization::lu_solve(&lu, &perm, &factorization::lu_solve(&lu_t, &perm_t, v)),
+		n,
+		max_iter,
+		EPSILON,
+	);
+	(sigma_max_sq * inv_sigma_min_sq).sqrt()
+}
+
+/// Autovalor de maior modulo de uma matriz quadrada e o autovetor unitario correspondente, pelo metodo da potencia
+///
+/// Cada iteraçao faz um produto `mul_vec`, em O(k + n), entao o metodo aproveita a esparsidade de `A`.
+/// Para quando dois quocientes de Rayleigh seguidos diferem menos que `tol`, ou apos `max_iter` iteraçoes,
+/// retornando a ultima estimativa. A convergencia depende da razao `|lambda_2 / lambda_1|` entre os dois maiores
+/// autovalores: o metodo nao converge se ela for 1, por exemplo com autovalores `lambda` e `-lambda`.
+/// Em matrizes simetricas o quociente de Rayleigh converge com o dobro da velocidade do vetor, entao o erro
+/// de `A v - lambda v` fica na ordem de `sqrt(tol)`.
+/// Entra em panico se a matriz nao for quadrada.
+pub fn power_iteration(a: &impl Matrix, max_iter: usize, tol: f64) -> (f64, Vec<f64>) {
+	assert!(a.is_square(), "power_iteration requires a square matrix");
+	dominant_eigenpair(|v| a.mul_vec(v), a.size().0, max_iter, tol)
+}
+
+/// Autovalor de menor modulo de uma matriz quadrada e o autovetor unitario correspondente, pelo metodo da potencia
+/// aplicado a `A^-1`, cujo maior autovalor é `1 / lambda_min`
+///
+/// `A^-1 v` é calculado resolvendo `A x = v` com a fatoraçao LU de uma copia densa de `A`, feita uma unica vez em O(n^3);
+/// cada iteraçao custa O(n^2). O criterio de parada de `tol` é o de `power_iteration`, sobre os autovalores de `A^-1`.
+/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada e `MatrixError::SingularMatrix` se ela for
+/// singular (algum pivo da LU com modulo menor que `EPSILON`), quando o menor autovalor é 0.
+pub fn inverse_power_iteration(a: &impl Matrix, max_iter: usize, tol: f64) -> Result<(f64, Vec<f64>), MatrixError> {
+	let (n, m) = a.size();
+	if n != m {
+		return Err(MatrixError::DimensionMismatch { expected: (n, n), got: a.size() });
+	}
+	let (lu, perm) = factorization::lu_dense(&factorization::to_dense(a))?;
+	let (mu, v) = dominant_eigenpair(|v| factorization::lu_solve(&lu, &perm, v), n, max_iter, tol);
+	Ok((1.0 / mu, v))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{MatrixInfo, test_utils::*};
+
+	#[test]
+	fn qr_factors_are_orthogonal_and_triangular() {
+		let a = TableMatrix::from(vec![
+			vec![12.0, -51.0, 4.0],
+			vec![6.0, 167.0, -68.0],
+			vec![-4.0, 24.0, -41.0],
+			vec![1.0, 2.0, 3.0],
+		]);
+		let (q, r) = qr_decompose(&a).unwrap();
+		assert_matrix_close(&TableMatrix::mul(&q, &r), &a, 1e-9);
+		assert_matrix_close(&TableMatrix::mul(&q, &q.clone().transposed()), &TableMatrix::eye(4), 1e-12);
+		for i in 0..4 {
+			for j in 0..i.min(3) {
+				assert!(r.get((i, j)).abs() < 1e-12);
+			}
+		}
+	}
+
+	#[test]
+	fn least_squares_minimizes_the_residual() {
+		let a = TableMatrix::from(vec![vec![1.0, 1.0], vec![1.0, 2.0], vec![1.0, 3.0], vec![1.0, 4.0]]);
+		let b = [6.0, 5.0, 7.0, 10.0];
+		let x = least_squares(&a, &b).unwrap();
+		assert!((x[0] - 3.5).abs() < 1e-12 && (x[1] - 1.4).abs() < 1e-12);
+		let best = residual(&a, &x, &b);
+		for delta in [[0.1, 0.0], [0.0, -0.1], [0.05, 0.05], [-0.2, 0.1]] {
+			assert!(best < residual(&a, &[x[0] + delta[0], x[1] + delta[1]], &b));
+		}
+	}
+
+	fn spd5() -> TableMatrix {
+		TableMatrix::from(vec![
+			vec![6.0, 2.0, 1.0, 0.0, 0.0],
+			vec![2.0, 7.0, 1.0, 1.0, 0.0],
+			vec![1.0, 1.0, 8.0, 2.0, 1.0],
+			vec![0.0, 1.0, 2.0, 9.0, 3.0],
+			vec![0.0, 0.0, 1.0, 3.0, 10.0],
+		])
+	}
+
+	#[test]
+	fn cholesky_factor_reproduces_matrix() {
+		let a = spd5();
+		let l = cholesky(&a).unwrap();
+		assert!((0..5).all(|i| (i + 1..5).all(|j| l.get((i, j)) == 0.0)));
+		assert_matrix_close(&TableMatrix::mul(&l, &l.clone().transposed()), &a, 1e-12);
+	}
+
+	#[test]
+	fn solve_cholesky_solves_spd_system() {
+		let a = spd5();
+		let x = [1.0, -2.0, 3.0, 0.5, -1.0];
+		let b = a.mul_vec(&x);
+		let solution = solve_cholesky(&cholesky(&a).unwrap(), &b);
+		for (got, expected) in solution.iter().zip(x) {
+			assert!((got - expected).abs() < 1e-12);
+		}
+	}
+
+	#[test]
+	fn cholesky_rejects_non_spd() {
+		let indefinite = TableMatrix::from(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+		assert_eq!(cholesky(&indefinite).err(), Some(MatrixError::SingularMatrix));
+		assert_eq!(cholesky(&TableMatrix::new((2, 3))).err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	#[test]
+	fn condition_number_of_identity_is_one() {
+		for n in [2, 5] {
+			assert!((condition_number_est(&TableMatrix::eye(n), 100) - 1.0).abs() < 1e-6);
+		}
+	}
+
+	#[test]
+	fn condition_number_of_diagonal_is_ratio_of_extremes() {
+		assert!((condition_number_est(&TableMatrix::from_diagonal(&[1.0, 1000.0]), 100) - 1000.0).abs() < 1e-6);
+		assert!((condition_number_est(&TableMatrix::from_diagonal(&[2.0, 1.0, 50.0]), 100) - 50.0).abs() < 1e-3);
+	}
+
+	#[test]
+	fn condition_number_of_singular_matrix_is_infinite() {
+		let singular = TableMatrix::from(vec![vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 6.0], vec![0.0, 1.0, 1.0]]);
+		assert_eq!(condition_number_est(&singular, 100), f64::INFINITY);
+	}
+
+	fn assert_rank<M: Matrix>() {
+		for n in [1, 4, 7] {
+			assert_eq!(rank(&M::eye(n), EPSILON), n);
+			assert_eq!(rank(&M::new((n, n)), EPSILON), 0);
+			assert_eq!(nullity(&M::new((n, n)), EPSILON), n);
+		}
+		let (u, v) = ([1.0, -2.0, 0.5, 3.0], [2.0, 0.0, -1.0]);
+		assert_eq!(rank(&M::outer(&u, &v), EPSILON), 1);
+	}
+
+	matrix_tests!(rank_of_identity_zero_and_outer_product => assert_rank);
+
+	fn assert_rank_plus_nullity<M: Matrix>() {
+		for (k, (rows, inner, cols)) in [(6, 3, 5), (4, 4, 4), (3, 2, 8), (9, 5, 2)].into_iter().enumerate() {
+			let b: M = random((rows, inner), 1054 + k as u64);
+			let c: M = random((inner, cols), 4501 + k as u64);
+			let a = M::mul(&b, &c);
+			let expected = inner.min(rows).min(cols);
+			assert_eq!(rank(&a, 1e-9), expected);
+			assert_eq!(rank(&a, 1e-9) + nullity(&a, 1e-9), rows.min(cols));
+		}
+	}
+
+	matrix_tests!(rank_plus_nullity_is_the_smallest_dimension => assert_rank_plus_nullity);
+
+	fn eigen_residual(a: &impl Matrix, lambda: f64, v: &[f64]) -> f64 {
+		let av = a.mul_vec(v);
+		norm(&av.iter().zip(v).map(|(p, q)| p - lambda * q).collect::<Vec<_>>())
+	}
+
+	fn assert_power_iteration<M: Matrix>() {
+		let tol = 1e-10;
+		let triangular = M::from_info(&MatrixInfo::from_dense(&[vec![4.0, 1.0, 2.0], vec![0.0, 2.0, 1.0], vec![0.0, 0.0, 1.0]]).unwrap());
+		let (lambda, v) = power_iteration(&triangular, 1000, tol);
+		assert!((lambda - 4.0).abs() < tol * 10.0, "{lambda}");
+		assert!((norm(&v) - 1.0).abs() < 1e-12);
+		assert!(eigen_residual(&triangular, lambda, &v) < tol * 10.0);
+
+		let symmetric: M = tridiagonal(3, 2.0, 1.0);
+		let (lambda, v) = power_iteration(&symmetric, 1000, tol);
+		assert!((lambda - (2.0 + 2f64.sqrt())).abs() < tol * 10.0, "{lambda}");
+		assert!(eigen_residual(&symmetric, lambda, &v) < tol.sqrt() * 10.0);
+	}
+
+	matrix_tests!(power_iteration_finds_the_dominant_eigenpair => assert_power_iteration);
+
+	fn assert_power_iteration_of_identity<M: Matrix>() {
+		for n in [1, 4] {
+			let (lambda, v) = power_iteration(&M::eye(n), 100, 1e-12);
+			assert!((lambda - 1.0).abs() < 1e-12);
+			assert!((norm(&v) - 1.0).abs() < 1e-12);
+		}
+	}
+
+	matrix_tests!(power_iteration_of_identity_is_one => assert_power_iteration_of_identity);
+
+	#[test]
+	fn inverse_power_iteration_finds_the_smallest_eigenvalue() {
+		let tol = 1e-10;
+		let symmetric = TableMatrix::from(vec![vec![2.0, 1.0, 0.0], vec![1.0, 2.0, 1.0], vec![0.0, 1.0, 2.0]]);
+		let (lambda, v) = inverse_power_iteration(&symmetric, 1000, tol).unwrap();
+		assert!((lambda - (2.0 - 2f64.sqrt())).abs() < 1e-8, "{lambda}");
+		assert!(eigen_residual(&symmetric, lambda, &v) < tol.sqrt() * 10.0);
+
+		let singular = TableMatrix::from(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+		assert_eq!(inverse_power_iteration(&singular, 100, tol).err(), Some(MatrixError::SingularMatrix));
+		assert_eq!(
+			inverse_power_iteration(&TableMatrix::new((2, 3)), 100, tol).err(),
+			Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) })
+		);
+	}
+}
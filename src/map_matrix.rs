@@ -1,11 +1,15 @@
 mod tree_map;
 mod hash_map;
 mod transposable_map;
+mod open_addr;
 pub use hash_map::HashMapStore;
+#[cfg(feature = "ahash")]
+pub use hash_map::AHashMapStore;
+pub use open_addr::OpenAddrHashStore;
 pub use tree_map::TreeStore;
-use transposable_map::TransposableMap;
-use crate::basic::{Matrix, MatrixInfo, Pair};
-use std::borrow::Cow; 
+pub(crate) use transposable_map::TransposableMap;
+use crate::{EPSILON, basic::{Matrix, MatrixError, MatrixInfo, Pair, Scalar, check_add_sizes, check_mul_sizes, inner_products}};
+use std::{borrow::Cow, fmt, ops::{AddAssign, MulAssign, Range}};
 
 
 /// Estrutura que guarda um mapa de chaves de do K para valores do tipo U
@@ -14,6 +18,12 @@ pub trait Map<K : Copy, U : Clone > : Clone {
 	// Cria um mapa a partir de um iterador de pares (K,U)
 	fn from_iter<I: IntoIterator<Item=(K,U)>>(iter: I) -> Self;
 
+	/// Cria um mapa a partir de um vetor de pares ja alocado, o mesmo que `from_iter` sem coletar o iterador
+	/// Por padrao chama `from_iter`
+	fn from_entries(entries: Vec<(K, U)>) -> Self {
+		Self::from_iter(entries)
+	}
+
 	/// Insere ou atualiza o valor associado a chave
 	fn set_or_insert(&mut self, key: K, value: U);
 	
@@ -23,6 +33,28 @@ pub trait Map<K : Copy, U : Clone > : Clone {
 	/// Retorna uma referencia ao valor associado a chave, ou None se a chave nao existir
 	fn get(&self, key: &K) -> Option<&U>;
 
+	/// Retorna o numero de pares guardados no mapa
+	fn len(&self) -> usize;
+
+	/// Retorna os valores associados a cada uma das chaves, na mesma ordem de `keys`
+	/// Por padrao chama `get` para cada chave
+	fn batch_get<'a>(&'a self, keys: &[K]) -> Vec<Option<&'a U>> {
+		keys.iter().map(|key| self.get(key)).collect()
+	}
+
+	/// Insere ou atualiza todos os pares de `items`; se uma chave aparecer mais de uma vez, fica o ultimo valor
+	/// Por padrao chama `set_or_insert` para cada par
+	fn batch_set(&mut self, items: impl IntoIterator<Item=(K, U)>) {
+		for (key, value) in items {
+			self.set_or_insert(key, value);
+		}
+	}
+
+	/// Verifica se o mapa nao tem nenhum par
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
 	/// Retorna um iterador sobre os pares (K, U) do mapa
 	/// Cow<'a, U> é copy-on-write, permitindo retornar referencias ou valores proprietarios dependendo do contexto, otimizando o uso de memoria
 	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(K, Cow<'a, U>)> + 'a>;
@@ -32,6 +64,85 @@ pub trait Map<K : Copy, U : Clone > : Clone {
 	/// Permite modificar os valores diretamente durante a iteraçao
 	fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item=(K, &'a mut U)> + 'a>;
 
+	/// Remove todos os pares do mapa, retornando-os por valor, sem clonar
+	/// Por padrao clona os pares de `iter` e substitui o mapa por um vazio, para mapas sem um `drain` proprio
+	fn drain<'a>(&'a mut self) -> Box<dyn Iterator<Item=(K, U)> + 'a> where K: 'a, U: 'a {
+		let pairs: Vec<(K, U)> = self.iter().map(|(key, value)| (key, value.into_owned())).collect();
+		*self = Self::from_iter(std::iter::empty());
+		Box::new(pairs.into_iter())
+	}
+
+	/// Consome o mapa, retornando seus pares por valor em um vetor
+	/// Por padrao coleta os pares de `drain`
+	fn into_entries(mut self) -> Vec<(K, U)> {
+		self.drain().collect()
+	}
+
+	/// Retorna as chaves do mapa em ordem crescente
+	/// Por padrao coleta as chaves de `iter` e as ordena, em O(n log n)
+	fn sorted_keys(&self) -> Vec<K> where K: Ord {
+		let mut keys: Vec<K> = self.iter().map(|(key, _)| key).collect();
+		keys.sort_unstable();
+		keys
+	}
+
+	/// Retorna um iterador sobre os pares (K, U) do mapa em ordem crescente de chave
+	/// Por padrao percorre `sorted_keys` consultando cada chave com `get`
+	fn iter_sorted<'a>(&'a self) -> Box<dyn Iterator<Item=(K, Cow<'a, U>)> + 'a> where K: Ord + 'a {
+		Box::new(self.sorted_keys().into_iter()
+			.filter_map(move |key| self.get(&key).map(|value| (key, Cow::Borrowed(value)))))
+	}
+
+	/// Retorna quantos elementos o mapa comporta sem realocar memoria
+	/// Por padrao, para mapas sem pre-alocaçao, é o proprio numero de elementos
+	fn capacity(&self) -> usize {
+		self.len()
+	}
+
+	/// Reserva espaço para pelo menos `additional` novos elementos
+	/// Por padrao nao faz nada, para mapas que nao suportam pre-alocaçao
+	fn reserve(&mut self, _additional: usize) {}
+
+	/// Libera a memoria reservada que nao esta sendo usada
+	/// Por padrao nao faz nada, para mapas que nao suportam pre-alocaçao
+	fn shrink_to_fit(&mut self) {}
+
+	/// Reconstroi a estrutura interna do mapa com o tamanho ideal para o numero atual de elementos
+	/// Por padrao nao faz nada, para mapas que se rebalanceiam sozinhos
+	fn consolidate(&mut self) {}
+
+	/// Estimativa dos bytes alocados no heap pelo mapa
+	/// Por padrao conta apenas os pares: `n * (size_of::<K>() + size_of::<U>())`
+	fn memory_estimate(&self) -> usize {
+		self.len() * (size_of::<K>() + size_of::<U>())
+	}
+
+	/// Mantem apenas os pares para os quais `f` retorna verdadeiro, removendo os outros
+	/// Por padrao coleta as chaves rejeitadas percorrendo `iter` e as remove com `remove`
+	fn retain(&mut self, mut f: impl FnMut(&K, &U) -> bool) {
+		let rejected: Vec<K> = self.iter()
+			.filter(|(key, value)| !f(key, value))
+			.map(|(key, _)| key)
+			.collect();
+		for key in rejected.iter() {
+			self.remove(key);
+		}
+	}
+
+	/// Retorna um iterador sobre os pares com chave no intervalo `[from, to)`
+	/// Por padrao filtra `iter`, percorrendo todo o mapa
+	fn iter_range<'a>(&'a self, from: K, to: K) -> Box<dyn Iterator<Item=(K, Cow<'a, U>)> + 'a> where K: Ord + 'a {
+		Box::new(self.iter().filter(move |(key, _)| *key >= from && *key < to))
+	}
+
+	/// Separa os pares do mapa em dois mapas disjuntos: os que satisfazem `f` e os que nao satisfazem
+	/// Por padrao percorre `iter` uma unica vez, guardando os pares em dois vetores, e constroi os mapas com `from_iter`
+	fn partition(&self, f: impl Fn(K, &U) -> bool) -> (Self, Self) {
+		let (accepted, rejected): (Vec<_>, Vec<_>) = self.iter()
+			.map(|(key, value)| (key, value.into_owned()))
+			.partition(|(key, value)| f(*key, value));
+		(Self::from_iter(accepted), Self::from_iter(rejected))
+	}
 }
 
 /// Extensao do Map para valores que sao vetores, permitindo adicionar elementos ao vetor associado a chave
@@ -44,91 +155,121 @@ pub trait MapVec <K : Copy, U : Clone> : Map<K, Vec<U>> {
 /// Matriz baseada em mapas para armazenar os valores
 /// - `T`: tipo do mapa usado para armazenar os valores da matriz
 /// - `LM`: tipo do mapa usado para armazenar os valores por linha ou coluna (usado na multiplicacao)
+/// - `S`: tipo dos valores, `f64` por padrao. Apenas com `f64` a matriz implementa `Matrix`, que é o caminho para todas
+///   as operaçoes; para outros tipos (como `f32` ou `i64`) a matriz so guarda valores, criados com `zeros`, `from_rows`
+///   ou `from_cols` e acessados com `value_at` e `set_value`
+///
 /// O tempo de cada uma das operações depende da implementaçao do mapa usado
 /// Será represenado como T::operacao a complexidade de tempo da operaçao do mapa T
 /// Será representando como T::full_iter a complexidade de tempo para iterar sobre todos os elementos do mapa T
-
-pub struct MapMatrix <T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> {
+pub struct MapMatrix <T:  Map<Pair, S>, LM : MapVec<usize, (Pair, S)>, S: Scalar = f64> {
 	/// Dimensoes da matriz, representadas como um par (linhas, colunas)
     size: Pair,
 	/// Mapa que armazena os valores da matriz, podendo ser transposto
     values: TransposableMap<T>,
-	/// PhantomData para o tipo LM, usado na multiplicacao, serve para indicar que a struct depende do tipo LM sem armazenar um valor dele
-	phatom: std::marker::PhantomData<LM>
+	/// Se verdadeiro, `add` e `mul` removem do resultado os valores com modulo ate `EPSILON`
+	auto_compact: bool,
+	/// PhantomData para o tipo LM, usado na multiplicacao, e para o tipo S dos valores, serve para indicar que a struct depende desses tipos sem armazenar um valor deles
+	phatom: std::marker::PhantomData<(LM, S)>
 }
 
-impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> Matrix for MapMatrix<T, LM> {
+impl<T: Map<Pair, S>, LM: MapVec<usize, (Pair, S)>, S: Scalar> MapMatrix<T, LM, S> {
 	/// Cria uma nova matriz com as dimensoes especificadas, inicialmente vazia
 	/// Complexidade de tempo: O(1)
-	/// Complexidade de espaco: O(1)
-	fn new(size: Pair) -> MapMatrix<T, LM>{
+	pub fn zeros(size: Pair) -> Self {
 		MapMatrix {
 			size,
 			values: TransposableMap::new(T::from_iter(std::iter::empty())),
+			auto_compact: false,
 			phatom: std::marker::PhantomData
 		}
 	}
-	/// Retorna uma nova matriz que é o produto da matriz atual com um escalar
-	/// Complexidade de tempo: O(n * T::set_or_insert(n)), onde n é o numero de elementos na matriz
-	fn muls(a : &Self, scalar: f64) -> Self {
-		let mut c = a.values.clone();
-		for (_, mut value) in c.iter_mut(){
-			*value = *value * scalar;
+
+	/// Constroi a matriz a partir de linhas esparsas, cada uma com os pares `(coluna, valor)` definidos
+	/// Os valores sao inseridos com `set_or_insert` conforme o iterador produz as linhas, sem montar uma `MatrixInfo`
+	/// intermediaria (no `TreeStore` um `batch_set` por linha pode reconstruir o mapa inteiro a cada lote);
+	/// o numero de linhas da matriz é o numero de itens de `rows`. Valores iguais a `S::default()` sao ignorados
+	/// e, se uma coluna aparecer mais de uma vez na mesma linha, fica o ultimo valor.
+	/// Entra em panico se alguma coluna for maior ou igual a `ncols`.
+	/// Complexidade de tempo: O(k * T::set_or_insert(k)), onde k é o numero de valores
+	pub fn from_rows<I>(rows: I, ncols: usize) -> Self where I: IntoIterator<Item = Vec<(usize, S)>> {
+		let mut m = Self::zeros((0, ncols));
+		for (i, row) in rows.into_iter().enumerate() {
+			for (j, value) in row {
+				assert!(j < ncols, "column out of bounds");
+				if value != S::default() {
+					m.values.set_or_insert((i, j), value);
+				}
+			}
+			m.size.0 = i + 1;
+		}
+		m
+	}
+
+	/// Constroi a matriz a partir de colunas esparsas, cada uma com os pares `(linha, valor)` definidos,
+	/// como `from_rows` com os papeis de linhas e colunas trocados
+	/// Entra em panico se alguma linha for maior ou igual a `nrows`.
+	/// Complexidade de tempo: O(k * T::set_or_insert(k)), onde k é o numero de valores
+	pub fn from_cols<I>(cols: I, nrows: usize) -> Self where I: IntoIterator<Item = Vec<(usize, S)>> {
+		let mut m = Self::zeros((nrows, 0));
+		for (j, col) in cols.into_iter().enumerate() {
+			for (i, value) in col {
+				assert!(i < nrows, "row out of bounds");
+				if value != S::default() {
+					m.values.set_or_insert((i, j), value);
+				}
+			}
+			m.size.1 = j + 1;
+		}
+		m
+	}
+
+	/// Retorna o valor na posiçao especificada, ou `S::default()` (o zero do tipo) se nao houver valor definido
+	/// Complexidade de tempo: O(T::get(n))
+	pub fn value_at(&self, pos: Pair) -> S {
+		self.values.get(&pos).copied().unwrap_or_default()
+	}
+
+	/// Define o valor na posiçao especificada, removendo a posiçao do mapa se o valor for `S::default()`
+	/// Complexidade de tempo: O(T::set_or_insert(n) + T::remove(n))
+	pub fn set_value(&mut self, pos: Pair, value: S) {
+		if value == S::default() {
+			self.values.remove(&pos);
+		} else {
+			self.values.set_or_insert(pos, value);
 		}
-		return MapMatrix {
-			size: a.size,
-			values: c,
-			phatom: std::marker::PhantomData
-		};
 	}
-	/// Define o valor na posiçao especificada
-	/// Complexidade de tempo: O(T::set_or_insert(n)  + T::remove(n)), onde n é o numero de elementos no mapa
-	fn set(&mut self, pos: Pair, value: f64) {
-        if value == 0.0 {
-            self.values.remove(&pos);
-        } else {
-            self.values.set_or_insert(pos, value);
-        }
-    }
-	/// Retorna o valor na posiçao especificada, retornando 0.0 se nao houver valor definido
-	/// Complexidade de tempo: O(T::get(n)), onde n é o numero de elementos no mapa
-    fn get(&self, pos: Pair) -> f64 {
-        *self.values.get(&pos).unwrap_or(&0.0)
-    }
-	/// Retorna uma nova matriz que é a transposta da matriz atual
-	/// Complexidade de tempo: O(1)
-    fn transposed(mut self) -> MapMatrix<T, LM> {
-		self.size = (self.size.1, self.size.0);
-        self.values.transpose();
-		self
-    }
 
-	/// Retorna uma nova matriz que é a soma da matriz atual com outra matriz
+	/// Retorna a soma `a + b`, partindo de uma copia de `a` e somando os valores de `b`
 	/// Complexidade de tempo: O( (ka + kb) * (T::set_or_insert(kc) + T::get(kc))),
 	/// Onde ka é o numero de elementos na matriz a, kb é o numero de elementos na matriz b, e kc é o numero de elementos na matriz resultante
-    fn add(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
-        let mut c = MapMatrix { 
+	fn add_values(a: &Self, b: &Self) -> Self {
+		let mut c = MapMatrix {
 			size: a.size,
 			values: a.values.clone(),
+			auto_compact: a.auto_compact,
 			phatom: std::marker::PhantomData
 		};
-		for (pos, vb) in b.values.iter()  {
-			let value =  a.get(pos)+ *vb;
-			c.set(pos, value);
-		}	
-        return c
-    }
-	/// Retorna uma nova matriz que é o produto da matriz atual com outra matriz
-	/// 
+		for (pos, vb) in b.values.iter() {
+			let value = a.value_at(pos) + *vb;
+			c.set_value(pos, value);
+		}
+		c
+	}
+
+	/// Retorna o produto `a * b`
+	///
 	/// Estrutura:
 	/// - Separação: Primeiro a função cria dois mapas auxiliares, um para armazenar os valores de cada coluna da matriz a.
 	/// - Mutiplicação: Então a função itera sobre as colunas da matriz a e linhas da matriz b, multiplicando os valores correspondentes e somando-os na matriz resultante.
+	///
 	/// Complexidade de tempo: O(ka * kb / n * (T::get(kc) + T::set_or_insert(kc))),
-    fn mul(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
-        let mut c = MapMatrix::new((a.size.0, b.size.1));
-		let mut acolumns = LM::from_iter(std::iter::empty()); 
+	fn mul_values(a: &Self, b: &Self) -> Self {
+		let mut c = Self::zeros((a.size.0, b.size.1));
+		c.auto_compact = a.auto_compact;
+		let mut acolumns = LM::from_iter(std::iter::empty());
 		let mut brows = LM::from_iter(std::iter::empty());
-		for (apos, va) in a.values.iter()  {
+		for (apos, va) in a.values.iter() {
 			acolumns.add_to_vec(apos.1, (apos, *va));
 		}
 		for (bpos, vb) in b.values.iter() {
@@ -138,37 +279,1488 @@ impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> Matrix for MapMatrix<T
 			let Some(bvalues) = brows.get(&i) else {
 				continue;
 			};
-			for (apos, va) in avalues.iter()  {
-				for (bpos, vb) in  bvalues.iter() {
-					assert_eq!(a.size.1, b.size.0, "Incompatible matrices for multiplication");
+			for (apos, va) in avalues.iter() {
+				for (bpos, vb) in bvalues.iter() {
 					let pos = (apos.0, bpos.1);
-					let value =  c.get(pos)+ vb*va;
-					c.set(pos, value);
+					let value = c.value_at(pos) + *vb * *va;
+					c.set_value(pos, value);
 				}
 			}
 		}
-        return c;
-    }
+		c
+	}
 
-	/// Converte a matriz para uma estrutura MatrixInfo, que armazena as dimensoes e os valores da matriz
-	/// Complexidade de tempo: O(T::full_iter(n)), onde n é o numero de elementos na matriz
-	fn to_info(&self) -> MatrixInfo {
-		let mut values = Vec::new();
-		for (pos, value) in self.values.iter() {
-			values.push(( pos, value.into_owned()));
+	/// Retorna a matriz com todos os valores multiplicados por `scalar`
+	/// Complexidade de tempo: O(T::clone(n) + T::full_iter(n))
+	fn scale_values(a: &Self, scalar: S) -> Self {
+		let mut c = MapMatrix {
+			size: a.size,
+			values: a.values.clone(),
+			auto_compact: a.auto_compact,
+			phatom: std::marker::PhantomData
+		};
+		c.scale_values_in_place(scalar);
+		c
+	}
+
+	/// Multiplica todos os valores da matriz por `scalar`, alterando os valores direto no mapa
+	/// Complexidade de tempo: O(T::full_iter(n))
+	fn scale_values_in_place(&mut self, scalar: S) {
+		for (_, value) in self.values.iter_mut() {
+			*value = *value * scalar;
+		}
+	}
+}
+
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> MapMatrix<T, LM> {
+	/// Reserva espaço no mapa de valores para pelo menos `additional` novos elementos
+	/// Util antes de uma sequencia de chamadas de `set`
+	pub fn reserve_capacity(&mut self, additional: usize) {
+		self.values.reserve(additional);
+	}
+
+	/// Libera a memoria reservada e nao usada pelo mapa de valores
+	/// Util depois de remover muitos elementos da matriz
+	pub fn shrink_to_fit(&mut self) {
+		self.values.shrink_to_fit();
+	}
+
+	/// Reconstroi o mapa de valores com o tamanho ideal para o numero atual de elementos
+	/// Util depois de muitas remoçoes e inserçoes, que deixam o `HashMap` com fator de carga ruim
+	/// Para o `BTreeMap`, que se rebalanceia sozinho, nao faz nada
+	pub fn consolidate(&mut self) {
+		self.values.consolidate();
+	}
+
+	/// Ativa ou desativa a remoçao automatica de valores quase nulos (`auto_sparsify`) ao fim de `add`, `add_assign`,
+	/// `sub`, `weighted_sum` e `TreeMatrix::mul_sorted`; o resultado dessas operaçoes herda a configuraçao da matriz `a`
+	/// `mul` (via `try_mul`) chama `compress` sempre, com a configuraçao ativada ou nao
+	pub fn with_auto_compact(mut self, enabled: bool) -> Self {
+		self.auto_compact = enabled;
+		self
+	}
+
+	/// Copia a matriz para uma `MapMatrix` com outro tipo de mapa
+	/// Equivalente a `from_info(&self.to_info())`, mas sem alocar a MatrixInfo intermediaria
+	/// Complexidade de tempo: O(T::full_iter(n) + U::from_iter(n))
+	pub fn clone_as<U: Map<Pair, f64>, ULM: MapVec<usize, (Pair, f64)>>(&self) -> MapMatrix<U, ULM> {
+		MapMatrix {
+			size: self.size,
+			values: TransposableMap::new(U::from_iter(self.values.iter().map(|(pos, value)| (pos, value.into_owned())))),
+			auto_compact: self.auto_compact,
+			phatom: std::marker::PhantomData
 		}
+	}
+
+	/// Converte a matriz para uma estrutura MatrixInfo com os valores ordenados por posiçao (linha, coluna)
+	/// Diferente de `to_info`, o resultado nao depende da ordem de iteraçao do mapa
+	/// Complexidade de tempo: O(T::sorted_keys(n) + n * T::get(n))
+	pub fn to_info_sorted(&self) -> MatrixInfo {
 		MatrixInfo {
 			size: self.size,
-			values
+			values: self.values.iter_sorted()
+				.map(|(pos, value)| (pos, value.into_owned()))
+				.collect()
 		}
 	}
-	/// Cria uma matriz a partir de uma estrutura MatrixInfo
-	/// Complexidade de tempo: O(n * T::set_or_insert(n)), onde n é o numero de elementos na MatrixInfo
-	fn from_info(info: &MatrixInfo) -> Self {
+
+	/// Verifica se a transposiçao da matriz ainda esta pendente no mapa, ou seja,
+	/// se as chaves guardadas estao trocadas (ver `Matrix::transposed`)
+	pub fn is_transposed(&self) -> bool {
+		self.values.is_transposed()
+	}
+
+	/// Retorna as colunas e os valores definidos na linha `row`, em ordem crescente de coluna
+	/// Percorre apenas o intervalo da linha em mapas ordenados como o `TreeStore`; nos outros filtra os valores guardados
+	/// Complexidade de tempo: O(log k + r) no `TreeStore` e O(k) nos outros mapas, mais O(r log r) para ordenar,
+	/// onde k é o numero de elementos e r o numero de elementos da linha
+	pub fn get_row(&self, row: usize) -> Vec<(usize, f64)> {
+		let mut values: Vec<(usize, f64)> = self.values.iter_row(row).map(|(pos, value)| (pos.1, *value)).collect();
+		values.sort_unstable_by_key(|(col, _)| *col);
+		values
+	}
+
+	/// Retorna as linhas e os valores definidos na coluna `col`, em ordem crescente de linha
+	/// A coluna so é um intervalo do mapa com a transposiçao pendente (ver `Matrix::transposed`); senao filtra os valores guardados
+	/// Complexidade de tempo: O(k + r log r), ou O(log k + r log r) no `TreeStore` transposto
+	pub fn get_col(&self, col: usize) -> Vec<(usize, f64)> {
+		let mut values: Vec<(usize, f64)> = self.values.iter_col(col).map(|(pos, value)| (pos.0, *value)).collect();
+		values.sort_unstable_by_key(|(row, _)| *row);
+		values
+	}
+
+	/// Converte a matriz para uma estrutura MatrixInfo, consumindo a matriz
+	/// Diferente de `to_info`, move os valores do mapa com `Map::into_entries`, sem clona-los
+	/// Complexidade de tempo: O(T::full_iter(n))
+	pub fn into_info(self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size,
+			values: self.values.into_entries(),
+		}
+	}
+
+	/// Materializa uma transposiçao pendente, reinserindo cada valor com a posiçao trocada
+	/// Depois disso os acessos ao mapa nao precisam mais trocar as chaves (ver `Matrix::transposed`)
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n)), ou O(1) se a matriz nao estiver transposta
+	pub fn realize(&mut self) {
+		self.values.realize();
+	}
+
+	/// Cria uma matriz do mesmo tamanho e configuraçao com os valores `values`
+	fn with_values(&self, values: TransposableMap<T>) -> Self {
 		MapMatrix {
-			size: info.size,
-			values: TransposableMap::new(T::from_iter(info.values.iter().map(|(pos, value)| (*pos, *value)))),
+			size: self.size,
+			values,
+			auto_compact: self.auto_compact,
+			phatom: std::marker::PhantomData
+		}
+	}
+
+	/// Separa a matriz em duas: a parte triangular superior (com a diagonal) e a parte estritamente triangular inferior
+	/// Faz uma unica passagem pelos valores, com `Map::partition`
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	pub fn split_triangular(&self) -> (Self, Self) {
+		let (upper, lower) = self.values.partition(|(i, j), _| i <= j);
+		(self.with_values(upper), self.with_values(lower))
+	}
+
+	/// Retorna a parte triangular superior da matriz, incluindo a diagonal
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	pub fn upper_triangular(&self) -> Self {
+		self.with_values(self.values.partition(|(i, j), _| i <= j).0)
+	}
+
+	/// Retorna a parte triangular inferior da matriz, incluindo a diagonal
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	pub fn lower_triangular(&self) -> Self {
+		self.with_values(self.values.partition(|(i, j), _| i >= j).0)
+	}
+
+	/// Separa a matriz em duas: uma só com os valores da linha `row` e outra com o resto
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	pub fn partition_by_row(&self, row: usize) -> (Self, Self) {
+		let (in_row, rest) = self.values.partition(|(i, _), _| i == row);
+		(self.with_values(in_row), self.with_values(rest))
+	}
+
+	/// Estimativa dos bytes usados pela matriz: o tamanho da propria struct mais a estimativa do mapa (`Map::memory_estimate`)
+	/// As formulas de cada mapa estao documentadas em `HashMapStore` e `TreeStore`
+	pub fn memory_estimate(&self) -> usize {
+		size_of::<Self>() + self.values.memory_estimate()
+	}
+
+	/// Retorna a menor largura de banda `(kl, ku)` que contem todos os valores nao nulos
+	/// `kl` é o numero de subdiagonais e `ku` o numero de superdiagonais
+	/// Complexidade de tempo: O(T::full_iter(n))
+	pub fn detect_bandwidth(&self) -> (usize, usize) {
+		self.values.iter().fold((0, 0), |(kl, ku), ((i, j), _)| {
+			(kl.max(i.saturating_sub(j)), ku.max(j.saturating_sub(i)))
+		})
+	}
+
+	/// Converte a matriz para o armazenamento compacto em banda do LAPACK (usado pelo `DGBSV`)
+	/// O resultado é um vetor `(kl + ku + 1) x n` em ordem de coluna onde `band[ku + i - j][j] = A[i][j]`
+	/// Retorna `MatrixError::DimensionMismatch` com a banda detectada em `got` se algum valor estiver fora da banda
+	/// Complexidade de tempo: O(T::full_iter(n) + (kl + ku + 1) * n)
+	pub fn to_band_storage(&self, kl: usize, ku: usize) -> Result<Vec<f64>, MatrixError> {
+		let bandwidth = self.detect_bandwidth();
+		if bandwidth.0 > kl || bandwidth.1 > ku {
+			return Err(MatrixError::DimensionMismatch { expected: (kl, ku), got: bandwidth });
+		}
+		let ldab = kl + ku + 1;
+		let mut band = vec![0.0; ldab * self.size.1];
+		for ((i, j), value) in self.values.iter() {
+			band[j * ldab + ku + i - j] = *value;
+		}
+		Ok(band)
+	}
+
+	/// Constroi uma matriz `n x n` a partir do armazenamento compacto em banda do LAPACK
+	/// Inverso de `to_band_storage`; as posiçoes da banda que caem fora da matriz sao ignoradas
+	/// Complexidade de tempo: O((kl + ku + 1) * n * T::set_or_insert(n))
+	pub fn from_band_storage(band: &[f64], n: usize, kl: usize, ku: usize) -> Self {
+		let ldab = kl + ku + 1;
+		assert_eq!(band.len(), ldab * n, "band deve ter (kl + ku + 1) * n elementos");
+		let mut m = Self::new((n, n));
+		for j in 0..n {
+			for (i, value) in (j.saturating_sub(ku)..n.min(j + kl + 1)).map(|i| (i, band[j * ldab + ku + i - j])) {
+				if value != 0.0 {
+					m.set((i, j), value);
+				}
+			}
+		}
+		m
+	}
+}
+
+impl MapMatrix<TreeStore<Pair, f64>, TreeStore<usize, Vec<(Pair, f64)>>> {
+	/// Multiplicaçao que aproveita a ordem do `BTreeMap`: percorre `a` em ordem de linha e, para cada `a[i][k]`,
+	/// apenas a linha `k` de `b` com `Map::iter_range`, acumulando a linha `i` do resultado antes de inseri-la
+	/// Se `b` estiver transposta, ela é copiada para um mapa nao transposto antes, para que as linhas sejam intervalos do mapa.
+	/// Como nao ha especializaçao de traits, `Matrix::mul` continua usando o algoritmo generico; esta funçao deve ser chamada diretamente.
+	/// Complexidade de tempo: O(ka * (log kb + kb / n) + kc * log kc)
+	pub fn mul_sorted(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.1, b.size.0, "Incompatible matrices for multiplication");
+		let realized;
+		let b = if b.values.is_transposed() {
+			realized = b.clone_as();
+			&realized
+		} else {
+			b
+		};
+		let mut c = MapMatrix::new((a.size.0, b.size.1));
+		c.auto_compact = a.auto_compact;
+		let mut row = std::collections::BTreeMap::new();
+		let mut current = None;
+		for ((i, k), va) in a.values.iter_sorted() {
+			if current != Some(i) {
+				for (j, value) in std::mem::take(&mut row) {
+					if value != 0.0 {
+						c.values.set_or_insert((current.unwrap(), j), value);
+					}
+				}
+				current = Some(i);
+			}
+			for ((_, j), vb) in b.values.iter_range((k, 0), (k + 1, 0)) {
+				*row.entry(j).or_insert(0.0) += *va * *vb;
+			}
+		}
+		for (j, value) in row {
+			if value != 0.0 {
+				c.values.set_or_insert((current.unwrap(), j), value);
+			}
+		}
+		if c.auto_compact {
+			c.auto_sparsify();
+		}
+		c
+	}
+}
+
+/// Copia o tamanho, o mapa de valores (com a transposiçao pendente) e a configuraçao de `auto_compact`
+/// Implementado manualmente porque o `derive` exigiria `LM: Clone`, mas nenhum `LM` é guardado na matriz
+impl<T: Map<Pair, S>, LM: MapVec<usize, (Pair, S)>, S: Scalar> Clone for MapMatrix<T, LM, S> {
+	fn clone(&self) -> Self {
+		MapMatrix {
+			size: self.size,
+			values: self.values.clone(),
+			auto_compact: self.auto_compact,
 			phatom: std::marker::PhantomData
 		}
 	}
-}
\ No newline at end of file
+}
+
+#[test]
+fn map_matrix_is_clone() {
+	let mut original = crate::HashMapMatrix::new((2, 2));
+	original.set((0, 1), 3.0);
+	let mut copy = original.clone();
+	copy.set((0, 1), 5.0);
+	copy.set((1, 0), 7.0);
+	assert_eq!(original.get((0, 1)), 3.0);
+	assert_eq!(original.get((1, 0)), 0.0);
+	assert_eq!(original.nnz(), 1);
+	assert_eq!(copy.get((0, 1)), 5.0);
+}
+
+/// Tamanho e lista ordenada dos valores definidos, como `(linha, coluna): valor`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> fmt::Debug for MapMatrix<T, LM> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MapMatrix")
+			.field("size", &self.size)
+			.field("values", &fmt::from_fn(|f| f.debug_map().entries(self.values.iter_sorted()).finish()))
+			.finish()
+	}
+}
+
+/// Iguais se tem o mesmo tamanho e cada valor definido em uma das matrizes difere menos que `EPSILON`
+/// do valor na mesma posiçao da outra
+/// Complexidade de tempo: O(ka * T::get(kb) + kb * T::get(ka))
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> PartialEq for MapMatrix<T, LM> {
+	fn eq(&self, other: &Self) -> bool {
+		let contained = |a: &Self, b: &Self| a.values.iter().all(|(pos, value)| (*value - b.get(pos)).abs() < EPSILON);
+		self.size == other.size && contained(self, other) && contained(other, self)
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> AddAssign<&MapMatrix<T, LM>> for MapMatrix<T, LM> {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> MulAssign<f64> for MapMatrix<T, LM> {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+/// `&a + &b`, equivalente a `Matrix::add(&a, &b)`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Add<&MapMatrix<T, LM>> for &MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn add(self, other: &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		Matrix::add(self, other)
+	}
+}
+
+/// `a + &b`, reaproveitando `a` com `Matrix::add_assign`; permite encadear, como em `&a * &b + &c`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Add<&MapMatrix<T, LM>> for MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn add(mut self, other: &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		Matrix::add_assign(&mut self, other);
+		self
+	}
+}
+
+/// `&a - &b`, equivalente a `Matrix::sub(&a, &b)`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Sub<&MapMatrix<T, LM>> for &MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn sub(self, other: &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		Matrix::sub(self, other)
+	}
+}
+
+/// `&a * &b` é o produto de matrizes, equivalente a `Matrix::mul(&a, &b)`, e nao o produto elemento a elemento (ver `Matrix::hadamard`)
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Mul<&MapMatrix<T, LM>> for &MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn mul(self, other: &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		Matrix::mul(self, other)
+	}
+}
+
+/// `&a * s`, equivalente a `Matrix::muls(&a, s)`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Mul<f64> for &MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn mul(self, scalar: f64) -> MapMatrix<T, LM> {
+		Matrix::muls(self, scalar)
+	}
+}
+
+/// `-a`, multiplicando `a` por -1 no lugar com `Matrix::muls_assign`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Neg for MapMatrix<T, LM> {
+	type Output = MapMatrix<T, LM>;
+	fn neg(mut self) -> MapMatrix<T, LM> {
+		Matrix::muls_assign(&mut self, -1.0);
+		self
+	}
+}
+
+/// Leitura com `m[(i, j)]`, equivalente a `m.get((i, j))`
+/// Nao ha `IndexMut`: atribuir em uma posiçao vazia exige inserir no mapa, entao a escrita continua sendo com `set`
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> std::ops::Index<Pair> for MapMatrix<T, LM> {
+	type Output = f64;
+
+	fn index(&self, pos: Pair) -> &f64 {
+		self.values.get(&pos).unwrap_or(&0.0)
+	}
+}
+
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> fmt::Display for MapMatrix<T, LM> {
+	/// Escreve as dimensoes da matriz seguidas dos valores definidos, em ordem de posiçao
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{}x{}", self.size.0, self.size.1)?;
+		for (pos, value) in self.values.iter_sorted() {
+			writeln!(f, "{:?} = {}", pos, value)?;
+		}
+		Ok(())
+	}
+}
+
+impl<T:  Map<Pair, f64>, LM : MapVec<usize, (Pair, f64)>> Matrix for MapMatrix<T, LM> {
+	/// Cria uma nova matriz com as dimensoes especificadas, inicialmente vazia
+	/// Complexidade de tempo: O(1)
+	/// Complexidade de espaco: O(1)
+	fn new(size: Pair) -> MapMatrix<T, LM>{
+		MapMatrix::zeros(size)
+	}
+	/// Retorna as dimensoes da matriz
+	/// Complexidade de tempo: O(1)
+	fn size(&self) -> Pair {
+		self.size
+	}
+	/// Retorna o numero de valores guardados no mapa
+	/// Complexidade de tempo: O(1), todos os mapas guardam o numero de pares
+	fn nnz(&self) -> usize {
+		self.values.len()
+	}
+	/// Soma apenas os valores definidos na diagonal, sem consultar as posiçoes vazias
+	/// Complexidade de tempo: O(n), onde n é o numero de elementos na matriz
+	fn trace(&self) -> f64 {
+		self.values.iter().filter(|(pos, _)| pos.0 == pos.1).map(|(_, value)| *value).sum()
+	}
+	/// Retorna uma nova matriz que é o produto da matriz atual com um escalar
+	/// Complexidade de tempo: O(n * T::set_or_insert(n)), onde n é o numero de elementos na matriz
+	fn muls(a : &Self, scalar: f64) -> Self {
+		MapMatrix::scale_values(a, scalar)
+	}
+	/// Define o valor na posiçao especificada
+	/// Complexidade de tempo: O(T::set_or_insert(n)  + T::remove(n)), onde n é o numero de elementos no mapa
+	fn set(&mut self, pos: Pair, value: f64) {
+		self.set_value(pos, value);
+    }
+	/// Retorna o valor na posiçao especificada, retornando 0.0 se nao houver valor definido
+	/// Complexidade de tempo: O(T::get(n)), onde n é o numero de elementos no mapa
+    fn get(&self, pos: Pair) -> f64 {
+        self.value_at(pos)
+    }
+	/// Retorna uma nova matriz que é a transposta da matriz atual
+	/// Apenas marca o mapa como transposto, e cada acesso seguinte troca a posiçao antes de consultar o mapa.
+	/// Se a matriz transposta for lida muitas vezes, vale chamar `realize` uma vez para reconstruir o mapa ja trocado.
+	/// Complexidade de tempo: O(1)
+    fn transposed(mut self) -> MapMatrix<T, LM> {
+		self.size = (self.size.1, self.size.0);
+        self.values.transpose();
+		self
+    }
+
+	/// Retorna uma nova matriz que é a soma da matriz atual com outra matriz (ver `add_values`)
+	/// Complexidade de tempo: O( (ka + kb) * (T::set_or_insert(kc) + T::get(kc))),
+	/// Onde ka é o numero de elementos na matriz a, kb é o numero de elementos na matriz b, e kc é o numero de elementos na matriz resultante
+    fn try_add(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> Result<MapMatrix<T, LM>, MatrixError> {
+		check_add_sizes(a.size, b.size)?;
+        let mut c = MapMatrix::add_values(a, b);
+		if c.auto_compact {
+			c.auto_sparsify();
+		}
+        Ok(c)
+    }
+	/// Soma os valores de `b` direto no mapa de `a`
+	/// Complexidade de tempo: O(kb * (T::get(ka) + T::set_or_insert(ka)))
+	fn add_assign(a : &mut MapMatrix<T, LM>, b : &MapMatrix<T, LM>) {
+		assert_eq!(a.size, b.size, "Incompatible matrices for addition");
+		for (pos, vb) in b.values.iter() {
+			let value = a.get(pos) + *vb;
+			a.set(pos, value);
+		}
+		if a.auto_compact {
+			a.auto_sparsify();
+		}
+	}
+	/// Complexidade de tempo: O(T::full_iter(n)), sem copiar o mapa
+	fn muls_assign(a : &mut MapMatrix<T, LM>, scalar: f64) {
+		a.scale_values_in_place(scalar);
+	}
+	/// Retorna `a - b`, partindo de uma copia de `a` e subtraindo os valores de `b`
+	/// Os valores que se anulam sao removidos por `set`
+	/// Complexidade de tempo: O(T::clone(ka) + kb * (T::set_or_insert(kc) + T::get(kc)))
+	fn sub(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		let mut c = a.with_values(a.values.clone());
+		for (pos, vb) in b.values.iter() {
+			let value = a.get(pos) - *vb;
+			c.set(pos, value);
+		}
+		if c.auto_compact {
+			c.auto_sparsify();
+		}
+		c
+	}
+
+	/// Retorna `-a`
+	/// Complexidade de tempo: O(T::clone(n) + T::full_iter(n))
+	fn neg(a : &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		let mut c = a.with_values(a.values.clone());
+		for (_, value) in c.values.iter_mut() {
+			*value = -*value;
+		}
+		c
+	}
+
+	/// Monta o mapa do resultado diretamente a partir dos valores transformados, sem os que viram 0
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	fn map_nnz(a : &Self, f: impl Fn(Pair, f64) -> f64) -> Self {
+		let values = a.values.iter()
+			.map(|(pos, value)| (pos, f(pos, *value)))
+			.filter(|(_, value)| *value != 0.0);
+		a.with_values(TransposableMap::new(T::from_iter(values)))
+	}
+
+	/// Percorre os valores da matriz com menos elementos e consulta a outra com `get`,
+	/// de modo que o resultado só tem valores na interseçao dos padroes de esparsidade
+	/// Complexidade de tempo: O(min(ka, kb) * (T::get(k) + T::set_or_insert(kc)))
+	fn hadamard(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> MapMatrix<T, LM> {
+		assert_eq!(a.size, b.size, "Incompatible matrices for hadamard product");
+		let (small, large) = if a.nnz() <= b.nnz() { (a, b) } else { (b, a) };
+		let mut c = a.with_values(TransposableMap::new(T::from_iter(std::iter::empty())));
+		for (pos, value) in small.values.iter() {
+			let other = large.get(pos);
+			if other != 0.0 {
+				c.set(pos, *value * other);
+			}
+		}
+		c
+	}
+
+	/// Retorna uma nova matriz que é o produto da matriz atual com outra matriz (ver `mul_values`), sem o ruido numerico (ver `compress`)
+	/// Complexidade de tempo: O(ka * kb / n * (T::get(kc) + T::set_or_insert(kc))),
+    fn try_mul(a : &MapMatrix<T, LM>, b : &MapMatrix<T, LM>) -> Result<MapMatrix<T, LM>, MatrixError> {
+		check_mul_sizes(a.size, b.size)?;
+        let mut c = MapMatrix::mul_values(a, b);
+		c.compress();
+        Ok(c)
+    }
+
+	/// Retorna a matriz com o tamanho aumentado para `target_size x target_size`
+	/// Como os zeros nao sao armazenados, basta copiar os valores e atualizar as dimensoes
+	/// Complexidade de tempo: O(T::clone(n))
+	fn pad_symmetric(&self, target_size: usize) -> Result<Self, MatrixError> {
+		if target_size < self.size.0 || target_size < self.size.1 {
+			let needed = self.size.0.max(self.size.1);
+			return Err(MatrixError::DimensionMismatch { expected: (needed, needed), got: (target_size, target_size) });
+		}
+		Ok(MapMatrix {
+			size: (target_size, target_size),
+			values: self.values.clone(),
+			auto_compact: self.auto_compact,
+			phatom: std::marker::PhantomData
+		})
+	}
+
+	/// Filtra os valores do mapa que estao na regiao, deslocando as posiçoes para começar em 0
+	/// Complexidade de tempo: O(T::full_iter(n) + ks * T::set_or_insert(ks)), onde ks é o numero de elementos da submatriz
+	fn submatrix(&self, row_range: Range<usize>, col_range: Range<usize>) -> Self {
+		assert!(row_range.end <= self.size.0 && col_range.end <= self.size.1, "submatrix out of bounds");
+		let values = self.values.iter()
+			.filter(|((i, j), _)| row_range.contains(i) && col_range.contains(j))
+			.map(|((i, j), value)| ((i - row_range.start, j - col_range.start), *value));
+		let mut c = self.with_values(TransposableMap::new(T::from_iter(values)));
+		c.size = (row_range.len(), col_range.len());
+		c
+	}
+
+	/// Remove os valores da regiao e insere os valores de `sub` deslocados
+	/// Complexidade de tempo: O(T::full_iter(n) + r * T::remove(n) + ks * T::set_or_insert(n)),
+	/// onde r é o numero de valores removidos e ks o numero de elementos de sub
+	fn set_submatrix(&mut self, row_offset: usize, col_offset: usize, sub: &Self) {
+		assert!(row_offset + sub.size.0 <= self.size.0 && col_offset + sub.size.1 <= self.size.1, "submatrix out of bounds");
+		let (rows, cols) = (row_offset..row_offset + sub.size.0, col_offset..col_offset + sub.size.1);
+		let inside: Vec<Pair> = self.values.iter()
+			.map(|(pos, _)| pos)
+			.filter(|(i, j)| rows.contains(i) && cols.contains(j))
+			.collect();
+		for pos in inside.iter() {
+			self.values.remove(pos);
+		}
+		for ((i, j), value) in sub.values.iter() {
+			self.values.set_or_insert((i + row_offset, j + col_offset), *value);
+		}
+	}
+
+	/// Parte de uma copia do mapa de `a` e insere os valores de `b` com as colunas deslocadas em `a.size.1`
+	/// Complexidade de tempo: O(T::clone(ka) + kb * T::set_or_insert(ka + kb))
+	fn hstack(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.0, b.size.0, "Incompatible matrices for hstack");
+		let mut c = a.with_values(a.values.clone());
+		c.size = (a.size.0, a.size.1 + b.size.1);
+		for ((i, j), value) in b.values.iter() {
+			c.values.set_or_insert((i, j + a.size.1), *value);
+		}
+		c
+	}
+
+	/// Parte de uma copia do mapa de `a` e insere os valores de `b` com as linhas deslocadas em `a.size.0`
+	/// Complexidade de tempo: O(T::clone(ka) + kb * T::set_or_insert(ka + kb))
+	fn vstack(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.1, b.size.1, "Incompatible matrices for vstack");
+		let mut c = a.with_values(a.values.clone());
+		c.size = (a.size.0 + b.size.0, a.size.1);
+		for ((i, j), value) in b.values.iter() {
+			c.values.set_or_insert((i + a.size.0, j), *value);
+		}
+		c
+	}
+
+	/// Com `value` 0 esvazia o mapa com `Map::drain`, que mantem a capacidade reservada dos mapas que tem uma.
+	/// Com qualquer outro valor guarda todas as `linhas * colunas` posiçoes: a matriz fica densa,
+	/// usando mais memoria que uma `TableMatrix` do mesmo tamanho.
+	/// Complexidade de tempo: O(T::drain(n)) com 0, e O(T::drain(n) + linhas * colunas * T::set_or_insert) nos outros casos
+	fn fill(&mut self, value: f64) {
+		drop(self.values.drain());
+		if value != 0.0 {
+			let (rows, cols) = self.size;
+			self.values.batch_set((0..rows).flat_map(|i| (0..cols).map(move |j| ((i, j), value))));
+		}
+	}
+
+	/// Remove os valores com modulo menor ou igual a `threshold`, retornando quantos foram removidos
+	/// Se mais de 10% dos valores forem removidos, o mapa é reconstruido com `consolidate`
+	/// Complexidade de tempo: O(T::retain(n))
+	fn threshold_sparsify(&mut self, threshold: f64) -> usize {
+		let total = self.values.len();
+		self.values.retain(|_, value| value.abs() > threshold);
+		let removed = total - self.values.len();
+		if removed * 10 > total {
+			self.consolidate();
+		}
+		removed
+	}
+
+	/// Remove os valores com modulo menor que `eps`, mantendo os iguais a `eps`
+	/// Se mais de 10% dos valores forem removidos, o mapa é reconstruido com `consolidate`
+	/// Complexidade de tempo: O(T::retain(n))
+	fn apply_threshold(&mut self, eps: f64) {
+		let total = self.values.len();
+		self.values.retain(|_, value| value.abs() >= eps);
+		if (total - self.values.len()) * 10 > total {
+			self.consolidate();
+		}
+	}
+
+	/// Retorna uma matriz com os valores desta matriz apenas nas posiçoes definidas em `mask`
+	/// O resultado tem como elementos a interseçao dos elementos das duas matrizes
+	/// Complexidade de tempo: O(km * (T::get(n) + T::set_or_insert(km))), onde km é o numero de elementos de mask
+	fn apply_mask(&self, mask: &Self) -> Result<Self, MatrixError> {
+		if self.size != mask.size {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: mask.size });
+		}
+		let mut c = MapMatrix::new(self.size);
+		c.auto_compact = self.auto_compact;
+		for (pos, _) in mask.values.iter() {
+			c.set(pos, self.get(pos));
+		}
+		Ok(c)
+	}
+
+	/// Remove do mapa os valores fora de `new_size` e atualiza o tamanho, sem reconstruir o mapa
+	/// Complexidade de tempo: O(T::full_iter(n) + r * T::remove(n)), onde r é o numero de valores removidos
+	fn shrink_size(&mut self, new_size: Pair) -> Result<usize, MatrixError> {
+		if new_size.0 > self.size.0 || new_size.1 > self.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: new_size });
+		}
+		let outside: Vec<Pair> = self.values.iter()
+			.map(|(pos, _)| pos)
+			.filter(|pos| pos.0 >= new_size.0 || pos.1 >= new_size.1)
+			.collect();
+		for pos in outside.iter() {
+			self.values.remove(pos);
+		}
+		self.size = new_size;
+		Ok(outside.len())
+	}
+
+	/// Esvazia o mapa com `drain` e o reconstroi com as chaves remapeadas
+	/// Uma transposiçao pendente é materializada no processo
+	/// Complexidade de tempo: O(T::full_iter(n) + T::from_iter(n))
+	fn reshape(mut self, new_size: Pair) -> Result<Self, MatrixError> {
+		if new_size.0 * new_size.1 != self.size.0 * self.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: new_size });
+		}
+		let cols = self.size.1;
+		let values = T::from_iter(self.values.drain().map(|((i, j), value)| {
+			let flat = i * cols + j;
+			((flat / new_size.1, flat % new_size.1), value)
+		}));
+		self.values = TransposableMap::new(values);
+		self.size = new_size;
+		Ok(self)
+	}
+
+	/// Matriz de Gram guardando apenas os produtos internos com modulo maior que `EPSILON`
+	/// Complexidade de tempo: O(na * nb * d + kc * T::set_or_insert(kc)), onde kc é o numero de produtos guardados
+	fn inner_product_matrix(a_vecs: &[Vec<f64>], b_vecs: &[Vec<f64>]) -> Result<Self, MatrixError> {
+		let values = inner_products(a_vecs, b_vecs)?.filter(|(_, value)| value.abs() > EPSILON);
+		Ok(MapMatrix {
+			size: (a_vecs.len(), b_vecs.len()),
+			values: TransposableMap::new(T::from_iter(values)),
+			auto_compact: false,
+			phatom: std::marker::PhantomData
+		})
+	}
+
+	/// Retorna a combinaçao linear das matrizes, partindo de uma copia escalada da primeira
+	/// Complexidade de tempo: O(T::clone(k1) + (k2 + ... + kn) * (T::get(kc) + T::set_or_insert(kc))),
+	/// onde ki é o numero de elementos da i-esima matriz e kc o numero de elementos do resultado
+	fn weighted_sum(matrices: &[(&Self, f64)]) -> Result<Self, MatrixError> {
+		let Some(((first, weight), rest)) = matrices.split_first() else {
+			return Ok(MapMatrix::new((0, 0)));
+		};
+		if let Some((m, _)) = rest.iter().find(|(m, _)| m.size != first.size) {
+			return Err(MatrixError::DimensionMismatch { expected: first.size, got: m.size });
+		}
+		let mut c = if *weight == 0.0 {
+			MapMatrix::new(first.size).with_auto_compact(first.auto_compact)
+		} else {
+			MapMatrix::muls(first, *weight)
+		};
+		for (m, weight) in rest {
+			for (pos, value) in m.values.iter() {
+				let value = c.get(pos) + weight * *value;
+				c.set(pos, value);
+			}
+		}
+		if c.auto_compact {
+			c.auto_sparsify();
+		}
+		Ok(c)
+	}
+
+	/// Converte a matriz para uma estrutura MatrixInfo, que armazena as dimensoes e os valores da matriz
+	/// Complexidade de tempo: O(T::full_iter(n)), onde n é o numero de elementos na matriz
+	fn to_info(&self) -> MatrixInfo {
+		let mut values = Vec::new();
+		for (pos, value) in self.values.iter() {
+			values.push(( pos, value.into_owned()));
+		}
+		MatrixInfo {
+			size: self.size,
+			values
+		}
+	}
+	/// Itera diretamente sobre os valores armazenados no mapa, sem alocar
+	/// Complexidade de tempo: O(T::full_iter(n)) para percorrer todos os valores
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new(self.values.iter().map(|(pos, value)| (pos, value.into_owned())))
+	}
+
+	/// Remove do mapa os valores das duas linhas e os reinsere com as linhas trocadas
+	/// Complexidade de tempo: O(m * T::get(k) + r * (T::remove(k) + T::set_or_insert(k))), onde r é o numero de valores das duas linhas
+	fn row_swap(&mut self, i: usize, j: usize) {
+		assert!(i < self.size.0 && j < self.size.0, "row out of bounds");
+		if i == j {
+			return;
+		}
+		let (row_i, row_j) = (self.get_row(i), self.get_row(j));
+		for (col, _) in row_i.iter().chain(row_j.iter()) {
+			self.values.remove(&(i, *col));
+			self.values.remove(&(j, *col));
+		}
+		for (col, value) in row_i {
+			self.values.set_or_insert((j, col), value);
+		}
+		for (col, value) in row_j {
+			self.values.set_or_insert((i, col), value);
+		}
+	}
+
+	/// Remove do mapa os valores das duas colunas e os reinsere com as colunas trocadas
+	/// Complexidade de tempo: O(n * T::get(k) + r * (T::remove(k) + T::set_or_insert(k))), onde r é o numero de valores das duas colunas
+	fn col_swap(&mut self, i: usize, j: usize) {
+		assert!(i < self.size.1 && j < self.size.1, "column out of bounds");
+		if i == j {
+			return;
+		}
+		let (col_i, col_j) = (self.get_col(i), self.get_col(j));
+		for (row, _) in col_i.iter().chain(col_j.iter()) {
+			self.values.remove(&(*row, i));
+			self.values.remove(&(*row, j));
+		}
+		for (row, value) in col_i {
+			self.values.set_or_insert((row, j), value);
+		}
+		for (row, value) in col_j {
+			self.values.set_or_insert((row, i), value);
+		}
+	}
+
+	/// Agrupa os valores por linha em um `LM`, como `iter_cols`, e monta cada linha quando pedida
+	/// Complexidade de tempo: O(T::full_iter(k) + k * LM::add_to_vec(n)) para agrupar, mais O(m + LM::get(n)) por linha
+	fn iter_rows<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		let cols = self.size.1;
+		let mut rows = LM::from_iter(std::iter::empty());
+		for (pos, value) in self.values.iter() {
+			rows.add_to_vec(pos.0, (pos, *value));
+		}
+		Box::new((0..self.size.0).map(move |i| {
+			let mut row = vec![0.0; cols];
+			for ((_, j), value) in rows.get(&i).into_iter().flatten() {
+				row[*j] = *value;
+			}
+			row
+		}))
+	}
+
+	/// Agrupa os valores por coluna em um `LM`, como as linhas de `b` em `mul`, e monta cada coluna quando pedida
+	/// Complexidade de tempo: O(T::full_iter(k) + k * LM::add_to_vec(m)) para agrupar, mais O(n + LM::get(m)) por coluna
+	fn iter_cols<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		let rows = self.size.0;
+		let mut columns = LM::from_iter(std::iter::empty());
+		for (pos, value) in self.values.iter() {
+			columns.add_to_vec(pos.1, (pos, *value));
+		}
+		Box::new((0..self.size.1).map(move |j| {
+			let mut col = vec![0.0; rows];
+			for ((i, _), value) in columns.get(&j).into_iter().flatten() {
+				col[*i] = *value;
+			}
+			col
+		}))
+	}
+	/// Cria uma matriz a partir de uma estrutura MatrixInfo
+	/// Complexidade de tempo: O(n * T::set_or_insert(n)), onde n é o numero de elementos na MatrixInfo
+	fn from_info(info: &MatrixInfo) -> Self {
+		MapMatrix {
+			size: info.size,
+			values: TransposableMap::new(T::from_iter(info.values.iter().map(|(pos, value)| (*pos, *value)))),
+			auto_compact: false,
+			phatom: std::marker::PhantomData
+		}
+	}
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{HashMapMatrix, TableMatrix, TreeMatrix, conjugate_gradient, test_utils::*};
+	use rand::{Rng, SeedableRng, rngs::StdRng};
+
+	fn expected_row<M: Matrix>(a: &M, row: usize) -> Vec<(usize, f64)> {
+		(0..a.size().1).map(|col| (col, a.get((row, col)))).filter(|(_, value)| *value != 0.0).collect()
+	}
+
+	fn expected_col<M: Matrix>(a: &M, col: usize) -> Vec<(usize, f64)> {
+		(0..a.size().0).map(|row| (row, a.get((row, col)))).filter(|(_, value)| *value != 0.0).collect()
+	}
+
+	/// Expoe os metodos proprios das `MapMatrix` para as funçoes genericas de `matrix_tests!`
+	trait MapMatrixOps {
+		fn get_row(&self, row: usize) -> Vec<(usize, f64)>;
+		fn get_col(&self, col: usize) -> Vec<(usize, f64)>;
+		fn into_info(self) -> MatrixInfo;
+		fn with_auto_compact(self, enabled: bool) -> Self;
+		fn split_triangular(&self) -> (Self, Self) where Self: Sized;
+		fn upper_triangular(&self) -> Self;
+		fn lower_triangular(&self) -> Self;
+		fn partition_by_row(&self, row: usize) -> (Self, Self) where Self: Sized;
+		fn realize(&mut self);
+		fn is_transposed(&self) -> bool;
+	}
+
+	impl<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> MapMatrixOps for MapMatrix<T, LM> {
+		fn get_row(&self, row: usize) -> Vec<(usize, f64)> {
+			MapMatrix::get_row(self, row)
+		}
+
+		fn get_col(&self, col: usize) -> Vec<(usize, f64)> {
+			MapMatrix::get_col(self, col)
+		}
+
+		fn into_info(self) -> MatrixInfo {
+			MapMatrix::into_info(self)
+		}
+
+		fn with_auto_compact(self, enabled: bool) -> Self {
+			MapMatrix::with_auto_compact(self, enabled)
+		}
+
+		fn split_triangular(&self) -> (Self, Self) {
+			MapMatrix::split_triangular(self)
+		}
+
+		fn upper_triangular(&self) -> Self {
+			MapMatrix::upper_triangular(self)
+		}
+
+		fn lower_triangular(&self) -> Self {
+			MapMatrix::lower_triangular(self)
+		}
+
+		fn partition_by_row(&self, row: usize) -> (Self, Self) {
+			MapMatrix::partition_by_row(self, row)
+		}
+
+		fn realize(&mut self) {
+			MapMatrix::realize(self)
+		}
+
+		fn is_transposed(&self) -> bool {
+			MapMatrix::is_transposed(self)
+		}
+	}
+
+	fn assert_rows_and_cols_match_get<M: Matrix + MapMatrixOps>(a: &M) {
+		for row in 0..a.size().0 {
+			assert_eq!(a.get_row(row), expected_row(a, row));
+		}
+		for col in 0..a.size().1 {
+			assert_eq!(a.get_col(col), expected_col(a, col));
+		}
+	}
+
+	fn assert_get_row_and_get_col<M: Matrix + MapMatrixOps>() {
+		assert_rows_and_cols_match_get(&sample::<M>());
+	}
+
+	fn assert_get_row_and_get_col_transposed<M: Matrix + MapMatrixOps>() {
+		assert_rows_and_cols_match_get(&sample::<M>().transposed());
+	}
+
+	matrix_tests!(sparse:
+		get_row_and_get_col_match_get => assert_get_row_and_get_col,
+		get_row_and_get_col_follow_pending_transpose => assert_get_row_and_get_col_transposed,
+	);
+
+	fn assert_rows_match_get<M: Matrix>(a: &M) {
+		let rows: Vec<Vec<f64>> = a.iter_rows().collect();
+		assert_eq!(rows.len(), a.size().0);
+		for (i, row) in rows.iter().enumerate() {
+			let expected: Vec<f64> = (0..a.size().1).map(|j| a.get((i, j))).collect();
+			assert_eq!(*row, expected);
+		}
+	}
+
+	fn assert_iter_rows<M: Matrix>() {
+		assert_rows_match_get(&sample::<M>());
+		assert_rows_match_get(&sample::<M>().transposed());
+	}
+
+	matrix_tests!(iter_rows_matches_get => assert_iter_rows);
+
+	/// Matriz aleatoria `n x n` somada a `n * I` e a sua pseudo-inversa, coluna a coluna por gradiente
+	/// conjugado nas equaçoes normais; o produto das duas tem residuos de ponto flutuante fora da diagonal
+	fn cg_pseudo_inverse_pair(n: usize, seed: u64) -> (HashMapMatrix, HashMapMatrix) {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut a = HashMapMatrix::new((n, n));
+		for i in 0..n {
+			for j in 0..n {
+				a.set((i, j), rng.random_range(-1.0..1.0) + if i == j { n as f64 } else { 0.0 });
+			}
+		}
+		let at = a.clone().transposed();
+		let normal = HashMapMatrix::mul(&at, &a);
+		let mut pinv = HashMapMatrix::new((n, n));
+		for j in 0..n {
+			let mut e = vec![0.0; n];
+			e[j] = 1.0;
+			let x = conjugate_gradient(&normal, &at.mul_vec(&e), 1000, 1e-14).unwrap();
+			for (i, value) in x.into_iter().enumerate() {
+				pinv.set((i, j), value);
+			}
+		}
+		(a, pinv)
+	}
+
+	/// Verifica que `c` é a identidade `n x n` a menos de `EPSILON`, sem nenhum valor guardado fora da diagonal
+	fn assert_compressed_identity(c: &impl Matrix, n: usize) {
+		assert_eq!(c.size(), (n, n));
+		for ((i, j), value) in c.iter_nnz().filter(|(_, v)| *v != 0.0) {
+			assert_eq!(i, j, "({i}, {j}) = {value} is above EPSILON");
+			assert!((value - 1.0).abs() < EPSILON);
+		}
+	}
+
+	#[test]
+	fn mul_by_cg_pseudo_inverse_compresses_to_identity() {
+		let n = 50;
+		let (a, pinv) = cg_pseudo_inverse_pair(n, 1044);
+		let identity = HashMapMatrix::mul(&a, &pinv);
+		assert_eq!(identity.nnz(), n);
+		assert_compressed_identity(&identity, n);
+	}
+
+	#[test]
+	fn compress_removes_the_residue_of_a_dense_product() {
+		let n = 50;
+		let (a, pinv) = cg_pseudo_inverse_pair(n, 1044);
+		// O produto denso nao passa por compress e guarda os residuos fora da diagonal
+		let mut dense = TableMatrix::mul(&TableMatrix::from_info(&a.to_info()), &TableMatrix::from_info(&pinv.to_info()));
+		let mut sparse = HashMapMatrix::from_info(&dense.to_info());
+		assert!(sparse.nnz() > n);
+		assert!(sparse.iter_nnz().all(|((i, j), value)| i == j || value.abs() < EPSILON));
+
+		sparse.compress();
+		assert_eq!(sparse.nnz(), n);
+		assert_compressed_identity(&sparse, n);
+		dense.compress();
+		assert_compressed_identity(&dense, n);
+	}
+
+	#[test]
+	fn band_storage_of_laplacian_has_three_rows() {
+		let a: HashMapMatrix = tridiagonal(5, 2.0, -1.0);
+		assert_eq!(a.detect_bandwidth(), (1, 1));
+		let band = a.to_band_storage(1, 1).unwrap();
+		assert_eq!(band.len(), 3 * 5);
+		// cada coluna guarda a superdiagonal, a diagonal e a subdiagonal
+		for (j, column) in band.chunks(3).enumerate() {
+			let above = if j > 0 { -1.0 } else { 0.0 };
+			let below = if j < 4 { -1.0 } else { 0.0 };
+			assert_eq!(column, [above, 2.0, below]);
+		}
+		let back = HashMapMatrix::from_band_storage(&band, 5, 1, 1);
+		assert_eq!(back.to_info_sorted(), a.to_info_sorted());
+		assert_eq!(a.to_band_storage(0, 1), Err(MatrixError::DimensionMismatch { expected: (0, 1), got: (1, 1) }));
+	}
+
+	#[test]
+	fn band_storage_of_wider_and_transposed_bands() {
+		let mut a: TreeMatrix = TreeMatrix::new((4, 4));
+		for (pos, value) in [((0, 0), 1.0), ((2, 0), 2.0), ((1, 2), 3.0), ((3, 3), 4.0)] {
+			a.set(pos, value);
+		}
+		assert_eq!(a.detect_bandwidth(), (2, 1));
+		let band = a.to_band_storage(2, 2).unwrap();
+		assert_eq!(band.len(), 5 * 4);
+		assert_eq!(band[2], 1.0);
+		assert_eq!(band[4], 2.0);
+		assert_eq!(band[2 * 5 + 1], 3.0);
+		let back = TreeMatrix::from_band_storage(&band, 4, 2, 2);
+		assert_eq!(back.to_info_sorted(), a.to_info_sorted());
+
+		let t = a.transposed();
+		assert_eq!(t.detect_bandwidth(), (1, 2));
+		let back = TreeMatrix::from_band_storage(&t.to_band_storage(1, 2).unwrap(), 4, 1, 2);
+		assert_eq!(back.to_info_sorted(), t.to_info_sorted());
+	}
+
+	fn assert_index_matches_get<M: Matrix + Clone + std::ops::Index<Pair, Output = f64>>() {
+		let a: M = crate::test_utils::sample();
+		let transposed = a.clone().transposed();
+		let (rows, cols) = a.size();
+		for i in 0..rows {
+			for j in 0..cols {
+				assert_eq!(a[(i, j)], a.get((i, j)));
+				assert_eq!(transposed[(j, i)], a.get((i, j)));
+			}
+		}
+		assert_eq!(a[(0, 3)], 7.0);
+		assert_eq!(transposed[(3, 0)], 7.0);
+	}
+
+	matrix_tests!(sparse: index_syntax_matches_get => assert_index_matches_get);
+
+	fn assert_debug_and_display_are_sorted<M: Matrix + fmt::Debug + fmt::Display>() {
+		let mut a = M::new((3, 2));
+		a.set((2, 1), 4.5);
+		a.set((0, 1), -1.0);
+		assert_eq!(format!("{a:?}"), "MapMatrix { size: (3, 2), values: {(0, 1): -1.0, (2, 1): 4.5} }");
+		assert_eq!(a.to_string(), "3x2\n(0, 1) = -1\n(2, 1) = 4.5\n");
+		let t = a.transposed();
+		assert_eq!(t.to_string(), "2x3\n(1, 0) = -1\n(1, 2) = 4.5\n");
+	}
+
+	matrix_tests!(sparse: debug_and_display_list_sorted_values => assert_debug_and_display_are_sorted);
+
+	#[test]
+	fn hadamard_keeps_the_intersection_of_the_patterns() {
+		let n = 1000;
+		let mut rng = StdRng::seed_from_u64(1014);
+		let mut random_sparse = || {
+			let mut m = HashMapMatrix::new((n, n));
+			for _ in 0..n * n / 100 {
+				m.set((rng.random_range(0..n), rng.random_range(0..n)), rng.random_range(1.0..2.0));
+			}
+			m
+		};
+		let (a, b) = (random_sparse(), random_sparse());
+		let c = HashMapMatrix::hadamard(&a, &b);
+		let intersection = a.iter_nnz().filter(|(pos, _)| b.get(*pos) != 0.0).count();
+		assert_eq!(c.nnz(), intersection);
+		// 1% * 1% = 0.01% das n^2 posiçoes, cerca de 100 valores
+		assert!((50..200).contains(&c.nnz()), "{}", c.nnz());
+		for (pos, value) in c.iter_nnz() {
+			assert_eq!(value, a.get(pos) * b.get(pos));
+		}
+	}
+
+	fn assert_len_tracks_iter<T: Map<Pair, f64>>(mut map: T) {
+		let mut rng = StdRng::seed_from_u64(1022);
+		assert!(map.is_empty());
+		for _ in 0..500 {
+			let key = (rng.random_range(0..8), rng.random_range(0..8));
+			if rng.random_bool(0.6) {
+				map.set_or_insert(key, rng.random_range(1.0..2.0));
+			} else {
+				map.remove(&key);
+			}
+			assert_eq!(map.len(), map.iter().count());
+			assert_eq!(map.is_empty(), map.len() == 0);
+		}
+		assert!(!map.is_empty());
+	}
+
+	matrix_tests!(stores: len_agrees_with_iter_count => assert_len_tracks_iter);
+
+	fn assert_nnz_matches_iter_nnz<M: Matrix>() {
+		let a: M = sample();
+		assert_eq!(a.nnz(), a.iter_nnz().count());
+	}
+
+	matrix_tests!(sparse: nnz_agrees_with_iter_nnz_count => assert_nnz_matches_iter_nnz);
+
+	fn assert_auto_compact_removes_cancellation_residue<M: Matrix + MapMatrixOps + Clone>() {
+		let mut rng = StdRng::seed_from_u64(941);
+		let mut a = M::new((10, 10));
+		for i in 0..10 {
+			for j in 0..10 {
+				a.set((i, j), rng.random_range(1.0..2.0) / 3.0);
+			}
+		}
+		// 1 + 1e-17 arredonda para 1.0 em f64, entao a perturbaçao usada é 1e-12, que deixa residuos bem abaixo de EPSILON
+		let almost_minus_a = M::muls(&a, -(1.0 + 1e-12));
+		let kept = M::add(&a, &almost_minus_a);
+		assert!(kept.nnz() > 0);
+		assert!(kept.iter_nnz().all(|(_, value)| value.abs() <= EPSILON));
+		let compacted = M::add(&a.clone().with_auto_compact(true), &almost_minus_a);
+		assert_eq!(compacted.nnz(), 0);
+	}
+
+	matrix_tests!(sparse: auto_compact_removes_cancellation_residue => assert_auto_compact_removes_cancellation_residue);
+
+	fn assert_retain<T: Map<Pair, f64>>(mut map: T) {
+		for (key, value) in [((0, 1), 1.0), ((2, 0), -3.0), ((1, 1), 0.5), ((3, 2), 4.0)] {
+			map.set_or_insert(key, value);
+		}
+		let mut seen = Vec::new();
+		map.retain(|key, value| {
+			seen.push(*key);
+			key.0 >= 1 && value.abs() > 0.75
+		});
+		seen.sort();
+		assert_eq!(seen, vec![(0, 1), (1, 1), (2, 0), (3, 2)]);
+		assert_eq!(map.len(), 2);
+		assert_eq!((map.get(&(2, 0)), map.get(&(3, 2))), (Some(&-3.0), Some(&4.0)));
+	}
+
+	matrix_tests!(stores: retain_sees_logical_keys_and_removes_the_rest => assert_retain);
+
+	#[test]
+	fn apply_threshold_removes_only_small_entries() {
+		let mut rng = StdRng::seed_from_u64(1023);
+		let eps = 1e-3;
+		let mut a = TreeMatrix::new((30, 30));
+		for _ in 0..300 {
+			let magnitude = 10f64.powi(rng.random_range(-6..2));
+			a.set((rng.random_range(0..30), rng.random_range(0..30)), magnitude * rng.random_range(1.1..9.0));
+		}
+		let a = a.transposed();
+		let mut b = a.clone();
+		b.apply_threshold(eps);
+		assert!(b.nnz() < a.nnz());
+		for (pos, value) in a.iter_nnz() {
+			if value.abs() >= eps {
+				assert_eq!(b.get(pos), value);
+			} else {
+				assert_eq!(b.get(pos), 0.0);
+			}
+		}
+		assert_eq!(b.nnz(), a.iter_nnz().filter(|(_, v)| v.abs() >= eps).count());
+
+		// O limiar é estrito: valores com modulo igual a `eps` ficam
+		let mut c = TreeMatrix::from_info(&MatrixInfo { size: (2, 2), values: vec![((0, 0), eps), ((0, 1), -eps), ((1, 0), eps / 2.0)] });
+		c.apply_threshold(eps);
+		assert_eq!(c.to_info_sorted().values, vec![((0, 0), eps), ((0, 1), -eps)]);
+	}
+
+	fn assert_drain_empties<T: Map<Pair, f64>>(mut map: T) {
+		let pairs = [((0, 1), 1.0), ((2, 0), -3.0), ((1, 1), 0.5), ((3, 2), 4.0)];
+		for (key, value) in pairs {
+			map.set_or_insert(key, value);
+		}
+		let expected = vec![((0, 1), 1.0), ((1, 1), 0.5), ((2, 0), -3.0), ((3, 2), 4.0)];
+		assert_eq!(map.iter_sorted().map(|(pos, value)| (pos, *value)).collect::<Vec<_>>(), expected);
+		let drained: Vec<(Pair, f64)> = map.drain().collect();
+		assert_eq!(drained.len(), pairs.len());
+		let drained = TreeStore::from_iter(drained);
+		assert_eq!(drained.iter_sorted().map(|(pos, value)| (pos, *value)).collect::<Vec<_>>(), expected);
+		assert!(map.is_empty());
+		assert_eq!(map.iter().count(), 0);
+		map.set_or_insert((5, 5), 1.0);
+		assert_eq!(map.len(), 1);
+	}
+
+	matrix_tests!(stores: drain_yields_every_pair_and_empties_the_map => assert_drain_empties);
+
+	fn assert_iter_sorted_is_ascending<T: Map<Pair, f64>>(mut map: T) {
+		let mut rng = StdRng::seed_from_u64(936);
+		for _ in 0..200 {
+			map.set_or_insert((rng.random_range(0..20), rng.random_range(0..20)), rng.random_range(1.0..2.0));
+		}
+		let keys = map.sorted_keys();
+		assert_eq!(keys.len(), map.len());
+		assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+		let sorted: Vec<(Pair, f64)> = map.iter_sorted().map(|(pos, value)| (pos, *value)).collect();
+		assert_eq!(sorted.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), keys);
+		for (pos, value) in sorted {
+			assert_eq!(map.get(&pos), Some(&value));
+		}
+	}
+
+	matrix_tests!(stores: iter_sorted_yields_keys_in_ascending_order => assert_iter_sorted_is_ascending);
+
+	fn assert_consolidate_keeps_values<T: Map<Pair, f64>>(mut map: T) {
+		for i in 0..1000 {
+			map.set_or_insert((i / 40, i % 40), i as f64 + 1.0);
+		}
+		for i in (0..1000).filter(|i| i % 10 != 0) {
+			map.remove(&(i / 40, i % 40));
+		}
+		let before: Vec<(Pair, f64)> = map.iter_sorted().map(|(pos, value)| (pos, *value)).collect();
+		map.consolidate();
+		let after: Vec<(Pair, f64)> = map.iter_sorted().map(|(pos, value)| (pos, *value)).collect();
+		assert_eq!(after, before);
+		assert_eq!(map.len(), 100);
+		assert!(map.capacity() >= map.len());
+	}
+
+	matrix_tests!(stores: consolidate_keeps_every_value => assert_consolidate_keeps_values);
+
+	fn assert_partition_is_disjoint<T: Map<Pair, f64>>(mut map: T) {
+		let mut rng = StdRng::seed_from_u64(956);
+		for _ in 0..200 {
+			map.set_or_insert((rng.random_range(0..15), rng.random_range(0..25)), rng.random_range(-1.0..1.0));
+		}
+		let (accepted, rejected) = map.partition(|(i, j), value| i < j && *value > 0.0);
+		assert_eq!(accepted.len() + rejected.len(), map.len());
+		for (pos, value) in map.iter() {
+			let (side, other) = if pos.0 < pos.1 && *value > 0.0 { (&accepted, &rejected) } else { (&rejected, &accepted) };
+			assert_eq!(side.get(&pos), Some(value.as_ref()), "{pos:?}");
+			assert_eq!(other.get(&pos), None, "{pos:?}");
+		}
+	}
+
+	matrix_tests!(stores: partition_splits_into_disjoint_maps => assert_partition_is_disjoint);
+
+	fn assert_batch_get_matches_get<T: Map<Pair, f64>>(mut map: T) {
+		let mut rng = StdRng::seed_from_u64(1033);
+		for _ in 0..300 {
+			map.set_or_insert((rng.random_range(0..30), rng.random_range(0..30)), rng.random_range(-1.0..1.0));
+		}
+		// metade das chaves nao esta no mapa, e algumas se repetem
+		let mut keys: Vec<Pair> = (0..500).map(|_| (rng.random_range(0..30), rng.random_range(0..30))).collect();
+		keys.extend_from_within(..20);
+		keys.push((100, 100));
+		let expected: Vec<Option<&f64>> = keys.iter().map(|key| map.get(key)).collect();
+		assert!(expected.iter().any(Option::is_none) && expected.iter().any(Option::is_some));
+		assert_eq!(map.batch_get(&keys), expected);
+		assert_eq!(map.batch_get(&[]), vec![]);
+	}
+
+	matrix_tests!(stores: batch_get_matches_repeated_get => assert_batch_get_matches_get);
+
+	fn assert_entries_round_trip<T: Map<Pair, f64>>(mut map: T) {
+		let mut rng = StdRng::seed_from_u64(1058);
+		for _ in 0..200 {
+			// chaves fora da diagonal e com linhas e colunas de intervalos diferentes, para que a troca apareça
+			map.set_or_insert((rng.random_range(0..20), rng.random_range(20..50)), rng.random_range(-1.0..1.0));
+		}
+		let mut expected: Vec<(Pair, f64)> = map.iter().map(|(key, value)| (key, *value)).collect();
+		expected.sort_by_key(|(key, _)| *key);
+
+		let mut entries = map.clone().into_entries();
+		entries.sort_by_key(|(key, _)| *key);
+		assert_eq!(entries, expected);
+
+		let rebuilt = T::from_entries(entries);
+		assert_eq!(rebuilt.len(), map.len());
+		for (key, value) in &expected {
+			assert_eq!(rebuilt.get(key), Some(value));
+		}
+		let mut again = rebuilt.into_entries();
+		again.sort_by_key(|(key, _)| *key);
+		assert_eq!(again, expected);
+		assert!(T::from_entries(Vec::new()).into_entries().is_empty());
+	}
+
+	matrix_tests!(stores: from_entries_and_into_entries_round_trip => assert_entries_round_trip);
+
+	fn assert_triangular_parts_of<M: Matrix + MapMatrixOps + Clone>(a: &M) {
+		let (upper, lower) = a.split_triangular();
+		assert!(upper.iter_nnz().all(|((i, j), _)| i <= j));
+		assert!(lower.iter_nnz().all(|((i, j), _)| i > j));
+		assert_eq!(upper.nnz() + lower.nnz(), a.nnz());
+		assert_eq!(sorted(M::add(&upper, &lower).to_info()), sorted(a.to_info()));
+		assert_eq!(sorted(a.upper_triangular().to_info()), sorted(upper.to_info()));
+
+		// a diagonal fica nas duas partes de upper_triangular e lower_triangular
+		let full_lower = a.lower_triangular();
+		assert!(full_lower.iter_nnz().all(|((i, j), _)| i >= j));
+		let diagonal = M::from_diagonal(&a.diagonal());
+		assert!(diagonal.nnz() > 0);
+		assert_eq!(sorted(M::sub(&full_lower, &diagonal).to_info()), sorted(lower.to_info()));
+
+		for row in 0..a.size().0 {
+			let (in_row, rest) = a.partition_by_row(row);
+			assert!(in_row.iter_nnz().all(|((i, _), _)| i == row));
+			assert!(rest.iter_nnz().all(|((i, _), _)| i != row));
+			assert_eq!(sorted(M::add(&in_row, &rest).to_info()), sorted(a.to_info()));
+		}
+	}
+
+	fn assert_triangular_parts<M: Matrix + MapMatrixOps + Clone>() {
+		let a: M = random_square(7, 956);
+		assert_triangular_parts_of(&a);
+		assert_triangular_parts_of(&a.transposed());
+		let mut rectangular = M::new((4, 6));
+		for (pos, value) in [((0, 0), 1.0), ((3, 1), 2.0), ((1, 5), 3.0), ((2, 2), 4.0)] {
+			rectangular.set(pos, value);
+		}
+		assert_triangular_parts_of(&rectangular);
+		assert_triangular_parts_of(&rectangular.transposed());
+	}
+
+	matrix_tests!(sparse: triangular_parts_and_row_partition_cover_the_matrix => assert_triangular_parts);
+
+	fn assert_realize_keeps_the_logical_matrix<M: Matrix + MapMatrixOps + Clone>() {
+		let a: M = random((5, 8), 1032);
+		let mut t = a.clone().transposed();
+		assert!(t.is_transposed());
+		let before = t.clone();
+		t.realize();
+		assert!(!t.is_transposed());
+		assert_eq!(t.size(), (8, 5));
+		assert_eq!(t.nnz(), before.nnz());
+		assert_eq!(sorted(t.to_info()), sorted(before.to_info()));
+		assert_eq!(sorted(MatrixInfo { size: t.size(), values: t.iter_nnz().collect() }), sorted(before.to_info()));
+		for i in 0..8 {
+			for j in 0..5 {
+				assert_eq!(t.get((i, j)), a.get((j, i)));
+			}
+		}
+
+		// sem transposiçao pendente, realize nao muda nada
+		let mut b = a.clone();
+		b.realize();
+		assert!(!b.is_transposed());
+		assert_eq!(sorted(b.to_info()), sorted(a.to_info()));
+		// transpor duas vezes cancela a transposiçao pendente
+		let mut twice = a.clone().transposed().transposed();
+		assert!(!twice.is_transposed());
+		twice.realize();
+		assert_eq!(sorted(twice.to_info()), sorted(a.to_info()));
+	}
+
+	matrix_tests!(sparse: realize_keeps_the_logical_matrix => assert_realize_keeps_the_logical_matrix);
+
+	#[test]
+	fn mul_sorted_matches_mul() {
+		let mut rng = StdRng::seed_from_u64(959);
+		let mut random_sparse = |size: Pair| {
+			let mut m = TreeMatrix::new(size);
+			for _ in 0..size.0 * size.1 / 5 {
+				m.set((rng.random_range(0..size.0), rng.random_range(0..size.1)), rng.random_range(-1.0..1.0));
+			}
+			m
+		};
+		let (a, b) = (random_sparse((30, 20)), random_sparse((20, 40)));
+		let (at, bt) = (random_sparse((20, 30)).transposed(), random_sparse((40, 20)).transposed());
+		for (a, b) in [(&a, &b), (&at, &b), (&a, &bt), (&at, &bt)] {
+			let sorted = TreeMatrix::mul_sorted(a, b);
+			assert_eq!(sorted.size(), (30, 40));
+			assert_matrix_close(&sorted, &TreeMatrix::mul(a, b), 1e-12);
+			assert_eq!(sorted.nnz(), TreeMatrix::mul(a, b).nnz());
+		}
+
+		let empty = TreeMatrix::mul_sorted(&TreeMatrix::new((30, 20)), &b);
+		assert_eq!((empty.size(), empty.nnz()), ((30, 40), 0));
+		let empty = TreeMatrix::mul_sorted(&a, &TreeMatrix::new((20, 0)));
+		assert_eq!((empty.size(), empty.nnz()), ((30, 0), 0));
+	}
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for multiplication")]
+	fn mul_sorted_panics_on_dimension_mismatch() {
+		TreeMatrix::mul_sorted(&TreeMatrix::new((2, 3)), &TreeMatrix::new((2, 3)));
+	}
+
+	#[test]
+	fn consolidate_shrinks_the_capacity_of_a_hash_map_store() {
+		let mut map = HashMapStore::<Pair, f64>::from_iter((0..1000).map(|i| ((i, i), 1.0)));
+		for i in 100..1000 {
+			map.remove(&(i, i));
+		}
+		let capacity = Map::capacity(&map);
+		map.consolidate();
+		assert!(Map::capacity(&map) < capacity, "{} >= {capacity}", Map::capacity(&map));
+		assert_eq!(map.len(), 100);
+	}
+
+	#[test]
+	fn threshold_sparsify_consolidates_after_removing_more_than_a_tenth() {
+		let mut a = HashMapMatrix::new((100, 100));
+		for i in 0..100 {
+			for j in 0..10 {
+				a.set((i, j), if j == 0 { 1.0 } else { 1e-12 });
+			}
+		}
+		let expected = HashMapMatrix::from_info(&MatrixInfo { size: (100, 100), values: (0..100).map(|i| ((i, 0), 1.0)).collect() });
+		let capacity = a.values.capacity();
+		assert_eq!(a.threshold_sparsify(EPSILON), 900);
+		assert!(a.values.capacity() < capacity, "{} >= {capacity}", a.values.capacity());
+		assert_eq!(a.to_info_sorted(), expected.to_info_sorted());
+
+		// removendo ate 10% dos valores o mapa nao é reconstruido, mas os valores grandes continuam
+		let mut b = HashMapMatrix::new((100, 100));
+		for i in 0..100 {
+			for j in 0..10 {
+				b.set((i, j), if j == 0 && i % 2 == 0 { 1e-12 } else { 1.0 });
+			}
+		}
+		assert_eq!(b.threshold_sparsify(EPSILON), 50);
+		assert_eq!(b.nnz(), 950);
+		assert!(b.iter_nnz().all(|(_, value)| value == 1.0));
+	}
+
+	fn assert_into_info_matches_to_info<M: Matrix + MapMatrixOps + Clone>() {
+		let a: M = sample();
+		let t = a.clone().transposed();
+		for m in [a, t] {
+			assert_eq!(sorted(m.clone().into_info()), sorted(m.to_info()));
+		}
+	}
+
+	matrix_tests!(sparse: into_info_matches_to_info => assert_into_info_matches_to_info);
+
+	fn from_rows<M: Matrix>(rows: &[Vec<f64>]) -> M {
+		M::from_info(&MatrixInfo::from_dense(rows).unwrap())
+	}
+
+	#[test]
+	fn operators_delegate_to_the_trait_methods() {
+		let a: HashMapMatrix = from_rows(&[vec![1.0, 2.0], vec![3.0, 4.0]]);
+		let b: HashMapMatrix = from_rows(&[vec![0.0, 1.0], vec![1.0, 0.0]]);
+		let c: HashMapMatrix = from_rows(&[vec![1.0, 1.0], vec![1.0, 1.0]]);
+		assert_eq!(&a * &b + &c, from_rows(&[vec![3.0, 2.0], vec![5.0, 4.0]]));
+		assert_eq!(&a + &c, from_rows(&[vec![2.0, 3.0], vec![4.0, 5.0]]));
+		assert_eq!(&a - &c, from_rows(&[vec![0.0, 1.0], vec![2.0, 3.0]]));
+		assert_eq!(&a * 2.0, from_rows(&[vec![2.0, 4.0], vec![6.0, 8.0]]));
+		assert_eq!(-a.clone(), from_rows(&[vec![-1.0, -2.0], vec![-3.0, -4.0]]));
+
+		let mut d = a.clone();
+		d += &c;
+		d *= 0.5;
+		assert_eq!(d, from_rows(&[vec![1.0, 1.5], vec![2.0, 2.5]]));
+
+		let tree_a: TreeMatrix = from_rows(&[vec![1.0, 2.0], vec![3.0, 4.0]]);
+		let tree_b: TreeMatrix = from_rows(&[vec![0.0, 1.0], vec![1.0, 0.0]]);
+		assert_eq!(&(&tree_a * &tree_b) - &tree_a, from_rows(&[vec![1.0, -1.0], vec![1.0, -1.0]]));
+	}
+
+	fn sparse_row(row: Vec<f64>) -> Vec<(usize, f64)> {
+		row.into_iter().enumerate().filter(|(_, value)| *value != 0.0).collect()
+	}
+
+	fn assert_from_rows_and_cols_round_trip<M: Matrix>(a: &M) {
+		let (rows, cols) = a.size();
+		let by_rows = HashMapMatrix::from_rows(a.iter_rows().map(sparse_row), cols);
+		let by_cols = TreeMatrix::from_cols(a.iter_cols().map(sparse_row), rows);
+		assert_matrix_close(&by_rows, a, EPSILON);
+		assert_matrix_close(&by_cols, a, EPSILON);
+	}
+
+	fn assert_from_rows_and_cols<M: Matrix>() {
+		assert_from_rows_and_cols_round_trip(&sample::<M>());
+		assert_from_rows_and_cols_round_trip(&sample::<M>().transposed());
+		let mut rng = StdRng::seed_from_u64(1068);
+		let mut a = M::new((30, 40));
+		for _ in 0..60 {
+			a.set((rng.random_range(0..30), rng.random_range(0..40)), rng.random_range(-1.0..1.0));
+		}
+		assert_from_rows_and_cols_round_trip(&a);
+	}
+
+	matrix_tests!(from_rows_and_from_cols_round_trip => assert_from_rows_and_cols);
+
+	#[test]
+	fn from_rows_skips_zeros_and_keeps_trailing_empty_rows() {
+		let m = HashMapMatrix::from_rows(vec![vec![(1, 2.0), (0, 0.0), (1, 3.0)], vec![], vec![]], 4);
+		assert_eq!(m.size(), (3, 4));
+		assert_eq!(m.to_info_sorted().values, vec![((0, 1), 3.0)]);
+		assert_eq!(TreeMatrix::from_cols(Vec::<Vec<(usize, f64)>>::new(), 5).size(), (5, 0));
+	}
+
+	#[test]
+	#[should_panic(expected = "column out of bounds")]
+	fn from_rows_panics_on_column_out_of_bounds() {
+		HashMapMatrix::from_rows(vec![vec![(3, 1.0)]], 3);
+	}
+}
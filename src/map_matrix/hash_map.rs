@@ -1,21 +1,45 @@
 
 use crate::map_matrix::{Map, MapVec};
 
-/// https://docs.rs/hashbrown/latest/src/hashbrown/raw/mod.rs.html#1496-1524
-/// https://docs.rs/hashbrown/latest/src/hashbrown/raw/mod.rs.html#103-160
+// https://docs.rs/hashbrown/latest/src/hashbrown/raw/mod.rs.html#1496-1524
+// https://docs.rs/hashbrown/latest/src/hashbrown/raw/mod.rs.html#103-160
 
-use std::{borrow::Cow, collections::HashMap, hash::Hash};
+use std::{borrow::Cow, collections::HashMap, hash::{BuildHasher, Hash, RandomState}};
 
+/// Mapa baseado no `HashMap` da biblioteca padrao
+/// `H` é o construtor do hasher; o padrao `RandomState` usa SipHash, que resiste a ataques de colisao
+/// mas é mais lento que hashers como o AHash (ver `AHashMapStore`)
 #[derive(Clone)]
-pub struct HashMapStore<K :Copy + Eq + Hash, V> {
-	values: HashMap<K, V>,
+pub struct HashMapStore<K :Copy + Eq + Hash, V, H = RandomState> {
+	values: HashMap<K, V, H>,
+}
+
+/// `HashMapStore` com o hasher AHash, mais rapido que o SipHash para chaves que nao vem de um adversario
+#[cfg(feature = "ahash")]
+pub type AHashMapStore<K, V> = HashMapStore<K, V, ahash::RandomState>;
+
+impl<K : Copy + Eq + Hash, V, H : BuildHasher> HashMapStore<K, V, H> {
+	/// Retorna quantos elementos o `HashMap` comporta sem realocar
+	pub fn capacity(&self) -> usize {
+		self.values.capacity()
+	}
+	/// Reserva espaço para pelo menos `additional` novos elementos
+	pub fn reserve(&mut self, additional: usize) {
+		self.values.reserve(additional);
+	}
+	/// Reduz a capacidade do `HashMap` o maximo possivel
+	pub fn shrink_to_fit(&mut self) {
+		self.values.shrink_to_fit();
+	}
 }
-impl<K : Copy + Eq + Hash, V : Clone> Map<K, V> for HashMapStore<K, V> {
-	fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
-		let values : Vec<(K, V)> = iter.into_iter().collect();
 
+impl<K : Copy + Eq + Hash, V : Clone, H : BuildHasher + Default + Clone> Map<K, V> for HashMapStore<K, V, H> {
+	fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
+		Self::from_entries(iter.into_iter().collect())
+	}
+	fn from_entries(entries: Vec<(K, V)>) -> Self {
 		HashMapStore {
-			values: HashMap::from_iter(values.into_iter()),
+			values: HashMap::from_iter(entries),
 		}
 	}
 	fn set_or_insert(&mut self, key: K, value: V) {
@@ -27,6 +51,22 @@ impl<K : Copy + Eq + Hash, V : Clone> Map<K, V> for HashMapStore<K, V> {
 	fn get(&self, key: &K) -> Option<&V> {
 		self.values.get(key)
 	}
+	fn len(&self) -> usize {
+		self.values.len()
+	}
+	/// Reserva o vetor de resultado de uma vez e faz uma unica busca no `HashMap` por chave
+	fn batch_get<'a>(&'a self, keys: &[K]) -> Vec<Option<&'a V>> {
+		let mut result = Vec::with_capacity(keys.len());
+		result.extend(keys.iter().map(|key| self.values.get(key)));
+		result
+	}
+	/// `HashMap::extend` reserva espaço pelo tamanho do lote antes de inserir, evitando varios redimensionamentos da tabela
+	fn batch_set(&mut self, items: impl IntoIterator<Item=(K, V)>) {
+		self.values.extend(items);
+	}
+	fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+		self.values.retain(|k, v| f(k, v));
+	}
 	
 	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(K, Cow<'a, V>)> + 'a> {
 		Box::new(self.values.iter()
@@ -37,13 +77,57 @@ impl<K : Copy + Eq + Hash, V : Clone> Map<K, V> for HashMapStore<K, V> {
 		Box::new(self.values.iter_mut()
 			.map(|(k, v)| (*k, v)) )
 	}
+
+	/// Usa `HashMap::drain`, que mantem a capacidade do mapa
+	fn drain<'a>(&'a mut self) -> Box<dyn Iterator<Item=(K, V)> + 'a> where K: 'a, V: 'a {
+		Box::new(self.values.drain())
+	}
+
+	fn into_entries(self) -> Vec<(K, V)> {
+		self.values.into_iter().collect()
+	}
+
+	fn capacity(&self) -> usize {
+		HashMapStore::capacity(self)
+	}
+
+	fn reserve(&mut self, additional: usize) {
+		HashMapStore::reserve(self, additional);
+	}
+
+	fn shrink_to_fit(&mut self) {
+		HashMapStore::shrink_to_fit(self);
+	}
+
+	/// O `HashMap` (hashbrown) aloca `buckets` posiçoes para os pares mais um byte de controle por posiçao
+	/// e um grupo extra de 16 bytes de controle, onde `buckets` é 4 ou 8 para capacidades pequenas
+	/// e `(capacity * 8 / 7).next_power_of_two()` nos outros casos:
+	/// `buckets * (size_of::<(K, V)>() + 1) + 16`
+	fn memory_estimate(&self) -> usize {
+		let capacity = self.values.capacity();
+		let buckets = match capacity {
+			0 => return 0,
+			1..4 => 4,
+			4..8 => 8,
+			_ => (capacity * 8 / 7).next_power_of_two(),
+		};
+		buckets * (size_of::<(K, V)>() + 1) + 16
+	}
+
+	/// Reinsere todos os elementos em um `HashMap` alocado com a capacidade exata,
+	/// deixando o fator de carga no valor que a tabela usa logo apos ser criada
+	fn consolidate(&mut self) {
+		let mut values = HashMap::with_capacity_and_hasher(self.values.len(), H::default());
+		values.extend(self.values.drain());
+		self.values = values;
+	}
 } 
 
 
-impl <K : Copy + Eq + Hash, U : Clone> MapVec<K, U> for HashMapStore<K, Vec<U>> {
+impl <K : Copy + Eq + Hash, U : Clone, H : BuildHasher + Default + Clone> MapVec<K, U> for HashMapStore<K, Vec<U>, H> {
 	fn add_to_vec(&mut self, key: K, value: U) {
 		self.values.entry(key)
-			.or_insert_with(Vec::new)
+			.or_default()
 			.push(value);
 	}
 }
@@ -0,0 +1,167 @@
+use crate::{basic::Pair, map_matrix::Map};
+
+use std::borrow::Cow;
+
+/// Tabela hash de enderecamento aberto com sondagem linear, especifica para chaves `Pair`
+///
+/// Usa o hash polinomial `h(r, c) = r * 1_000_000 + c` (espalhado) modulo a capacidade e, em caso de colisao,
+/// procura a proxima posiçao livre. Os pares ficam direto no vetor `slots`, sem indireçao,
+/// o que deixa as sondagens proximas na memoria.
+/// A tabela dobra de tamanho quando a ocupaçao passa de 70%.
+#[derive(Clone)]
+pub struct OpenAddrHashStore {
+	slots: Vec<Option<(Pair, f64)>>,
+	/// Numero de pares guardados
+	size: usize,
+	/// Numero de posiçoes em `slots`
+	capacity: usize,
+}
+
+const MIN_CAPACITY: usize = 8;
+
+impl OpenAddrHashStore {
+	fn with_slots(capacity: usize) -> Self {
+		OpenAddrHashStore {
+			slots: vec![None; capacity],
+			size: 0,
+			capacity,
+		}
+	}
+
+	/// Menor numero de posiçoes que guarda `len` pares sem passar de 70% de ocupaçao
+	fn slots_for(len: usize) -> usize {
+		(len * 10 / 7 + 1).max(MIN_CAPACITY)
+	}
+
+	/// `h(r, c) = r * 1_000_000 + c`, espalhado com uma multiplicaçao de Fibonacci antes do modulo
+	/// Sem o espalhamento, quando a capacidade é menor que 1_000_000 os inicios das linhas `r * 1_000_000 % capacity`
+	/// ficam concentrados em poucas posiçoes e as colunas consecutivas formam sequencias longas de colisoes
+	fn hash(&self, key: &Pair) -> usize {
+		let h = key.0.wrapping_mul(1_000_000).wrapping_add(key.1) as u64;
+		(h.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % self.capacity
+	}
+
+	/// Posiçao onde a chave esta, ou a posiçao livre onde ela seria inserida
+	fn find_slot(&self, key: &Pair) -> usize {
+		let mut index = self.hash(key);
+		while let Some((k, _)) = &self.slots[index] {
+			if k == key {
+				break;
+			}
+			index = (index + 1) % self.capacity;
+		}
+		index
+	}
+
+	/// Reinsere todos os pares em uma tabela com `capacity` posiçoes
+	fn rehash(&mut self, capacity: usize) {
+		let old = std::mem::replace(self, Self::with_slots(capacity));
+		for (key, value) in old.slots.into_iter().flatten() {
+			let index = self.find_slot(&key);
+			self.slots[index] = Some((key, value));
+			self.size += 1;
+		}
+	}
+}
+
+impl Map<Pair, f64> for OpenAddrHashStore {
+	fn from_iter<I: IntoIterator<Item=(Pair, f64)>>(iter: I) -> Self {
+		Self::from_entries(iter.into_iter().collect())
+	}
+
+	/// Aloca as posiçoes para o tamanho do vetor de uma vez, sem redimensionar durante as inserçoes
+	fn from_entries(entries: Vec<(Pair, f64)>) -> Self {
+		let mut store = Self::with_slots(Self::slots_for(entries.len()));
+		for (key, value) in entries {
+			store.set_or_insert(key, value);
+		}
+		store
+	}
+
+	fn set_or_insert(&mut self, key: Pair, value: f64) {
+		if (self.size + 1) * 10 > self.capacity * 7 {
+			self.rehash(self.capacity * 2);
+		}
+		let index = self.find_slot(&key);
+		if self.slots[index].is_none() {
+			self.size += 1;
+		}
+		self.slots[index] = Some((key, value));
+	}
+
+	/// Remove com deslocamento para tras: os pares seguintes da mesma sequencia de sondagem
+	/// sao movidos para preencher o buraco, assim nao sao necessarias marcas de remoçao
+	fn remove(&mut self, key: &Pair) {
+		let mut hole = self.find_slot(key);
+		if self.slots[hole].is_none() {
+			return;
+		}
+		self.slots[hole] = None;
+		self.size -= 1;
+		let mut index = (hole + 1) % self.capacity;
+		while let Some((k, _)) = &self.slots[index] {
+			let home = self.hash(k);
+			// o par pode ir para o buraco se a sua posiçao ideal nao estiver entre o buraco e a posiçao atual
+			let distance_to_hole = (hole + self.capacity - home) % self.capacity;
+			let distance_to_index = (index + self.capacity - home) % self.capacity;
+			if distance_to_hole < distance_to_index {
+				self.slots[hole] = self.slots[index].take();
+				hole = index;
+			}
+			index = (index + 1) % self.capacity;
+		}
+	}
+
+	fn get(&self, key: &Pair) -> Option<&f64> {
+		self.slots[self.find_slot(key)].as_ref().map(|(_, value)| value)
+	}
+
+	fn len(&self) -> usize {
+		self.size
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(Pair, Cow<'a, f64>)> + 'a> {
+		Box::new(self.slots.iter()
+			.flatten()
+			.map(|(k, v)| (*k, Cow::Borrowed(v))))
+	}
+
+	fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item=(Pair, &'a mut f64)> + 'a> {
+		Box::new(self.slots.iter_mut()
+			.flatten()
+			.map(|(k, v)| (*k, v)))
+	}
+
+	/// Troca a tabela por uma vazia de capacidade minima e consome as posiçoes ocupadas da antiga
+	fn drain<'a>(&'a mut self) -> Box<dyn Iterator<Item=(Pair, f64)> + 'a> where Pair: 'a, f64: 'a {
+		let old = std::mem::replace(self, OpenAddrHashStore::with_slots(MIN_CAPACITY));
+		Box::new(old.slots.into_iter().flatten())
+	}
+
+	fn into_entries(self) -> Vec<(Pair, f64)> {
+		self.slots.into_iter().flatten().collect()
+	}
+
+	fn memory_estimate(&self) -> usize {
+		self.capacity * size_of::<Option<(Pair, f64)>>()
+	}
+
+	fn capacity(&self) -> usize {
+		self.capacity * 7 / 10
+	}
+
+	fn reserve(&mut self, additional: usize) {
+		let needed = Self::slots_for(self.size + additional);
+		if needed > self.capacity {
+			self.rehash(needed);
+		}
+	}
+
+	fn shrink_to_fit(&mut self) {
+		self.rehash(Self::slots_for(self.size));
+	}
+
+	fn consolidate(&mut self) {
+		self.rehash(Self::slots_for(self.size));
+	}
+}
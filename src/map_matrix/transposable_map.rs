@@ -3,12 +3,12 @@ use std::{borrow::Cow};
 use crate::{basic::Pair, map_matrix::Map};
 
 #[derive(Clone)]
-pub struct TransposableMap<M : Map<Pair, f64>> {
+pub struct TransposableMap<M> {
 	map: M,
 	transposed: bool
 }
 
-impl<M : Map<Pair, f64>> TransposableMap<M>  {
+impl<M> TransposableMap<M>  {
 	pub  fn new(map: M) -> Self {
 		TransposableMap {
 			map,
@@ -18,16 +18,56 @@ impl<M : Map<Pair, f64>> TransposableMap<M>  {
 	pub fn transpose(&mut self) {
 		self.transposed = !self.transposed;
 	}
+	pub fn is_transposed(&self) -> bool {
+		self.transposed
+	}
+	/// Reconstroi o mapa interno com as chaves trocadas, `(r, c)` para `(c, r)`, e desliga a transposiçao
+	/// Depois disso `get`, `set_or_insert` e `iter` nao precisam mais trocar as chaves
+	/// Nao faz nada se o mapa nao estiver transposto
+	pub fn realize<S : Clone>(&mut self) where M : Map<Pair, S> {
+		if self.transposed {
+			self.map = M::from_iter(self.map.iter().map(|(pos, value)| ((pos.1, pos.0), value.into_owned())));
+			self.transposed = false;
+		}
+	}
+
+	/// Pares da linha `row` da matriz, sem ordem definida
+	/// Sem transposiçao pendente a linha é o intervalo `[(row, 0), (row + 1, 0))` do mapa interno, percorrido com
+	/// `Map::iter_range` (uma busca no intervalo em mapas ordenados); transposta, a linha esta espalhada e o mapa é filtrado.
+	pub fn iter_row<'a, S : Clone + 'a>(&'a self, row: usize) -> Box<dyn Iterator<Item=(Pair, Cow<'a, S>)> + 'a> where M : Map<Pair, S> {
+		if self.transposed {
+			Box::new(self.map.iter().filter(move |(pos, _)| pos.1 == row).map(|(pos, value)| ((pos.1, pos.0), value)))
+		} else {
+			self.map.iter_range((row, 0), (row + 1, 0))
+		}
+	}
+
+	/// Pares da coluna `col` da matriz, sem ordem definida
+	/// Com a transposiçao pendente a coluna é uma linha do mapa interno e usa `Map::iter_range`; senao o mapa é filtrado.
+	pub fn iter_col<'a, S : Clone + 'a>(&'a self, col: usize) -> Box<dyn Iterator<Item=(Pair, Cow<'a, S>)> + 'a> where M : Map<Pair, S> {
+		if self.transposed {
+			Box::new(self.map.iter_range((col, 0), (col + 1, 0)).map(|(pos, value)| ((pos.1, pos.0), value)))
+		} else {
+			Box::new(self.map.iter().filter(move |(pos, _)| pos.1 == col))
+		}
+	}
 }
-impl<M : Map<Pair, f64>> Map<Pair, f64> for TransposableMap<M> {
-	fn from_iter<I: IntoIterator<Item=(Pair,f64)>>(iter: I) -> Self {
+impl<S : Clone, M : Map<Pair, S>> Map<Pair, S> for TransposableMap<M> {
+	fn from_iter<I: IntoIterator<Item=(Pair,S)>>(iter: I) -> Self {
 		TransposableMap {
 			map: M::from_iter(iter),
 			transposed: false
 		}
 	}
 
-	fn set_or_insert(&mut self, key: Pair, value: f64) {
+	fn from_entries(entries: Vec<(Pair, S)>) -> Self {
+		TransposableMap {
+			map: M::from_entries(entries),
+			transposed: false
+		}
+	}
+
+	fn set_or_insert(&mut self, key: Pair, value: S) {
 		if self.transposed {
 			self.map.set_or_insert((key.1, key.0), value);
 		} else {
@@ -43,7 +83,7 @@ impl<M : Map<Pair, f64>> Map<Pair, f64> for TransposableMap<M> {
 		}
 	}
 
-	fn get(&self, key: &Pair) -> Option<&f64> {
+	fn get(&self, key: &Pair) -> Option<&S> {
 		if self.transposed {
 			self.map.get(&(key.1, key.0))
 		} else {
@@ -51,7 +91,39 @@ impl<M : Map<Pair, f64>> Map<Pair, f64> for TransposableMap<M> {
 		}
 	}
 
-	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(Pair, Cow<'a, f64>)> + 'a> {
+	/// Troca as chaves antes de repassar o lote ao mapa interno, quando o mapa esta transposto
+	fn batch_get<'a>(&'a self, keys: &[Pair]) -> Vec<Option<&'a S>> {
+		if self.transposed {
+			let swapped: Vec<Pair> = keys.iter().map(|key| (key.1, key.0)).collect();
+			self.map.batch_get(&swapped)
+		} else {
+			self.map.batch_get(keys)
+		}
+	}
+
+	fn batch_set(&mut self, items: impl IntoIterator<Item=(Pair, S)>) {
+		if self.transposed {
+			self.map.batch_set(items.into_iter().map(|(key, value)| ((key.1, key.0), value)));
+		} else {
+			self.map.batch_set(items);
+		}
+	}
+
+	/// Transpor nao muda o numero de pares
+	fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// `f` recebe as chaves na orientaçao da matriz, desfazendo a troca quando o mapa esta transposto
+	fn retain(&mut self, mut f: impl FnMut(&Pair, &S) -> bool) {
+		if self.transposed {
+			self.map.retain(|key, value| f(&(key.1, key.0), value));
+		} else {
+			self.map.retain(f);
+		}
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(Pair, Cow<'a, S>)> + 'a> {
 		if self.transposed {
 			Box::new(self.map.iter()
 				.map(|(pos, value)| {
@@ -61,7 +133,7 @@ impl<M : Map<Pair, f64>> Map<Pair, f64> for TransposableMap<M> {
 			self.map.iter()
 		}
 	}
-	fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item=(Pair, &'a mut f64)> + 'a> {
+	fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item=(Pair, &'a mut S)> + 'a> {
 		if self.transposed {
 			Box::new(self.map.iter_mut()
 				.map(|(pos, value)| {
@@ -71,5 +143,73 @@ impl<M : Map<Pair, f64>> Map<Pair, f64> for TransposableMap<M> {
 			self.map.iter_mut()
 		}
 	}
+
+	fn drain<'a>(&'a mut self) -> Box<dyn Iterator<Item=(Pair, S)> + 'a> where S: 'a {
+		if self.transposed {
+			Box::new(self.map.drain().map(|(pos, value)| ((pos.1, pos.0), value)))
+		} else {
+			self.map.drain()
+		}
+	}
+
+	/// Troca as chaves no proprio vetor de `M::into_entries` quando a transposiçao esta pendente
+	fn into_entries(self) -> Vec<(Pair, S)> {
+		let mut entries = self.map.into_entries();
+		if self.transposed {
+			for (pos, _) in entries.iter_mut() {
+				*pos = (pos.1, pos.0);
+			}
+		}
+		entries
+	}
+
+	fn sorted_keys(&self) -> Vec<Pair> {
+		if self.transposed {
+			let mut keys: Vec<Pair> = self.iter().map(|(pos, _)| pos).collect();
+			keys.sort_unstable();
+			keys
+		} else {
+			self.map.sorted_keys()
+		}
+	}
+
+	fn iter_sorted<'a>(&'a self) -> Box<dyn Iterator<Item=(Pair, Cow<'a, S>)> + 'a> where Pair: 'a {
+		if self.transposed {
+			let mut entries: Vec<(Pair, Cow<'a, S>)> = self.iter().collect();
+			entries.sort_unstable_by_key(|(pos, _)| *pos);
+			Box::new(entries.into_iter())
+		} else {
+			self.map.iter_sorted()
+		}
+	}
+
+	fn iter_range<'a>(&'a self, from: Pair, to: Pair) -> Box<dyn Iterator<Item=(Pair, Cow<'a, S>)> + 'a> where Pair: 'a {
+		if self.transposed {
+			// as chaves transpostas do intervalo nao sao contiguas no mapa interno
+			Box::new(self.iter().filter(move |(pos, _)| *pos >= from && *pos < to))
+		} else {
+			self.map.iter_range(from, to)
+		}
+	}
+
+	fn memory_estimate(&self) -> usize {
+		self.map.memory_estimate()
+	}
+
+	fn capacity(&self) -> usize {
+		self.map.capacity()
+	}
+
+	fn reserve(&mut self, additional: usize) {
+		self.map.reserve(additional);
+	}
+
+	fn shrink_to_fit(&mut self) {
+		self.map.shrink_to_fit();
+	}
+
+	fn consolidate(&mut self) {
+		self.map.consolidate();
+	}
 }
 
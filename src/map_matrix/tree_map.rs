@@ -9,9 +9,11 @@ pub struct TreeStore<K : Copy + Eq + Hash + Ord, V> {
 }
 impl<K : Copy + Eq + Hash + Ord, V : Clone> Map<K, V> for TreeStore<K, V> {
 	fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
-		let values : Vec<(K, V)> = iter.into_iter().collect();
+		Self::from_entries(iter.into_iter().collect())
+	}
+	fn from_entries(entries: Vec<(K, V)>) -> Self {
 		TreeStore {
-			values: BTreeMap::from_iter(values.into_iter()),
+			values: BTreeMap::from_iter(entries),
 		}
 	}
 	fn set_or_insert(&mut self, key: K, value: V) {
@@ -23,6 +25,27 @@ impl<K : Copy + Eq + Hash + Ord, V : Clone> Map<K, V> for TreeStore<K, V> {
 	fn get(&self, key: &K) -> Option<&V> {
 		self.values.get(key)
 	}
+	fn len(&self) -> usize {
+		self.values.len()
+	}
+	/// Escolhe a estrategia pelo tamanho b do lote em relaçao aos n pares do mapa:
+	/// lotes pequenos sao inseridos um a um, em O(b log n); quando b log n passa de n, o lote é ordenado em um
+	/// `BTreeMap` e juntado com `BTreeMap::append`, que reconstroi a arvore em O(n + b log b)
+	fn batch_set(&mut self, items: impl IntoIterator<Item=(K, V)>) {
+		let items: Vec<(K, V)> = items.into_iter().collect();
+		let n = self.values.len();
+		if items.len() * (n.max(1).ilog2() as usize + 1) < n {
+			for (key, value) in items {
+				self.values.insert(key, value);
+			}
+		} else {
+			let mut batch = BTreeMap::from_iter(items);
+			self.values.append(&mut batch);
+		}
+	}
+	fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+		self.values.retain(|k, v| f(k, v));
+	}
 	fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(K, Cow<'a, V>)> + 'a> {
 		Box::new(self.values.iter()
 			.map(|(k, v)| (*k, Cow::Borrowed(v))) )
@@ -31,15 +54,77 @@ impl<K : Copy + Eq + Hash + Ord, V : Clone> Map<K, V> for TreeStore<K, V> {
 		Box::new(self.values.iter_mut()
 			.map(|(k, v)| (*k, v)) )
 	}
-
+	/// O `BTreeMap` nao tem `drain`: troca o mapa por um vazio e consome o antigo, em ordem de chave
+	fn drain<'a>(&'a mut self) -> Box<dyn Iterator<Item=(K, V)> + 'a> where K: 'a, V: 'a {
+		Box::new(std::mem::take(&mut self.values).into_iter())
+	}
+	/// Os pares saem em ordem de chave
+	fn into_entries(self) -> Vec<(K, V)> {
+		self.values.into_iter().collect()
+	}
+	fn sorted_keys(&self) -> Vec<K> {
+		self.values.keys().copied().collect()
+	}
+	fn iter_sorted<'a>(&'a self) -> Box<dyn Iterator<Item=(K, Cow<'a, V>)> + 'a> where K: 'a {
+		self.iter()
+	}
+	fn iter_range<'a>(&'a self, from: K, to: K) -> Box<dyn Iterator<Item=(K, Cow<'a, V>)> + 'a> where K: 'a {
+		Box::new(self.values.range(from..to)
+			.map(|(k, v)| (*k, Cow::Borrowed(v))) )
+	}
+	/// Cada par no `BTreeMap` custa a chave, o valor e cerca de 3 ponteiros de estrutura dos nós:
+	/// `n * (size_of::<K>() + size_of::<V>() + 3 * size_of::<*const ()>())`
+	/// É uma estimativa pessimista: mapas montados com `from_iter` tem nós cheios e usam cerca de metade disso
+	fn memory_estimate(&self) -> usize {
+		self.values.len() * (size_of::<K>() + size_of::<V>() + 3 * size_of::<*const ()>())
+	}
+	// O BTreeMap aloca nós sob demanda e nao tem capacidade reservada,
+	// entao capacity, reserve e shrink_to_fit usam as implementaçoes padrao do Map
 }
 
 
 impl <K : Copy + Eq + Hash + Ord, U : Clone> MapVec<K, U> for TreeStore<K, Vec<U>> {
 	fn add_to_vec(&mut self, key: K, value: U) {
 		self.values.entry(key)
-			.or_insert_with(Vec::new)
+			.or_default()
 			.push(value);
 	}
 }
 
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn batch_set_matches_inserts(existing: usize, batch: usize) {
+		let mut store: TreeStore<usize, usize> = Map::from_iter((0..existing).map(|k| (2 * k, k)));
+		let items: Vec<(usize, usize)> = (0..batch).map(|k| (3 * k, 100 + k)).chain([(0, 7)]).collect();
+		let mut expected = store.values.clone();
+		for (key, value) in items.iter().copied() {
+			expected.insert(key, value);
+		}
+		store.batch_set(items);
+		assert_eq!(store.values, expected);
+	}
+
+	#[test]
+	fn batch_set_small_batch_inserts_each_pair() {
+		batch_set_matches_inserts(1000, 3);
+	}
+
+	#[test]
+	fn batch_set_large_batch_appends() {
+		batch_set_matches_inserts(10, 500);
+	}
+
+	#[test]
+	fn iter_range_yields_the_keys_in_the_half_open_interval_in_order() {
+		let store: TreeStore<(usize, usize), f64> = Map::from_iter((0..5).flat_map(|i| (0..4).map(move |j| ((i, j), (i * 4 + j) as f64))));
+		let row: Vec<((usize, usize), f64)> = store.iter_range((2, 0), (3, 0)).map(|(k, v)| (k, *v)).collect();
+		assert_eq!(row, vec![((2, 0), 8.0), ((2, 1), 9.0), ((2, 2), 10.0), ((2, 3), 11.0)]);
+		let keys: Vec<(usize, usize)> = store.iter_range((1, 3), (3, 1)).map(|(k, _)| k).collect();
+		assert_eq!(keys, vec![(1, 3), (2, 0), (2, 1), (2, 2), (2, 3), (3, 0)]);
+		assert_eq!(store.iter_range((7, 0), (8, 0)).count(), 0);
+		assert_eq!(store.iter_range((0, 0), (9, 0)).count(), store.len());
+	}
+}
@@ -0,0 +1,173 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::{basic::{Matrix, MatrixInfo, Pair}, table_matrix::TableMatrix};
+
+/// Gerador de matrizes aleatorias para testes e benchmarks
+/// Criado com `with_seed`, gera sempre a mesma sequencia de matrizes, o que permite comparar execuçoes diferentes
+pub struct MatrixGenerator {
+	rng: StdRng,
+}
+
+impl Default for MatrixGenerator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MatrixGenerator {
+	/// Cria um gerador com uma semente aleatoria, tirada do gerador do sistema
+	pub fn new() -> Self {
+		MatrixGenerator {
+			rng: StdRng::from_rng(&mut rand::rng()),
+		}
+	}
+	/// Cria um gerador reprodutivel: dois geradores com a mesma semente geram as mesmas matrizes
+	pub fn with_seed(seed: u64) -> Self {
+		MatrixGenerator {
+			rng: StdRng::seed_from_u64(seed),
+		}
+	}
+	/// Gerador de numeros aleatorios usado internamente, para sortear outros valores na mesma sequencia
+	pub fn rng(&mut self) -> &mut StdRng {
+		&mut self.rng
+	}
+	pub fn uniform<M : Matrix>(&mut self, size: Pair, population: usize) -> M {
+		M::from_info(&self.uniform_info(size, population))
+	}
+	pub fn uniform_table(&mut self, size: Pair, population: usize) -> TableMatrix {
+		let mut data = vec![vec![0.0; size.1]; size.0];
+		for ((row, col), value) in self.uniform_info(size, population).values {
+			data[row][col] = value;
+		}
+		TableMatrix::from(data)
+	}
+	/// `population` valores em posiçoes distintas sorteadas, com valores uniformes em `[-10, 10)`
+	pub fn uniform_info(&mut self, size: Pair, population: usize) -> MatrixInfo {
+		let positions = self.positions(size, population);
+		let values = positions.into_iter()
+			.map(|pos| (pos, self.rng.random_range(-10.0..10.0)))
+			.collect();
+		MatrixInfo {
+			size,
+			values,
+		}
+	}
+	pub fn normal<M : Matrix>(&mut self, size: Pair, population: usize) -> M {
+		M::from_info(&self.normal_info(size, population))
+	}
+	/// Como `uniform_info`, mas com valores da distribuiçao normal de media 0 e a mesma variancia
+	/// da uniforme em `[-10, 10)`, desvio padrao `10 / sqrt(3)` (ver `gaussian_info`)
+	pub fn normal_info(&mut self, size: Pair, population: usize) -> MatrixInfo {
+		self.gaussian_info(size, population, 0.0, 10.0 / 3.0_f64.sqrt())
+	}
+	pub fn gaussian<M : Matrix>(&mut self, size: Pair, population: usize, mean: f64, std_dev: f64) -> M {
+		M::from_info(&self.gaussian_info(size, population, mean, std_dev))
+	}
+	/// `population` valores em posiçoes distintas sorteadas, com valores da distribuiçao normal de media `mean`
+	/// e desvio padrao `std_dev`, sorteados com `rand_distr::Normal`
+	/// Entra em panico se `std_dev` nao for finito.
+	pub fn gaussian_info(&mut self, size: Pair, population: usize, mean: f64, std_dev: f64) -> MatrixInfo {
+		let normal = Normal::new(mean, std_dev).expect("Invalid standard deviation for gaussian_info");
+		let positions = self.positions(size, population);
+		let values = positions.into_iter()
+			.map(|pos| (pos, normal.sample(&mut self.rng)))
+			.collect();
+		MatrixInfo {
+			size,
+			values,
+		}
+	}
+	pub fn symmetric<M : Matrix>(&mut self, n: usize, population: usize) -> M {
+		M::from_info(&self.symmetric_info(n, population))
+	}
+	/// Matriz simetrica `n x n` com `population` valores uniformes em `[-10, 10)` em posiçoes distintas do triangulo
+	/// superior (incluindo a diagonal), cada um espelhado na posiçao transposta.
+	/// A matriz tem entao ate `2 * population` valores; `population` é limitado a `n * (n + 1) / 2`.
+	pub fn symmetric_info(&mut self, n: usize, population: usize) -> MatrixInfo {
+		let total_elements = n * (n + 1) / 2;
+		let mut samples = rand::seq::index::sample(&mut self.rng, total_elements, population.min(total_elements)).into_vec();
+		samples.sort_unstable();
+		// o triangulo é numerado por linhas: a linha i comeca em `row_start` e tem as colunas i..n
+		let mut values = Vec::with_capacity(2 * samples.len());
+		let (mut row, mut row_start) = (0, 0);
+		for index in samples {
+			while index >= row_start + n - row {
+				row_start += n - row;
+				row += 1;
+			}
+			let col = row + index - row_start;
+			let value = self.rng.random_range(-10.0..10.0);
+			values.push(((row, col), value));
+			if row != col {
+				values.push(((col, row), value));
+			}
+		}
+		MatrixInfo {
+			size: (n, n),
+			values,
+		}
+	}
+	pub fn banded<M : Matrix>(&mut self, n: usize, bandwidth: usize) -> M {
+		M::from_info(&self.banded_info(n, bandwidth))
+	}
+	/// Matriz de banda `n x n`: todas as posiçoes com `|i - j| <= bandwidth` tem valores uniformes em `[-10, 10)`,
+	/// ou seja, a diagonal principal e as `bandwidth` diagonais de cada lado dela
+	pub fn banded_info(&mut self, n: usize, bandwidth: usize) -> MatrixInfo {
+		let values = (0..n)
+			.flat_map(|i| (i.saturating_sub(bandwidth)..(i + bandwidth + 1).min(n)).map(move |j| (i, j)))
+			.map(|pos| (pos, self.rng.random_range(-10.0..10.0)))
+			.collect();
+		MatrixInfo {
+			size: (n, n),
+			values,
+		}
+	}
+	/// Sorteia `population` posiçoes distintas da matriz, limitado ao numero de posiçoes
+	fn positions(&mut self, size: Pair, population: usize) -> Vec<Pair> {
+		let total_elements = size.0 * size.1;
+		let non_zero_elements = population.min(total_elements);
+
+		let samples = rand::seq::index::sample(&mut self.rng, total_elements, non_zero_elements);
+
+		samples.iter()
+			.map(|index| {
+				(index % size.0, index / size.0)
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn same_seed_generates_the_same_matrices() {
+		let (mut a, mut b) = (MatrixGenerator::with_seed(1061), MatrixGenerator::with_seed(1061));
+		assert_eq!(a.uniform_info((40, 30), 200), b.uniform_info((40, 30), 200));
+		assert_eq!(a.gaussian_info((40, 30), 200, 1.0, 2.0), b.gaussian_info((40, 30), 200, 1.0, 2.0));
+		assert_eq!(a.symmetric_info(30, 100), b.symmetric_info(30, 100));
+		assert_eq!(a.banded_info(30, 2), b.banded_info(30, 2));
+		assert_ne!(a.uniform_info((40, 30), 200), MatrixGenerator::with_seed(1062).uniform_info((40, 30), 200));
+	}
+
+	#[test]
+	fn gaussian_info_has_the_requested_mean_and_deviation() {
+		let (mean, std_dev) = (3.0, 0.5);
+		let info = MatrixGenerator::with_seed(1061).gaussian_info((300, 300), 50_000, mean, std_dev);
+		assert_eq!(info.values.len(), 50_000);
+		let n = info.values.len() as f64;
+		let sample_mean = info.values.iter().map(|(_, value)| value).sum::<f64>() / n;
+		let sample_var = info.values.iter().map(|(_, value)| (value - sample_mean).powi(2)).sum::<f64>() / (n - 1.0);
+		// o erro padrao da media é std_dev / sqrt(n) ~ 0.002
+		assert!((sample_mean - mean).abs() < 0.01, "{sample_mean}");
+		assert!((sample_var.sqrt() - std_dev).abs() < 0.01, "{}", sample_var.sqrt());
+	}
+
+	#[test]
+	#[should_panic(expected = "Invalid standard deviation for gaussian_info")]
+	fn gaussian_info_rejects_an_infinite_deviation() {
+		MatrixGenerator::with_seed(1061).gaussian_info((2, 2), 1, 0.0, f64::INFINITY);
+	}
+}
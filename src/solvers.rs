@@ -0,0 +1,167 @@
+pub(crate) mod vec_ops;
+
+use crate::{EPSILON, basic::{Matrix, MatrixError}};
+use vec_ops::{axpy, dot, norm};
+
+/// Resolve `A x = b` pelo metodo dos gradientes conjugados, para `A` simetrica definida positiva
+///
+/// Parte de `x = 0` e para quando o residuo relativo `||b - A x|| / ||b||` fica menor que `tol`.
+/// Cada iteraçao faz um produto matriz-vetor, em O(k + n), entao o metodo aproveita a esparsidade de `A`.
+/// A simetria e a definiçao positiva nao sao verificadas; sem elas o metodo em geral nao converge.
+/// Retorna `MatrixError::DimensionMismatch` se `A` nao for quadrada ou `b` nao tiver n elementos, e
+/// `MatrixError::SingularMatrix` se o residuo nao convergir em `max_iter` iteraçoes.
+pub fn conjugate_gradient(a: &impl Matrix, b: &[f64], max_iter: usize, tol: f64) -> Result<Vec<f64>, MatrixError> {
+	let n = a.size().0;
+	if !a.is_square() {
+		return Err(MatrixError::DimensionMismatch { expected: (n, n), got: a.size() });
+	}
+	if b.len() != n {
+		return Err(MatrixError::DimensionMismatch { expected: (n, 1), got: (b.len(), 1) });
+	}
+	let mut x = vec![0.0; n];
+	let b_norm = norm(b);
+	if b_norm == 0.0 {
+		return Ok(x);
+	}
+	let mut r = b.to_vec();
+	let mut p = r.clone();
+	let mut rr = dot(&r, &r);
+	for _ in 0..max_iter {
+		if rr.sqrt() / b_norm < tol {
+			return Ok(x);
+		}
+		let ap = a.mul_vec(&p);
+		let pap = dot(&p, &ap);
+		if pap <= 0.0 {
+			return Err(MatrixError::SingularMatrix);
+		}
+		let alpha = rr / pap;
+		axpy(alpha, &p, &mut x);
+		axpy(-alpha, &ap, &mut r);
+		let rr_next = dot(&r, &r);
+		let beta = rr_next / rr;
+		for (pi, ri) in p.iter_mut().zip(&r) {
+			*pi = ri + beta * *pi;
+		}
+		rr = rr_next;
+	}
+	if rr.sqrt() / b_norm < tol {
+		Ok(x)
+	} else {
+		Err(MatrixError::SingularMatrix)
+	}
+}
+
+/// Resolve `A x = b` pelo metodo de Jacobi, para `A` diagonal dominante
+///
+/// Parte de `x = 0` e em cada iteraçao calcula `x_novo[i] = (b[i] - soma_{j != i} A[i][j] * x[j]) / A[i][i]`,
+/// com a soma fora da diagonal tirada de um produto `A * x` (em O(k + n)) menos `A[i][i] * x[i]`.
+/// Para quando a maior diferença `|x_novo[i] - x[i]|` fica menor que `tol`.
+/// A dominancia diagonal nao é verificada; sem ela o metodo pode nao convergir.
+/// Retorna `MatrixError::DimensionMismatch` se `A` nao for quadrada ou `b` nao tiver n elementos, e
+/// `MatrixError::SingularMatrix` se algum `|A[i][i]|` for menor que `EPSILON` ou o metodo nao convergir em `max_iter` iteraçoes.
+pub fn jacobi(a: &impl Matrix, b: &[f64], max_iter: usize, tol: f64) -> Result<Vec<f64>, MatrixError> {
+	let n = a.size().0;
+	if !a.is_square() {
+		return Err(MatrixError::DimensionMismatch { expected: (n, n), got: a.size() });
+	}
+	if b.len() != n {
+		return Err(MatrixError::DimensionMismatch { expected: (n, 1), got: (b.len(), 1) });
+	}
+	let diagonal = a.diagonal();
+	if diagonal.iter().any(|d| d.abs() < EPSILON) {
+		return Err(MatrixError::SingularMatrix);
+	}
+	let mut x = vec![0.0; n];
+	for _ in 0..max_iter {
+		let ax = a.mul_vec(&x);
+		let mut max_diff: f64 = 0.0;
+		for i in 0..n {
+			let off_diagonal = ax[i] - diagonal[i] * x[i];
+			let xi = (b[i] - off_diagonal) / diagonal[i];
+			max_diff = max_diff.max((xi - x[i]).abs());
+			x[i] = xi;
+		}
+		if max_diff < tol {
+			return Ok(x);
+		}
+	}
+	Err(MatrixError::SingularMatrix)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{TableMatrix, test_utils::*};
+
+	fn assert_cg_solves<M: Matrix>() {
+		let n = 100;
+		let a: M = tridiagonal(n, 4.0, -1.0);
+		let b: Vec<f64> = (0..n).map(|i| (i as f64 * 0.37).sin() + 1.0).collect();
+		let tol = 1e-10;
+		let x = conjugate_gradient(&a, &b, n, tol).unwrap();
+		assert!(residual(&a, &x, &b) / norm(&b) < tol);
+	}
+
+	matrix_tests!(conjugate_gradient_solves_tridiagonal_spd_system => assert_cg_solves);
+
+	#[test]
+	fn conjugate_gradient_errors() {
+		let a: TableMatrix = tridiagonal(100, 4.0, -1.0);
+		let b = vec![1.0; 100];
+		assert_eq!(conjugate_gradient(&a, &b, 2, 1e-12), Err(MatrixError::SingularMatrix));
+		assert_eq!(conjugate_gradient(&a, &b[..99], 100, 1e-12), Err(MatrixError::DimensionMismatch { expected: (100, 1), got: (99, 1) }));
+		assert_eq!(conjugate_gradient(&TableMatrix::new((2, 3)), &b[..2], 10, 1e-12), Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+		assert_eq!(conjugate_gradient(&a, &[0.0; 100], 1, 1e-12), Ok(vec![0.0; 100]));
+	}
+
+	#[test]
+	fn vector_operations() {
+		let mut y = vec![1.0, 2.0, 3.0];
+		axpy(2.0, &[1.0, 0.0, -1.0], &mut y);
+		assert_eq!(y, vec![3.0, 2.0, 1.0]);
+		assert_eq!(dot(&y, &[1.0, 1.0, 1.0]), 6.0);
+		assert_eq!(norm(&[3.0, 4.0]), 5.0);
+	}
+
+	fn assert_jacobi_solves<M: Matrix>() {
+		let n = 100;
+		let a: M = tridiagonal(n, 4.0, -1.0);
+		let expected: Vec<f64> = (0..n).map(|i| (i as f64 * 0.21).cos()).collect();
+		let b = a.mul_vec(&expected);
+		let x = jacobi(&a, &b, n - 1, 1e-12).unwrap();
+		for (xi, ei) in x.iter().zip(&expected) {
+			assert!((xi - ei).abs() < 1e-10, "{xi} != {ei}");
+		}
+		assert_eq!(jacobi(&a, &b, 3, 1e-12), Err(MatrixError::SingularMatrix));
+	}
+
+	matrix_tests!(jacobi_converges_in_fewer_than_n_iterations_on_a_dominant_system => assert_jacobi_solves);
+
+	fn assert_jacobi_solves_poisson<M: Matrix>() {
+		let n = 10;
+		let a: M = tridiagonal(n, 2.0, -1.0);
+		let h = 1.0 / (n + 1) as f64;
+		let b = vec![h * h; n];
+		let x = jacobi(&a, &b, 5000, 1e-13).unwrap();
+		for (i, xi) in x.iter().enumerate() {
+			let t = (i + 1) as f64 * h;
+			assert!((xi - t * (1.0 - t) / 2.0).abs() < 1e-10, "x[{i}] = {xi}");
+		}
+	}
+
+	matrix_tests!(jacobi_solves_the_1d_poisson_equation => assert_jacobi_solves_poisson);
+
+	#[test]
+	fn jacobi_rejects_zero_diagonal_and_mismatched_sizes() {
+		let mut a: TableMatrix = tridiagonal(3, 4.0, -1.0);
+		a.set((1, 1), 0.0);
+		assert_eq!(jacobi(&a, &[1.0; 3], 10, 1e-8), Err(MatrixError::SingularMatrix));
+		let a: TableMatrix = tridiagonal(3, 4.0, -1.0);
+		assert_eq!(jacobi(&a, &[1.0; 2], 10, 1e-8), Err(MatrixError::DimensionMismatch { expected: (3, 1), got: (2, 1) }));
+		assert_eq!(
+			jacobi(&TableMatrix::new((2, 3)), &[1.0; 2], 10, 1e-8),
+			Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) })
+		);
+	}
+}
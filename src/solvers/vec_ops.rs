@@ -0,0 +1,16 @@
+/// Produto interno `<x, y>`
+pub(crate) fn dot(x: &[f64], y: &[f64]) -> f64 {
+	x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+/// `y += alpha * x`
+pub(crate) fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+	for (yi, xi) in y.iter_mut().zip(x) {
+		*yi += alpha * xi;
+	}
+}
+
+/// Norma euclidiana `||x||_2`
+pub(crate) fn norm(x: &[f64]) -> f64 {
+	dot(x, x).sqrt()
+}
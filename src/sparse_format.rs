@@ -0,0 +1,248 @@
+use std::str::FromStr;
+
+use crate::basic::{MatrixError, MatrixInfo, Pair};
+
+/// Formatos de texto suportados por `MatrixInfo::to_format_string` e `MatrixInfo::from_format_string`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparseFormat {
+	/// Coordenadas: uma linha `linhas colunas nnz` seguida de uma linha `linha coluna valor` por elemento
+	COO,
+	/// Compressed Sparse Row: uma linha `linhas colunas` seguida das linhas `row_ptr`, `col_ind` e `values`
+	CSR,
+	/// Grade densa: uma linha de texto por linha da matriz, com todos os valores separados por espaço
+	Dense,
+	/// Formato Matrix Market (`.mtx`) coordinate real general, com indices a partir de 1
+	MatrixMarket,
+}
+
+fn parse_error(line: usize, message: &str) -> MatrixError {
+	MatrixError::Parse(format!("linha {}: {}", line + 1, message))
+}
+
+fn parse_field<T: FromStr>(line: usize, field: &str) -> Result<T, MatrixError> {
+	field.parse().map_err(|_| parse_error(line, &format!("valor invalido `{}`", field)))
+}
+
+/// Le todos os campos de uma linha, separados por espaço
+fn parse_fields<T: FromStr>(line: usize, text: &str) -> Result<Vec<T>, MatrixError> {
+	text.split_whitespace().map(|field| parse_field(line, field)).collect()
+}
+
+/// Le uma linha com exatamente `N` campos
+fn parse_exact<T: FromStr + Copy + Default, const N: usize>(line: usize, text: &str) -> Result<[T; N], MatrixError> {
+	let fields = parse_fields::<T>(line, text)?;
+	if fields.len() != N {
+		return Err(parse_error(line, &format!("esperados {} campos, encontrados {}", N, fields.len())));
+	}
+	let mut result = [T::default(); N];
+	result.copy_from_slice(&fields);
+	Ok(result)
+}
+
+/// Le uma linha `linha coluna valor`, subtraindo `base` dos indices
+fn parse_entry(line: usize, text: &str, base: usize) -> Result<(Pair, f64), MatrixError> {
+	let fields: Vec<&str> = text.split_whitespace().collect();
+	let [row, col, value] = fields[..] else {
+		return Err(parse_error(line, &format!("esperados 3 campos, encontrados {}", fields.len())));
+	};
+	let (row, col, value): (usize, usize, f64) = (parse_field(line, row)?, parse_field(line, col)?, parse_field(line, value)?);
+	if row < base || col < base {
+		return Err(parse_error(line, "indice menor que a base"));
+	}
+	Ok(((row - base, col - base), value))
+}
+
+fn join<T: ToString>(values: impl Iterator<Item = T>) -> String {
+	values.map(|value| value.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+impl MatrixInfo {
+	/// Escreve a matriz como texto no formato escolhido
+	pub fn to_format_string(&self, format: SparseFormat) -> String {
+		let mut sorted = self.values.clone();
+		sorted.sort_by_key(|(pos, _)| *pos);
+		let mut out = String::new();
+		match format {
+			SparseFormat::COO => {
+				out.push_str(&format!("{} {} {}\n", self.size.0, self.size.1, sorted.len()));
+				for ((row, col), value) in sorted.iter() {
+					out.push_str(&format!("{} {} {}\n", row, col, value));
+				}
+			}
+			SparseFormat::CSR => {
+				let mut row_ptr = vec![0; self.size.0 + 1];
+				for ((row, _), _) in sorted.iter() {
+					row_ptr[row + 1] += 1;
+				}
+				for i in 0..self.size.0 {
+					row_ptr[i + 1] += row_ptr[i];
+				}
+				out.push_str(&format!("{} {}\n", self.size.0, self.size.1));
+				out.push_str(&format!("{}\n", join(row_ptr.iter())));
+				out.push_str(&format!("{}\n", join(sorted.iter().map(|((_, col), _)| col))));
+				out.push_str(&format!("{}\n", join(sorted.iter().map(|(_, value)| value))));
+			}
+			SparseFormat::Dense => {
+				let mut grid = vec![vec![0.0; self.size.1]; self.size.0];
+				for ((row, col), value) in sorted.iter() {
+					grid[*row][*col] = *value;
+				}
+				for row in grid.iter() {
+					out.push_str(&format!("{}\n", join(row.iter())));
+				}
+			}
+			SparseFormat::MatrixMarket => {
+				out.push_str("%%MatrixMarket matrix coordinate real general\n");
+				out.push_str(&format!("{} {} {}\n", self.size.0, self.size.1, sorted.len()));
+				for ((row, col), value) in sorted.iter() {
+					out.push_str(&format!("{} {} {}\n", row + 1, col + 1, value));
+				}
+			}
+		}
+		out
+	}
+
+	/// Le uma matriz escrita no formato escolhido
+	/// Retorna `MatrixError::Parse` se o texto estiver mal formado e `MatrixError::OutOfBounds`
+	/// se algum elemento estiver fora das dimensoes declaradas.
+	pub fn from_format_string(s: &str, format: SparseFormat) -> Result<MatrixInfo, MatrixError> {
+		let info = match format {
+			SparseFormat::COO => {
+				let mut lines = s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+				let (i, header) = lines.next().ok_or_else(|| parse_error(0, "cabeçalho ausente"))?;
+				let [rows, cols, nnz] = parse_exact::<usize, 3>(i, header)?;
+				let values = lines
+					.map(|(i, line)| parse_entry(i, line, 0))
+					.collect::<Result<Vec<_>, _>>()?;
+				if values.len() != nnz {
+					return Err(parse_error(i, &format!("esperados {} elementos, encontrados {}", nnz, values.len())));
+				}
+				MatrixInfo { size: (rows, cols), values }
+			}
+			SparseFormat::CSR => {
+				let lines: Vec<&str> = s.lines().collect();
+				if lines.len() < 4 {
+					return Err(parse_error(lines.len(), "esperadas 4 linhas"));
+				}
+				let [rows, cols] = parse_exact::<usize, 2>(0, lines[0])?;
+				let row_ptr = parse_fields::<usize>(1, lines[1])?;
+				let col_ind = parse_fields::<usize>(2, lines[2])?;
+				let data = parse_fields::<f64>(3, lines[3])?;
+				if row_ptr.len() != rows + 1 || row_ptr.windows(2).any(|w| w[0] > w[1]) {
+					return Err(parse_error(1, "row_ptr invalido"));
+				}
+				if col_ind.len() != data.len() || row_ptr[rows] != data.len() {
+					return Err(parse_error(2, "col_ind e values devem ter row_ptr[linhas] elementos"));
+				}
+				let mut values = Vec::with_capacity(data.len());
+				for row in 0..rows {
+					for k in row_ptr[row]..row_ptr[row + 1] {
+						values.push(((row, col_ind[k]), data[k]));
+					}
+				}
+				MatrixInfo { size: (rows, cols), values }
+			}
+			SparseFormat::Dense => {
+				let mut values = Vec::new();
+				let mut size = (0, 0);
+				for (i, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+					let row = parse_fields::<f64>(i, line)?;
+					if size.0 == 0 {
+						size.1 = row.len();
+					} else if row.len() != size.1 {
+						return Err(parse_error(i, &format!("esperadas {} colunas, encontradas {}", size.1, row.len())));
+					}
+					for (col, value) in row.into_iter().enumerate() {
+						if value != 0.0 {
+							values.push(((size.0, col), value));
+						}
+					}
+					size.0 += 1;
+				}
+				MatrixInfo { size, values }
+			}
+			SparseFormat::MatrixMarket => {
+				let mut lines = s.lines().enumerate();
+				let (_, banner) = lines.next().ok_or_else(|| parse_error(0, "cabeçalho ausente"))?;
+				let banner = banner.to_lowercase();
+				// apenas matrizes reais ou inteiras sem simetria: os outros tipos guardam valores ou metade da matriz de outra forma
+				match banner.split_whitespace().collect::<Vec<_>>()[..] {
+					["%%matrixmarket", "matrix", "coordinate", "real" | "integer", "general"] => {}
+					["%%matrixmarket", "matrix", "coordinate", ..] => {
+						return Err(parse_error(0, "apenas `real general` e `integer general` sao suportados"));
+					}
+					_ => return Err(parse_error(0, "esperado `%%MatrixMarket matrix coordinate`")),
+				}
+				let mut lines = lines.filter(|(_, line)| !line.starts_with('%') && !line.trim().is_empty());
+				let (i, header) = lines.next().ok_or_else(|| parse_error(1, "linha de tamanho ausente"))?;
+				let [rows, cols, nnz] = parse_exact::<usize, 3>(i, header)?;
+				let values = lines
+					.map(|(i, line)| parse_entry(i, line, 1))
+					.collect::<Result<Vec<_>, _>>()?;
+				if values.len() != nnz {
+					return Err(parse_error(i, &format!("esperados {} elementos, encontrados {}", nnz, values.len())));
+				}
+				MatrixInfo { size: (rows, cols), values }
+			}
+		};
+		if let Some((pos, _)) = info.values.iter().find(|(pos, _)| pos.0 >= info.size.0 || pos.1 >= info.size.1) {
+			return Err(MatrixError::OutOfBounds { pos: *pos, size: info.size });
+		}
+		Ok(info)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::{sample_info, sorted};
+
+	const FORMATS: [SparseFormat; 4] = [SparseFormat::COO, SparseFormat::CSR, SparseFormat::Dense, SparseFormat::MatrixMarket];
+
+	#[test]
+	fn every_format_round_trips() {
+		for format in FORMATS {
+			let text = sample_info().to_format_string(format);
+			let parsed = MatrixInfo::from_format_string(&text, format).unwrap();
+			assert_eq!(parsed, sorted(sample_info()), "{format:?}");
+		}
+	}
+
+	#[test]
+	fn text_layout_of_each_format() {
+		assert_eq!(sample_info().to_format_string(SparseFormat::COO), "3 4 4\n0 1 2.5\n0 3 7\n2 0 0.001\n2 3 -0.125\n");
+		assert_eq!(sample_info().to_format_string(SparseFormat::CSR), "3 4\n0 2 2 4\n1 3 0 3\n2.5 7 0.001 -0.125\n");
+		assert_eq!(sample_info().to_format_string(SparseFormat::Dense), "0 2.5 0 7\n0 0 0 0\n0.001 0 0 -0.125\n");
+		assert!(sample_info().to_format_string(SparseFormat::MatrixMarket).starts_with("%%MatrixMarket matrix coordinate real general\n3 4 4\n1 2 2.5\n"));
+	}
+
+	#[test]
+	fn parse_errors() {
+		assert_eq!(MatrixInfo::from_format_string("2 2 1\n2 0 1\n", SparseFormat::COO).err(), Some(MatrixError::OutOfBounds { pos: (2, 0), size: (2, 2) }));
+		assert!(matches!(MatrixInfo::from_format_string("2 2 2\n0 0 1\n", SparseFormat::COO), Err(MatrixError::Parse(_))));
+		assert!(matches!(MatrixInfo::from_format_string("2 2\n0 2 1\n0\n1\n", SparseFormat::CSR), Err(MatrixError::Parse(_))));
+		assert!(matches!(MatrixInfo::from_format_string("1 2\n3\n", SparseFormat::Dense), Err(MatrixError::Parse(_))));
+		assert!(matches!(MatrixInfo::from_format_string("2 2 1\n1 1 1\n", SparseFormat::MatrixMarket), Err(MatrixError::Parse(_))));
+		assert!(matches!(MatrixInfo::from_format_string("%%MatrixMarket matrix coordinate real general\n2 2 1\n0 1 1\n", SparseFormat::MatrixMarket), Err(MatrixError::Parse(_))));
+	}
+
+	#[test]
+	fn matrix_market_accepts_only_real_or_integer_general() {
+		let body = "2 2 1\n1 2 3\n";
+		for banner in ["%%MatrixMarket matrix coordinate real general", "%%MatrixMarket matrix coordinate INTEGER General"] {
+			let info = MatrixInfo::from_format_string(&format!("{banner}\n{body}"), SparseFormat::MatrixMarket).unwrap();
+			assert_eq!(info.values, vec![((0, 1), 3.0)]);
+		}
+		for banner in [
+			"%%MatrixMarket matrix coordinate real symmetric",
+			"%%MatrixMarket matrix coordinate complex general",
+			"%%MatrixMarket matrix coordinate pattern general",
+			"%%MatrixMarket matrix coordinate integer skew-symmetric",
+			"%%MatrixMarket matrix coordinate real",
+			"%%MatrixMarket matrix array real general",
+		] {
+			let result = MatrixInfo::from_format_string(&format!("{banner}\n{body}"), SparseFormat::MatrixMarket);
+			assert!(matches!(result, Err(MatrixError::Parse(_))), "{banner}: {result:?}");
+		}
+	}
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::basic::Scalar;
+
+/// Vetor esparso, guardando em um `HashMap` apenas as posiçoes com valor diferente de `S::default()` (o zero do tipo)
+/// O tamanho do vetor nao é guardado: as posiçoes sem valor valem zero, e `to_dense` recebe o tamanho desejado.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SparseVec<S: Scalar = f64> {
+	values: HashMap<usize, S>,
+}
+
+impl<S: Scalar> SparseVec<S> {
+	/// Cria um vetor sem valores definidos
+	pub fn new() -> Self {
+		SparseVec { values: HashMap::new() }
+	}
+
+	/// Valor na posiçao `i`, ou `S::default()` se nao houver valor definido
+	pub fn get(&self, i: usize) -> S {
+		self.values.get(&i).copied().unwrap_or_default()
+	}
+
+	/// Define o valor na posiçao `i`, removendo a posiçao se o valor for `S::default()`
+	pub fn set(&mut self, i: usize, value: S) {
+		if value == S::default() {
+			self.values.remove(&i);
+		} else {
+			self.values.insert(i, value);
+		}
+	}
+
+	/// Soma `value` na posiçao `i`, removendo a posiçao se a soma der `S::default()`
+	pub fn add_at(&mut self, i: usize, value: S) {
+		self.set(i, self.get(i) + value);
+	}
+
+	/// Numero de posiçoes com valor definido
+	pub fn nnz(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Itera sobre as posiçoes definidas como pares `(indice, valor)`, sem ordem definida
+	pub fn iter(&self) -> impl Iterator<Item = (usize, S)> + '_ {
+		self.values.iter().map(|(i, value)| (*i, *value))
+	}
+
+	/// Produto interno `<self, other>`, percorrendo o vetor com menos valores e consultando o outro
+	/// Complexidade de tempo: O(min(ka, kb))
+	pub fn dot(&self, other: &SparseVec<S>) -> S {
+		let (small, large) = if self.nnz() <= other.nnz() { (self, other) } else { (other, self) };
+		small.iter().fold(S::default(), |sum, (i, value)| sum + value * large.get(i))
+	}
+
+	/// Copia o vetor para um vetor denso de tamanho `len`
+	/// Entra em panico se alguma posiçao definida for maior ou igual a `len`.
+	pub fn to_dense(&self, len: usize) -> Vec<S> {
+		let mut dense = vec![S::default(); len];
+		for (i, value) in self.iter() {
+			assert!(i < len, "SparseVec index out of bounds for to_dense");
+			dense[i] = value;
+		}
+		dense
+	}
+}
+
+impl SparseVec<f64> {
+	/// Le um vetor denso, guardando apenas as posiçoes nao nulas
+	pub fn from_dense(v: &[f64]) -> Self {
+		SparseVec {
+			values: v.iter().copied().enumerate().filter(|(_, value)| *value != 0.0).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{CscMatrix, CsrMatrix, HashMapMatrix, Matrix, test_utils::*};
+	use rand::{Rng, SeedableRng, rngs::StdRng};
+
+	/// Vetor com `k` posiçoes aleatorias em `0..len` e valores uniformes em `[-1, 1)`
+	fn random_vec(len: usize, k: usize, seed: u64) -> SparseVec {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut v = SparseVec::new();
+		for _ in 0..k {
+			v.set(rng.random_range(0..len), rng.random_range(-1.0..1.0));
+		}
+		v
+	}
+
+	#[test]
+	fn set_to_zero_removes_the_entry() {
+		let mut v = SparseVec::new();
+		v.set(3, 2.0);
+		v.set(7, -1.0);
+		assert_eq!(v.nnz(), 2);
+		v.set(3, 0.0);
+		assert_eq!(v.nnz(), 1);
+		assert_eq!(v.get(3), 0.0);
+		assert_eq!(v.iter().collect::<Vec<_>>(), vec![(7, -1.0)]);
+		v.add_at(7, 1.0);
+		assert_eq!(v, SparseVec::new());
+	}
+
+	#[test]
+	fn dot_is_symmetric() {
+		let (v, w) = (random_vec(100, 5, 1052), random_vec(100, 40, 1053));
+		assert_eq!(v.dot(&w), w.dot(&v));
+		let dense: f64 = v.to_dense(100).iter().zip(w.to_dense(100)).map(|(a, b)| a * b).sum();
+		assert!((v.dot(&w) - dense).abs() < 1e-12);
+	}
+
+	#[test]
+	#[should_panic(expected = "SparseVec index out of bounds for to_dense")]
+	fn to_dense_panics_on_an_index_out_of_range() {
+		let mut v = SparseVec::new();
+		v.set(5, 1.0);
+		v.to_dense(5);
+	}
+
+	fn assert_mul_sparse_vec<M: Matrix>() {
+		let (m, n) = (30, 40);
+		let a: M = random((m, n), 1052);
+		for (k, seed) in [(0, 1), (1, 2), (4, 3), (n, 4)] {
+			let v = random_vec(n, k, seed);
+			let expected = a.mul_vec(&v.to_dense(n));
+			let product = a.mul_sparse_vec(&v).to_dense(m);
+			for (p, q) in product.iter().zip(&expected) {
+				assert!((p - q).abs() < 1e-12, "{p} != {q}");
+			}
+		}
+	}
+
+	matrix_tests!(mul_sparse_vec_matches_mul_vec => assert_mul_sparse_vec);
+
+	#[test]
+	fn compressed_mul_sparse_vec_matches_mul_vec() {
+		let a: HashMapMatrix = random((30, 40), 1052);
+		let info = a.to_info();
+		let v = random_vec(40, 4, 1052);
+		let expected = a.mul_vec(&v.to_dense(40));
+		for product in [CsrMatrix::from_info(&info).mul_sparse_vec(&v), CscMatrix::from_info(&info).mul_sparse_vec(&v)] {
+			for (p, q) in product.to_dense(30).iter().zip(&expected) {
+				assert!((p - q).abs() < 1e-12, "{p} != {q}");
+			}
+		}
+	}
+}
@@ -0,0 +1,230 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::{EPSILON, basic::{Matrix, MatrixError, MatrixInfo, Pair}, map_matrix::{Map, MapMatrix, MapVec}};
+
+/// Matriz simetrica esparsa que guarda apenas o triangulo superior (`linha <= coluna`) em uma `MapMatrix`
+///
+/// `set` e `get` em `(i, j)` usam a posiçao `(min(i, j), max(i, j))`, entao `get((i, j)) == get((j, i))` sempre vale
+/// e cada valor fora da diagonal ocupa metade da memoria de uma `MapMatrix` completa. Para o resto da biblioteca
+/// a matriz é completa: `iter_nnz` e `to_info` devolvem os dois lados da diagonal.
+/// A matriz é sempre quadrada: `new` entra em panico com dimensoes diferentes, assim como as operaçoes padrao
+/// do `Matrix` que criam matrizes nao quadradas (por exemplo `hstack`).
+pub struct SymmetricMapMatrix<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> {
+	/// Triangulo superior da matriz, incluindo a diagonal
+	upper: MapMatrix<T, LM>,
+}
+
+impl<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> SymmetricMapMatrix<T, LM> {
+	/// Posiçao do triangulo superior onde fica o valor de `(i, j)`
+	fn upper_pos(pos: Pair) -> Pair {
+		(pos.0.min(pos.1), pos.0.max(pos.1))
+	}
+
+	/// Copia a matriz para uma `MapMatrix` completa, com os dois lados da diagonal
+	/// Complexidade de tempo: O(T::full_iter(n) + 2n * T::set_or_insert(n))
+	pub fn to_full(&self) -> MapMatrix<T, LM> {
+		let mut full = MapMatrix::new(self.upper.size());
+		for (pos, value) in self.iter_nnz() {
+			full.set(pos, value);
+		}
+		full
+	}
+
+	/// Estimativa dos bytes usados pela matriz, os da `MapMatrix` com o triangulo superior
+	pub fn memory_estimate(&self) -> usize {
+		self.upper.memory_estimate()
+	}
+}
+
+impl<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> Matrix for SymmetricMapMatrix<T, LM> {
+	/// Entra em panico se a matriz nao for quadrada
+	fn new(size: Pair) -> Self {
+		assert_eq!(size.0, size.1, "SymmetricMapMatrix must be square");
+		SymmetricMapMatrix { upper: MapMatrix::new(size) }
+	}
+
+	fn size(&self) -> Pair {
+		self.upper.size()
+	}
+
+	/// Define o valor de `(i, j)` e, portanto, tambem o de `(j, i)`
+	fn set(&mut self, pos: Pair, value: f64) {
+		self.upper.set(Self::upper_pos(pos), value);
+	}
+
+	fn get(&self, pos: Pair) -> f64 {
+		self.upper.get(Self::upper_pos(pos))
+	}
+
+	/// A matriz é igual a sua transposta
+	/// Complexidade de tempo: O(1)
+	fn transposed(self) -> Self {
+		self
+	}
+
+	/// A soma de matrizes simetricas é simetrica: soma apenas os triangulos superiores
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		Ok(SymmetricMapMatrix { upper: MapMatrix::try_add(&a.upper, &b.upper)? })
+	}
+
+	/// Soma os triangulos superiores; o padrao, que percorre `iter_nnz` de `b`, somaria duas vezes os valores fora da diagonal
+	fn add_assign(a: &mut Self, b: &Self) {
+		Matrix::add_assign(&mut a.upper, &b.upper);
+	}
+
+	/// Multiplica as matrizes completas e guarda o triangulo superior do produto
+	/// O produto de matrizes simetricas so é simetrico se elas comutarem (`AB = BA`);
+	/// entra em panico se o produto nao for simetrico (a menos de `EPSILON`), em vez de descartar o triangulo inferior.
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		let product = MapMatrix::try_mul(&a.to_full(), &b.to_full())?;
+		assert!(product.is_symmetric(EPSILON), "product of symmetric matrices is not symmetric");
+		let mut c = SymmetricMapMatrix::new(product.size());
+		for ((i, j), value) in product.iter_nnz() {
+			if i <= j {
+				c.upper.set((i, j), value);
+			}
+		}
+		Ok(c)
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		SymmetricMapMatrix { upper: MapMatrix::muls(&a.upper, scalar) }
+	}
+
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		MapMatrix::muls_assign(&mut a.upper, scalar);
+	}
+
+	/// Cada valor é guardado no triangulo superior; se `(i, j)` e `(j, i)` aparecerem com valores diferentes, vale o ultimo
+	/// Entra em panico se a matriz nao for quadrada
+	fn from_info(info: &MatrixInfo) -> Self {
+		let mut m = SymmetricMapMatrix::new(info.size);
+		for (pos, value) in info.values.iter() {
+			m.set(*pos, *value);
+		}
+		m
+	}
+
+	/// Valores dos dois lados da diagonal, como os de `iter_nnz`
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size(),
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Percorre o triangulo superior, devolvendo `(i, j)` e `(j, i)` para os valores fora da diagonal
+	/// e `(i, i)` uma unica vez para os da diagonal
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new(self.upper.iter_nnz().flat_map(|((i, j), value)| {
+			let mirrored = (i != j).then_some(((j, i), value));
+			std::iter::once(((i, j), value)).chain(mirrored)
+		}))
+	}
+
+	/// Sempre simetrica, por construçao
+	fn is_symmetric(&self, _eps: f64) -> bool {
+		true
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> AddAssign<&SymmetricMapMatrix<T, LM>> for SymmetricMapMatrix<T, LM> {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>> MulAssign<f64> for SymmetricMapMatrix<T, LM> {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{HashMapMatrix, SymmetricHashMapMatrix, SymmetricTreeMatrix};
+	use rand::{Rng, SeedableRng, rngs::StdRng};
+
+	fn random_symmetric<M: Matrix>(n: usize, seed: u64) -> M {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut a = M::new((n, n));
+		for _ in 0..3 * n {
+			a.set((rng.random_range(0..n), rng.random_range(0..n)), rng.random_range(1.0..2.0));
+		}
+		a
+	}
+
+	fn assert_mirrored<T: Map<Pair, f64>, LM: MapVec<usize, (Pair, f64)>>(a: &SymmetricMapMatrix<T, LM>) {
+		let n = a.size().0;
+		for i in 0..n {
+			for j in 0..n {
+				assert_eq!(a.get((i, j)), a.get((j, i)));
+			}
+		}
+		let mut positions: Vec<Pair> = a.iter_nnz().map(|(pos, _)| pos).collect();
+		let total = positions.len();
+		positions.sort();
+		positions.dedup();
+		assert_eq!(positions.len(), total);
+		let diagonal = positions.iter().filter(|(i, j)| i == j).count();
+		assert_eq!(total, 2 * a.upper.nnz() - diagonal);
+		assert!(a.to_full().is_symmetric(EPSILON));
+	}
+
+	#[test]
+	fn get_is_symmetric_and_iter_nnz_yields_both_sides() {
+		assert_mirrored(&random_symmetric::<SymmetricHashMapMatrix>(20, 1046));
+		assert_mirrored(&random_symmetric::<SymmetricTreeMatrix>(20, 1046));
+	}
+
+	#[test]
+	fn set_writes_both_sides() {
+		let mut a = SymmetricTreeMatrix::new((3, 3));
+		a.set((2, 0), 5.0);
+		a.set((0, 2), 6.0);
+		assert_eq!((a.get((2, 0)), a.get((0, 2))), (6.0, 6.0));
+		assert_eq!(a.upper.nnz(), 1);
+	}
+
+	#[test]
+	fn add_and_mul_match_the_full_matrices() {
+		let a: SymmetricHashMapMatrix = random_symmetric(15, 1);
+		let b: SymmetricHashMapMatrix = random_symmetric(15, 2);
+		let sum = SymmetricHashMapMatrix::add(&a, &b);
+		assert_eq!(sum.to_full(), HashMapMatrix::add(&a.to_full(), &b.to_full()));
+		let mut acc = SymmetricHashMapMatrix::new((15, 15));
+		acc += &a;
+		acc += &b;
+		assert_eq!(acc.to_full(), sum.to_full());
+
+		// A e A^2 comutam, entao o produto é simetrico
+		let square = SymmetricHashMapMatrix::mul(&a, &a);
+		let cube = SymmetricHashMapMatrix::mul(&a, &square);
+		assert_eq!(cube.to_full(), HashMapMatrix::mul(&a.to_full(), &square.to_full()));
+	}
+
+	#[test]
+	#[should_panic(expected = "product of symmetric matrices is not symmetric")]
+	fn mul_of_non_commuting_matrices_panics() {
+		let a: SymmetricHashMapMatrix = random_symmetric(6, 3);
+		let b: SymmetricHashMapMatrix = random_symmetric(6, 4);
+		SymmetricHashMapMatrix::mul(&a, &b);
+	}
+
+	#[test]
+	fn stores_about_half_of_the_values() {
+		let a: SymmetricHashMapMatrix = random_symmetric(200, 5);
+		let full = a.to_full();
+		assert!(a.upper.nnz() * 2 <= full.nnz() + 200);
+		assert!(a.memory_estimate() < full.memory_estimate() * 6 / 10);
+	}
+
+	#[test]
+	#[should_panic(expected = "SymmetricMapMatrix must be square")]
+	fn rectangular_matrix_panics() {
+		SymmetricTreeMatrix::new((2, 3));
+	}
+}
@@ -1,99 +1,1000 @@
-use crate::{basic::{Matrix, MatrixInfo, Pair}};
+use std::{fmt, ops::{AddAssign, Index, IndexMut, MulAssign, Range}};
 
-#[derive(Clone, Debug)]
+use crate::{EPSILON, basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_mul_sizes, fmt_grid}, factorization};
+
+/// Matriz densa guardada em um unico vetor, linha por linha: `A[i][j] = data[i * cols + j]`
+#[derive(Clone)]
 pub struct TableMatrix {
 	pub size: Pair,
-	pub data: Vec<Vec<f64>>,
+	pub data: Vec<f64>,
+}
+
+/// Ordem dos elementos de uma matriz guardada em um vetor continuo
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixLayout {
+	/// Linha por linha: `data[i * cols + j] = A[i][j]`
+	RowMajor,
+	/// Coluna por coluna, como no Fortran e no LAPACK: `data[j * rows + i] = A[i][j]`
+	ColMajor,
 }
 
 impl TableMatrix {
 	fn zero_like(&self) -> Self {
 		TableMatrix::new(self.size)
 	}
+
+	/// Indice da posiçao `(i, j)` em `data`
+	/// Entra em panico se a posiçao estiver fora da matriz: sem a verificaçao, uma coluna grande demais
+	/// apontaria para uma posiçao da linha seguinte.
+	fn index_of(&self, pos: Pair) -> usize {
+		assert!(pos.0 < self.size.0 && pos.1 < self.size.1, "position out of bounds");
+		pos.0 * self.size.1 + pos.1
+	}
+
+	/// Valores da linha `i`
+	fn row(&self, i: usize) -> &[f64] {
+		&self.data[i * self.size.1..(i + 1) * self.size.1]
+	}
+
+	/// Valores da linha `i`, mutaveis
+	fn row_mut(&mut self, i: usize) -> &mut [f64] {
+		let cols = self.size.1;
+		&mut self.data[i * cols..(i + 1) * cols]
+	}
+
+	/// Itera sobre as linhas da matriz, em ordem
+	fn rows(&self) -> impl Iterator<Item = &[f64]> {
+		(0..self.size.0).map(|i| self.row(i))
+	}
+
+	/// Ponteiro para o primeiro elemento de `data`, para passar a bibliotecas como o BLAS
+	/// Os `rows * cols` valores sao continuos, em ordem `MatrixLayout::RowMajor`
+	pub fn data_ptr(&self) -> *const f64 {
+		self.data.as_ptr()
+	}
+
+	/// Estimativa dos bytes usados pela matriz: a struct e um `f64` por posiçao
+	/// `size_of::<Self>() + rows * cols * size_of::<f64>()`
+	pub fn memory_estimate(&self) -> usize {
+		size_of::<Self>() + self.size.0 * self.size.1 * size_of::<f64>()
+	}
+
+	/// Constroi a matriz a partir de um vetor continuo na ordem `layout`, como os retornados pelo LAPACK
+	/// Retorna `MatrixError::DimensionMismatch` (com os tamanhos como vetores coluna) se `data` nao tiver `rows * cols` elementos
+	/// Complexidade de tempo: O(rows * cols)
+	pub fn from_lapack_layout(data: Vec<f64>, size: Pair, layout: MatrixLayout) -> Result<Self, MatrixError> {
+		if data.len() != size.0 * size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (size.0 * size.1, 1), got: (data.len(), 1) });
+		}
+		let m = match layout {
+			MatrixLayout::RowMajor => TableMatrix { size, data },
+			MatrixLayout::ColMajor => TableMatrix {
+				size,
+				data: (0..size.0)
+					.flat_map(|i| (0..size.1).map(move |j| (i, j)))
+					.map(|(i, j)| data[j * size.0 + i])
+					.collect(),
+			},
+		};
+		Ok(m)
+	}
+
+	/// Copia a matriz para um vetor continuo na ordem `layout`, para passar ao LAPACK
+	/// Complexidade de tempo: O(rows * cols)
+	pub fn to_lapack_layout(&self, layout: MatrixLayout) -> Vec<f64> {
+		match layout {
+			MatrixLayout::RowMajor => self.data.clone(),
+			MatrixLayout::ColMajor => (0..self.size.1)
+				.flat_map(|j| self.rows().map(move |row| row[j]))
+				.collect(),
+		}
+	}
+
+	/// Produto `a * b` pelo algoritmo de Strassen, com 7 multiplicaçoes de blocos em vez de 8
+	/// As matrizes sao completadas com zeros ate um quadrado de lado potencia de 2, e o resultado
+	/// é recortado de volta para `a.rows x b.cols`.
+	/// Entra em panico se o numero de colunas de `a` for diferente do numero de linhas de `b`.
+	/// Complexidade de tempo: O(n^log2(7)) ≈ O(n^2.81), onde n é a maior dimensao arredondada para potencia de 2
+	pub fn mul_strassen(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.1, b.size.0, "Incompatible matrices for multiplication");
+		let n = a.size.0.max(a.size.1).max(b.size.1).next_power_of_two();
+		let mut pa = TableMatrix::new((n, n));
+		let mut pb = TableMatrix::new((n, n));
+		pa.set_submatrix(0, 0, a);
+		pb.set_submatrix(0, 0, b);
+		strassen(&pa, &pb).submatrix(0..a.size.0, 0..b.size.1)
+	}
+}
+
+/// Lado a partir do qual `strassen` divide as matrizes; abaixo dele o produto comum é mais rapido
+const STRASSEN_THRESHOLD: usize = 64;
+
+/// Recursao de Strassen para matrizes quadradas de lado potencia de 2
+fn strassen(a: &TableMatrix, b: &TableMatrix) -> TableMatrix {
+	let n = a.size.0;
+	if n <= STRASSEN_THRESHOLD {
+		return TableMatrix::mul(a, b);
+	}
+	let h = n / 2;
+	let quarter = |m: &TableMatrix, i: usize, j: usize| m.submatrix(i * h..(i + 1) * h, j * h..(j + 1) * h);
+	let (a11, a12, a21, a22) = (quarter(a, 0, 0), quarter(a, 0, 1), quarter(a, 1, 0), quarter(a, 1, 1));
+	let (b11, b12, b21, b22) = (quarter(b, 0, 0), quarter(b, 0, 1), quarter(b, 1, 0), quarter(b, 1, 1));
+	let add = TableMatrix::add;
+	let sub = TableMatrix::sub;
+
+	let m1 = strassen(&add(&a11, &a22), &add(&b11, &b22));
+	let m2 = strassen(&add(&a21, &a22), &b11);
+	let m3 = strassen(&a11, &sub(&b12, &b22));
+	let m4 = strassen(&a22, &sub(&b21, &b11));
+	let m5 = strassen(&add(&a11, &a12), &b22);
+	let m6 = strassen(&sub(&a21, &a11), &add(&b11, &b12));
+	let m7 = strassen(&sub(&a12, &a22), &add(&b21, &b22));
+
+	let mut c = TableMatrix::new((n, n));
+	c.set_submatrix(0, 0, &add(&sub(&add(&m1, &m4), &m5), &m7));
+	c.set_submatrix(0, h, &add(&m3, &m5));
+	c.set_submatrix(h, 0, &add(&m2, &m4));
+	c.set_submatrix(h, h, &add(&add(&sub(&m1, &m2), &m3), &m6));
+	c
+}
+
+/// Soma em `res_row` a linha `i` de `a * b`, percorrendo `a[i][k]` e a linha `k` de `b` em ordem na memoria
+fn mul_row(a: &TableMatrix, b: &TableMatrix, i: usize, res_row: &mut [f64]) {
+	for (k, aik) in a.row(i).iter().enumerate() {
+		for (rij, bkj) in res_row.iter_mut().zip(b.row(k)) {
+			*rij += aik * bkj;
+		}
+	}
+}
+
+#[cfg(feature = "rayon")]
+impl TableMatrix {
+	/// Como `Matrix::mul`, mas calcula as linhas do resultado em paralelo, uma tarefa do rayon por linha
+	/// Entra em panico se o numero de colunas de `a` for diferente do numero de linhas de `b`.
+	pub fn mul_parallel(a: &Self, b: &Self) -> Self {
+		use rayon::prelude::*;
+		assert_eq!(a.size.1, b.size.0, "Incompatible matrices for multiplication");
+		let mut res = TableMatrix::new((a.size.0, b.size.1));
+		if b.size.1 > 0 {
+			res.data.par_chunks_mut(b.size.1)
+				.enumerate()
+				.for_each(|(i, res_row)| mul_row(a, b, i, res_row));
+		}
+		res
+	}
+
+	/// Como `Matrix::add`, mas soma os valores em paralelo
+	/// Entra em panico se as matrizes tiverem tamanhos diferentes.
+	pub fn add_parallel(a: &Self, b: &Self) -> Self {
+		use rayon::prelude::*;
+		assert_eq!(a.size, b.size, "Incompatible matrices for addition");
+		TableMatrix {
+			size: a.size,
+			data: a.data.par_iter().zip(b.data.par_iter()).map(|(x, y)| x + y).collect(),
+		}
+	}
+}
+
+/// Tamanho seguido da grade com todos os valores
+impl fmt::Debug for TableMatrix {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "TableMatrix {}x{}", self.size.0, self.size.1)?;
+		fmt_grid(f, self.size, |pos| self[pos])
+	}
+}
+
+/// Iguais se tem o mesmo tamanho e cada posiçao difere menos que `EPSILON`
+impl PartialEq for TableMatrix {
+	fn eq(&self, other: &Self) -> bool {
+		self.size == other.size
+			&& self.data.iter().zip(other.data.iter())
+				.all(|(a, b)| (a - b).abs() < EPSILON)
+	}
+}
+
+/// Junta as linhas em um unico vetor
+/// Entra em panico se as linhas nao tiverem todas o mesmo tamanho
+impl From<Vec<Vec<f64>>> for TableMatrix {
+	fn from(rows: Vec<Vec<f64>>) -> Self {
+		let cols = rows.first().map_or(0, Vec::len);
+		assert!(rows.iter().all(|row| row.len() == cols), "todas as linhas devem ter {cols} colunas");
+		TableMatrix { size: (rows.len(), cols), data: rows.concat() }
+	}
+}
+
+/// Copia as linhas para uma nova matriz
+/// Entra em panico se as linhas nao tiverem todas o mesmo tamanho
+impl From<&[&[f64]]> for TableMatrix {
+	fn from(rows: &[&[f64]]) -> Self {
+		TableMatrix::from(rows.iter().map(|row| row.to_vec()).collect::<Vec<_>>())
+	}
+}
+
+/// Separa os valores da matriz em um vetor por linha
+impl From<TableMatrix> for Vec<Vec<f64>> {
+	fn from(m: TableMatrix) -> Self {
+		m.rows().map(<[f64]>::to_vec).collect()
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&TableMatrix> for TableMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for TableMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+/// `&a + &b`, equivalente a `Matrix::add(&a, &b)`
+impl std::ops::Add<&TableMatrix> for &TableMatrix {
+	type Output = TableMatrix;
+	fn add(self, other: &TableMatrix) -> TableMatrix {
+		Matrix::add(self, other)
+	}
+}
+
+/// `a + &b`, reaproveitando `a` com `Matrix::add_assign`; permite encadear, como em `&a * &b + &c`
+impl std::ops::Add<&TableMatrix> for TableMatrix {
+	type Output = TableMatrix;
+	fn add(mut self, other: &TableMatrix) -> TableMatrix {
+		Matrix::add_assign(&mut self, other);
+		self
+	}
+}
+
+/// `&a - &b`, equivalente a `Matrix::sub(&a, &b)`
+impl std::ops::Sub<&TableMatrix> for &TableMatrix {
+	type Output = TableMatrix;
+	fn sub(self, other: &TableMatrix) -> TableMatrix {
+		Matrix::sub(self, other)
+	}
+}
+
+/// `&a * &b` é o produto de matrizes, equivalente a `Matrix::mul(&a, &b)`, e nao o produto elemento a elemento (ver `Matrix::hadamard`)
+impl std::ops::Mul<&TableMatrix> for &TableMatrix {
+	type Output = TableMatrix;
+	fn mul(self, other: &TableMatrix) -> TableMatrix {
+		Matrix::mul(self, other)
+	}
+}
+
+/// `&a * s`, equivalente a `Matrix::muls(&a, s)`
+impl std::ops::Mul<f64> for &TableMatrix {
+	type Output = TableMatrix;
+	fn mul(self, scalar: f64) -> TableMatrix {
+		Matrix::muls(self, scalar)
+	}
+}
+
+/// `-a`, multiplicando `a` por -1 no lugar com `Matrix::muls_assign`
+impl std::ops::Neg for TableMatrix {
+	type Output = TableMatrix;
+	fn neg(mut self) -> TableMatrix {
+		Matrix::muls_assign(&mut self, -1.0);
+		self
+	}
+}
+
+/// Acesso com `m[(i, j)]`, equivalente a `m.get((i, j))`
+impl Index<Pair> for TableMatrix {
+	type Output = f64;
+
+	fn index(&self, pos: Pair) -> &f64 {
+		&self.data[self.index_of(pos)]
+	}
+}
+
+/// Atribuiçao com `m[(i, j)] = v`, equivalente a `m.set((i, j), v)`
+impl IndexMut<Pair> for TableMatrix {
+	fn index_mut(&mut self, pos: Pair) -> &mut f64 {
+		let index = self.index_of(pos);
+		&mut self.data[index]
+	}
 }
 
 impl Matrix for TableMatrix {
 	fn new(size: Pair) -> Self {
 		TableMatrix {
 			size,
-			data: vec![vec![0.0; size.1]; size.0],
+			data: vec![0.0; size.0 * size.1],
 		}
 	}
+	fn size(&self) -> Pair {
+		self.size
+	}
 	fn from_info(info: &MatrixInfo) -> Self {
 		let mut m = TableMatrix::new(info.size);
 		for (pos, value) in info.values.iter() {
-			let (r, c) = *pos;
-			m.data[r][c] = *value;
+			m[*pos] = *value;
 		}
 		m
 	}
 
 	fn to_info(&self) -> MatrixInfo {
-		let mut values = Vec::new();
-		for i in 0..self.size.0 {
-			for j in 0..self.size.1 {
-				let v = self.data[i][j];
-				values.push(((i, j), v));
-			}
-		}
+		let cols = self.size.1;
 		MatrixInfo {
 			size: self.size,
-			values: values,
+			values: self.data.iter().enumerate().map(|(k, v)| ((k / cols, k % cols), *v)).collect(),
+		}
+	}
+
+	/// Percorre a tabela linha por linha, pulando os valores com modulo ate `EPSILON`
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		let cols = self.size.1;
+		Box::new(self.data.iter().enumerate()
+			.filter(|(_, value)| value.abs() > EPSILON)
+			.map(move |(k, value)| ((k / cols, k % cols), *value)))
+	}
+
+	/// Copia cada linha de `data`
+	fn iter_rows<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		Box::new(self.rows().map(|row| row.to_vec()))
+	}
+
+	/// Le cada coluna de `data` com passo `colunas`
+	fn iter_cols<'a>(&'a self) -> Box<dyn Iterator<Item = Vec<f64>> + 'a> {
+		let cols = self.size.1;
+		Box::new((0..cols).map(move |j| self.data.iter().skip(j).step_by(cols).copied().collect()))
+	}
+
+	/// Soma `alpha * u[i] * v` em cada linha `i`, direto na tabela
+	/// Complexidade de tempo: O(n * m)
+	fn rank_one_update(a: &mut Self, alpha: f64, u: &[f64], v: &[f64]) {
+		assert_eq!(a.size, (u.len(), v.len()), "Incompatible vectors for rank_one_update");
+		for (i, ui) in u.iter().enumerate() {
+			for (value, vj) in a.row_mut(i).iter_mut().zip(v) {
+				*value += alpha * ui * vj;
+			}
+		}
+	}
+
+	/// Laço denso sobre todas as posiçoes de `a` e `b`, escrevendo cada linha do resultado em ordem
+	/// Complexidade de tempo: O(m * n * p * q)
+	fn kron(a: &Self, b: &Self) -> Self {
+		let (p, q) = b.size;
+		let mut c = TableMatrix::new((a.size.0 * p, a.size.1 * q));
+		for (i, a_row) in a.rows().enumerate() {
+			for (r, b_row) in b.rows().enumerate() {
+				let c_row = c.row_mut(i * p + r);
+				for (c_block, aij) in c_row.chunks_exact_mut(q.max(1)).zip(a_row) {
+					for (cv, bv) in c_block.iter_mut().zip(b_row) {
+						*cv = aij * bv;
+					}
+				}
+			}
+		}
+		c
+	}
+
+	/// Produto escalar de cada linha com `v`
+	/// Complexidade de tempo: O(n * m)
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.1, v.len(), "Incompatible vector for mul_vec");
+		self.rows().map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum()).collect()
+	}
+
+	/// Soma as linhas de `A` pesadas por `v`
+	/// Complexidade de tempo: O(n * m)
+	fn vec_mul(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.size.0, v.len(), "Incompatible vector for vec_mul");
+		let mut result = vec![0.0; self.size.1];
+		for (row, vi) in self.rows().zip(v) {
+			for (r, value) in result.iter_mut().zip(row) {
+				*r += vi * value;
+			}
 		}
+		result
 	}
 
 	fn transposed(self) -> Self {
 		let mut t = TableMatrix::new((self.size.1, self.size.0));
-		for i in 0..self.size.0 {
-			for j in 0..self.size.1 {
-				t.data[j][i] = self.data[i][j];
+		for (i, row) in self.rows().enumerate() {
+			for (j, value) in row.iter().enumerate() {
+				t[(j, i)] = *value;
 			}
 		}
 		t
 	}
 	fn muls(a : &Self, scalar: f64) -> Self {
-		let n = a.size;
-		let mut res = TableMatrix::new(n);
-		for i in 0..n.0 {
-			for j in 0..n.1 {
-				res.data[i][j] = a.data[i][j] * scalar;
-			}
+		TableMatrix {
+			size: a.size,
+			data: a.data.iter().map(|value| value * scalar).collect(),
 		}
-		res
 	}
-	fn mul(a: &Self, b: &Self) -> Self {
-		assert_eq!(a.size, b.size);
-		let n = a.size;
-		let mut res = TableMatrix::new(n);
-		for i in 0..n.0 {
-			for k in 0..n.1 {
-				let aik = a.data[i][k];
-				for j in 0..n.1 {
-					res.data[i][j] += aik * b.data[k][j];
-				}
-			}
+	fn add_assign(a : &mut Self, b : &Self) {
+		assert_eq!(a.size, b.size, "Incompatible matrices for addition");
+		for (va, vb) in a.data.iter_mut().zip(b.data.iter()) {
+			*va += vb;
 		}
-		res
+	}
+	fn muls_assign(a : &mut Self, scalar: f64) {
+		for value in a.data.iter_mut() {
+			*value *= scalar;
+		}
+	}
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size, b.size)?;
+		let mut res = TableMatrix::new((a.size.0, b.size.1));
+		for i in 0..a.size.0 {
+			mul_row(a, b, i, res.row_mut(i));
+		}
+		Ok(res)
 	}
 	
 	
+	fn nnz(&self) -> usize {
+		self.data.iter().filter(|value| value.abs() > EPSILON).count()
+	}
+
+	/// Complexidade de tempo: O(min(linhas, colunas))
+	fn trace(&self) -> f64 {
+		(0..self.size.0.min(self.size.1)).map(|i| self[(i, i)]).sum()
+	}
+
+	/// Preenche `data` no lugar
+	fn fill(&mut self, value: f64) {
+		self.data.fill(value);
+	}
+
+	/// Zera as posiçoes de `data` no lugar
+	fn threshold_sparsify(&mut self, threshold: f64) -> usize {
+		let mut removed = 0;
+		for value in self.data.iter_mut() {
+			if *value != 0.0 && value.abs() <= threshold {
+				*value = 0.0;
+				removed += 1;
+			}
+		}
+		removed
+	}
+	
+	fn apply_mask(&self, mask: &Self) -> Result<Self, MatrixError> {
+		if self.size != mask.size {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: mask.size });
+		}
+		Ok(TableMatrix {
+			size: self.size,
+			data: self.data.iter().zip(mask.data.iter())
+				.map(|(value, m)| value * (*m != 0.0) as u8 as f64)
+				.collect(),
+		})
+	}
+
+	fn sum_sq(&self) -> f64 {
+		self.data.iter().map(|v| v * v).sum()
+	}
+
+	/// Soma todas as posiçoes de `data`, inclusive as nulas
+	fn sum(&self) -> f64 {
+		self.data.iter().sum()
+	}
+
+	/// Percorre todas as posiçoes de `data`, inclusive as nulas
+	fn max_element(&self) -> f64 {
+		self.data.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+	}
+
+	/// Percorre todas as posiçoes de `data`, inclusive as nulas
+	fn min_element(&self) -> f64 {
+		self.data.iter().copied().fold(f64::INFINITY, f64::min)
+	}
+
+	/// Procura em cada linha o primeiro valor com modulo maior que `EPSILON`, da esquerda ate a diagonal
+	fn lower_bandwidth(&self) -> usize {
+		self.rows().enumerate()
+			.filter_map(|(i, row)| row[..i.min(row.len())].iter().position(|v| v.abs() > EPSILON).map(|j| i - j))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Procura em cada linha o ultimo valor com modulo maior que `EPSILON`, da direita ate a diagonal
+	fn upper_bandwidth(&self) -> usize {
+		self.rows().enumerate()
+			.filter_map(|(i, row)| row.get(i + 1..).and_then(|tail| tail.iter().rposition(|v| v.abs() > EPSILON)).map(|k| k + 1))
+			.max()
+			.unwrap_or(0)
+	}
+
+	fn bandwidth(&self) -> usize {
+		self.lower_bandwidth().max(self.upper_bandwidth())
+	}
+
+	/// Verifica linha por linha se os valores fora da banda tem modulo ate `EPSILON`
+	fn is_banded(&self, lower_bw: usize, upper_bw: usize) -> bool {
+		self.rows().enumerate().all(|(i, row)| {
+			let start = i.saturating_sub(lower_bw).min(row.len());
+			let end = (i + upper_bw + 1).min(row.len());
+			row[..start].iter().chain(&row[end.max(start)..]).all(|v| v.abs() <= EPSILON)
+		})
+	}
+
+	fn norm_one(&self) -> f64 {
+		let mut sums = vec![0.0; self.size.1];
+		for row in self.rows() {
+			for (sum, v) in sums.iter_mut().zip(row.iter()) {
+				*sum += v.abs();
+			}
+		}
+		sums.into_iter().fold(0.0, f64::max)
+	}
+
+	fn norm_inf(&self) -> f64 {
+		self.rows()
+			.map(|row| row.iter().map(|v| v.abs()).sum::<f64>())
+			.fold(0.0, f64::max)
+	}
+
+	fn row_sums(&self) -> Vec<f64> {
+		self.rows().map(|row| row.iter().sum()).collect()
+	}
+
+	fn col_sums(&self) -> Vec<f64> {
+		let mut sums = vec![0.0; self.size.1];
+		for row in self.rows() {
+			for (sum, v) in sums.iter_mut().zip(row.iter()) {
+				*sum += v;
+			}
+		}
+		sums
+	}
+
+	fn row_max(&self) -> Vec<f64> {
+		self.rows().map(|row| row.iter().fold(0.0, |max, v| f64::max(max, v.abs()))).collect()
+	}
+
+	fn col_max(&self) -> Vec<f64> {
+		let mut maxs = vec![0.0; self.size.1];
+		for row in self.rows() {
+			for (max, v) in maxs.iter_mut().zip(row.iter()) {
+				*max = f64::max(*max, v.abs());
+			}
+		}
+		maxs
+	}
+
+	/// Copia o trecho `col_range` de cada linha em `row_range`
+	fn submatrix(&self, row_range: Range<usize>, col_range: Range<usize>) -> Self {
+		assert!(row_range.end <= self.size.0 && col_range.end <= self.size.1, "submatrix out of bounds");
+		TableMatrix {
+			size: (row_range.len(), col_range.len()),
+			data: row_range.flat_map(|i| self.row(i)[col_range.clone()].iter().copied()).collect(),
+		}
+	}
+
+	/// Copia cada linha de `sub` sobre o trecho correspondente das linhas da regiao
+	fn set_submatrix(&mut self, row_offset: usize, col_offset: usize, sub: &Self) {
+		assert!(row_offset + sub.size.0 <= self.size.0 && col_offset + sub.size.1 <= self.size.1, "submatrix out of bounds");
+		for (i, sub_row) in sub.rows().enumerate() {
+			self.row_mut(row_offset + i)[col_offset..col_offset + sub.size.1].copy_from_slice(sub_row);
+		}
+	}
+
+	/// Copia cada linha de `a` seguida da linha correspondente de `b`
+	fn hstack(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.0, b.size.0, "Incompatible matrices for hstack");
+		TableMatrix {
+			size: (a.size.0, a.size.1 + b.size.1),
+			data: a.rows().zip(b.rows()).flat_map(|(ra, rb)| ra.iter().chain(rb).copied()).collect(),
+		}
+	}
+
+	/// Copia os valores de `a` seguidos dos valores de `b`, ja que as linhas sao continuas
+	fn vstack(a: &Self, b: &Self) -> Self {
+		assert_eq!(a.size.1, b.size.1, "Incompatible matrices for vstack");
+		TableMatrix {
+			size: (a.size.0 + b.size.0, a.size.1),
+			data: [a.data.as_slice(), b.data.as_slice()].concat(),
+		}
+	}
+
+	/// Compacta as linhas mantidas no inicio de `data` com `copy_within` e descarta o resto com `truncate`, sem realocar;
+	/// conta apenas os valores nao nulos da regiao removida
+	fn shrink_size(&mut self, new_size: Pair) -> Result<usize, MatrixError> {
+		if new_size.0 > self.size.0 || new_size.1 > self.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: new_size });
+		}
+		let cols = self.size.1;
+		let (new_rows, new_cols) = new_size;
+		let mut removed = self.data[new_rows * cols..].iter().filter(|value| **value != 0.0).count();
+		for i in 0..new_rows {
+			let start = i * cols;
+			removed += self.data[start + new_cols..start + cols].iter().filter(|value| **value != 0.0).count();
+			self.data.copy_within(start..start + new_cols, i * new_cols);
+		}
+		self.data.truncate(new_rows * new_cols);
+		self.size = new_size;
+		Ok(removed)
+	}
+
+	/// Copia as linhas de `data` direto para a forma densa, sem passar por `to_info`
+	fn inverse(a: &Self) -> Result<Self, MatrixError> {
+		if a.size.0 != a.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: (a.size.0, a.size.0), got: a.size });
+		}
+		let dense: Vec<Vec<f64>> = a.rows().map(|row| row.to_vec()).collect();
+		let inverse = factorization::inverse_dense(&dense)?;
+		Ok(TableMatrix { size: a.size, data: inverse.concat() })
+	}
+
+	fn row_swap(&mut self, i: usize, j: usize) {
+		assert!(i < self.size.0 && j < self.size.0, "row out of bounds");
+		let cols = self.size.1;
+		for col in 0..cols {
+			self.data.swap(i * cols + col, j * cols + col);
+		}
+	}
+
+	fn col_swap(&mut self, i: usize, j: usize) {
+		assert!(i < self.size.1 && j < self.size.1, "column out of bounds");
+		for row in 0..self.size.0 {
+			self.row_mut(row).swap(i, j);
+		}
+	}
+
+	fn row_scale(&mut self, row: usize, scalar: f64) {
+		assert!(row < self.size.0, "row out of bounds");
+		for value in self.row_mut(row) {
+			*value *= scalar;
+		}
+	}
+
+	fn add_row_multiple(&mut self, target: usize, source: usize, scalar: f64) {
+		assert!(target < self.size.0 && source < self.size.0, "row out of bounds");
+		let cols = self.size.1;
+		for col in 0..cols {
+			let value = self.data[source * cols + col];
+			self.data[target * cols + col] += scalar * value;
+		}
+	}
+
+	/// `data` ja esta em ordem linha por linha, entao basta trocar o tamanho
+	/// Complexidade de tempo: O(1)
+	fn reshape(mut self, new_size: Pair) -> Result<Self, MatrixError> {
+		if new_size.0 * new_size.1 != self.size.0 * self.size.1 {
+			return Err(MatrixError::DimensionMismatch { expected: self.size, got: new_size });
+		}
+		self.size = new_size;
+		Ok(self)
+	}
+
+	fn weighted_sum(matrices: &[(&Self, f64)]) -> Result<Self, MatrixError> {
+		let Some(((first, _), rest)) = matrices.split_first() else {
+			return Ok(TableMatrix::new((0, 0)));
+		};
+		if let Some((m, _)) = rest.iter().find(|(m, _)| m.size != first.size) {
+			return Err(MatrixError::DimensionMismatch { expected: first.size, got: m.size });
+		}
+		let mut res = first.zero_like();
+		for (m, weight) in matrices {
+			for (r, v) in res.data.iter_mut().zip(m.data.iter()) {
+				*r += weight * v;
+			}
+		}
+		Ok(res)
+	}
+
 	fn set(&mut self, pos: Pair, value: f64) {
-		self.data[pos.0][pos.1] = value;
+		self[pos] = value;
 	}
 	
 	fn get(&self, pos: Pair) -> f64 {
-		self.data[pos.0][pos.1]
+		self[pos]
 	}
 	
-	fn add(a : &Self, b : &Self) -> Self {
+	fn sub(a : &Self, b : &Self) -> Self {
 		assert_eq!(a.size, b.size);
-		let n = a.size;
-		let mut res = TableMatrix::new(n);
-		for i in 0..n.0 {
-			for j in 0..n.1 {
-				res.data[i][j] = a.data[i][j] + b.data[i][j];
+		TableMatrix {
+			size: a.size,
+			data: a.data.iter().zip(b.data.iter()).map(|(x, y)| x - y).collect(),
+		}
+	}
+
+	fn neg(a : &Self) -> Self {
+		let mut res = a.clone();
+		for value in res.data.iter_mut() {
+			*value = -*value;
+		}
+		res
+	}
+
+	fn try_add(a : &Self, b : &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size, b.size)?;
+		Ok(TableMatrix {
+			size: a.size,
+			data: a.data.iter().zip(b.data.iter()).map(|(x, y)| x + y).collect(),
+		})
+	}
+
+	/// Aplica `f` a todas as posiçoes, inclusive as nulas, entao `f(0.0)` pode ser diferente de 0
+	fn map_elements(a : &Self, f: impl Fn(f64) -> f64) -> Self {
+		TableMatrix {
+			size: a.size,
+			data: a.data.iter().map(|value| f(*value)).collect(),
+		}
+	}
+
+	fn map_nnz(a : &Self, f: impl Fn(Pair, f64) -> f64) -> Self {
+		let mut res = a.clone();
+		let cols = a.size.1;
+		for (k, value) in res.data.iter_mut().enumerate() {
+			if *value != 0.0 {
+				*value = f((k / cols, k % cols), *value);
 			}
 		}
 		res
 	}
-}
\ No newline at end of file
+
+	fn hadamard(a : &Self, b : &Self) -> Self {
+		assert_eq!(a.size, b.size, "Incompatible matrices for hadamard product");
+		TableMatrix {
+			size: a.size,
+			data: a.data.iter().zip(b.data.iter()).map(|(x, y)| x * y).collect(),
+		}
+	}
+}
+
+/// Separa a matriz `lu` de `factorization::lu_dense` em `L`, com diagonal 1, e `U`
+fn split_lu(lu: &factorization::Dense) -> (TableMatrix, TableMatrix) {
+	let n = lu.len();
+	let (mut l, mut u) = (TableMatrix::new((n, n)), TableMatrix::new((n, n)));
+	for (i, row) in lu.iter().enumerate() {
+		l[(i, i)] = 1.0;
+		for (j, value) in row.iter().enumerate() {
+			if j < i {
+				l[(i, j)] = *value;
+			} else {
+				u[(i, j)] = *value;
+			}
+		}
+	}
+	(l, u)
+}
+
+/// Fatoraçao LU com pivoteamento parcial `P * A = L * U` de uma matriz quadrada
+///
+/// Retorna `(L, U, perm)`: `L` triangular inferior com diagonal 1, `U` triangular superior, e a linha `i` de `P * A`
+/// é a linha `perm[i]` de `A`. Em cada coluna o pivo é o valor de maior modulo ainda nao usado.
+/// Retorna `MatrixError::DimensionMismatch` se a matriz nao for quadrada e `MatrixError::SingularMatrix` se algum pivo
+/// tiver modulo menor que `EPSILON`.
+/// Complexidade de tempo: O(n^3)
+pub fn lu_decompose(a: &TableMatrix) -> Result<(TableMatrix, TableMatrix, Vec<usize>), MatrixError> {
+	if a.size.0 != a.size.1 {
+		return Err(MatrixError::DimensionMismatch { expected: (a.size.0, a.size.0), got: a.size });
+	}
+	let (lu, perm) = factorization::lu_dense(&factorization::to_dense(a))?;
+	let (l, u) = split_lu(&lu);
+	Ok((l, u, perm))
+}
+
+/// Resolve `A * x = b` a partir de `(L, U, perm)` de `lu_decompose`: `L * y = P * b` por substituiçao direta
+/// e depois `U * x = y` por substituiçao reversa
+/// Entra em panico se os tamanhos de `l`, `u`, `perm` e `b` forem diferentes.
+/// Complexidade de tempo: O(n^2)
+pub fn solve_lu(l: &TableMatrix, u: &TableMatrix, perm: &[usize], b: &[f64]) -> Vec<f64> {
+	let n = b.len();
+	assert!(l.size() == (n, n) && u.size() == (n, n) && perm.len() == n, "Incompatible vector for solve_lu");
+	let mut x: Vec<f64> = perm.iter().map(|i| b[*i]).collect();
+	for i in 0..n {
+		let sum: f64 = (0..i).map(|j| l[(i, j)] * x[j]).sum();
+		x[i] = (x[i] - sum) / l[(i, i)];
+	}
+	for i in (0..n).rev() {
+		let sum: f64 = (i + 1..n).map(|j| u[(i, j)] * x[j]).sum();
+		x[i] = (x[i] - sum) / u[(i, i)];
+	}
+	x
+}
+
+/// Resolve o sistema `A * x = b` com a fatoraçao LU com pivoteamento parcial
+/// Retorna `MatrixError::DimensionMismatch` se `A` nao for quadrada ou `b` nao tiver n elementos (com `b` como vetor coluna),
+/// e `MatrixError::SingularMatrix` se algum pivo tiver modulo menor que `EPSILON`.
+/// Complexidade de tempo: O(n^3)
+pub fn solve(a: &TableMatrix, b: &[f64]) -> Result<Vec<f64>, MatrixError> {
+	let size = a.size();
+	if b.len() != size.0 {
+		return Err(MatrixError::DimensionMismatch { expected: (size.0, 1), got: (b.len(), 1) });
+	}
+	let (l, u, perm) = lu_decompose(a)?;
+	Ok(solve_lu(&l, &u, &perm, b))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::*;
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn get_column_out_of_bounds_panics() {
+		let mut m = TableMatrix::new((3, 3));
+		m.set((1, 0), 7.0);
+		m.get((0, 3));
+	}
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn set_column_out_of_bounds_panics() {
+		let mut m = TableMatrix::new((3, 3));
+		m[(0, 3)] = 1.0;
+	}
+
+	#[test]
+	fn lu_decompose_reconstructs_permuted_matrix() {
+		let a = TableMatrix::from_info(&MatrixInfo {
+			size: (3, 3),
+			values: vec![((0, 0), 1.0), ((0, 1), 2.0), ((1, 0), 4.0), ((1, 2), 1.0), ((2, 1), 3.0), ((2, 2), 5.0)],
+		});
+		let (l, u, perm) = lu_decompose(&a).unwrap();
+		let lu = TableMatrix::mul(&l, &u);
+		for (i, p) in perm.iter().enumerate() {
+			for j in 0..3 {
+				assert!((lu.get((i, j)) - a.get((*p, j))).abs() < EPSILON);
+			}
+		}
+	}
+
+	#[test]
+	fn lu_decompose_reports_singular_and_non_square() {
+		let mut singular = TableMatrix::new((2, 2));
+		singular.set((0, 0), 1.0);
+		singular.set((1, 0), 2.0);
+		assert_eq!(lu_decompose(&singular).err(), Some(MatrixError::SingularMatrix));
+		assert_eq!(lu_decompose(&TableMatrix::new((2, 3))).err(), Some(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	#[test]
+	fn shrink_size_keeps_top_left_block_in_place() {
+		let mut m = TableMatrix::new((3, 4));
+		for i in 0..3 {
+			for j in 0..4 {
+				m.set((i, j), (i * 4 + j + 1) as f64);
+			}
+		}
+		m.set((0, 3), 0.0);
+		let capacity = m.data.capacity();
+		assert_eq!(m.shrink_size((2, 2)), Ok(7));
+		assert_eq!(m.size(), (2, 2));
+		assert_eq!(m.data, vec![1.0, 2.0, 5.0, 6.0]);
+		assert_eq!(m.data.capacity(), capacity);
+		assert_eq!(m.shrink_size((3, 1)), Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (3, 1) }));
+	}
+
+	#[test]
+	fn lapack_layouts_round_trip() {
+		let a = TableMatrix::from(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+		assert_eq!(a.to_lapack_layout(MatrixLayout::RowMajor), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+		assert_eq!(a.to_lapack_layout(MatrixLayout::ColMajor), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+		for layout in [MatrixLayout::RowMajor, MatrixLayout::ColMajor] {
+			let back = TableMatrix::from_lapack_layout(a.to_lapack_layout(layout), (2, 3), layout).unwrap();
+			assert_eq!(back, a);
+		}
+		let col_major = TableMatrix::from_lapack_layout(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], (3, 2), MatrixLayout::ColMajor).unwrap();
+		assert_eq!(col_major, TableMatrix::from(vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]));
+		assert_eq!(TableMatrix::from_lapack_layout(vec![1.0; 5], (2, 3), MatrixLayout::RowMajor), Err(MatrixError::DimensionMismatch { expected: (6, 1), got: (5, 1) }));
+	}
+
+	#[test]
+	fn index_syntax_matches_get_and_set() {
+		let mut m = TableMatrix::new((2, 3));
+		m[(0, 2)] = 5.0;
+		m.set((1, 0), -1.5);
+		m[(1, 0)] += 0.5;
+		assert_eq!(m.get((0, 2)), 5.0);
+		assert_eq!(m[(1, 0)], -1.0);
+		assert_eq!(m[(1, 2)], m.get((1, 2)));
+		assert_eq!(m.data, vec![0.0, 0.0, 5.0, -1.0, 0.0, 0.0]);
+	}
+
+	#[test]
+	#[should_panic(expected = "position out of bounds")]
+	fn index_out_of_bounds_panics() {
+		let m = TableMatrix::new((2, 3));
+		let _ = m[(0, 3)];
+	}
+
+	#[test]
+	fn debug_is_a_grid_after_the_size() {
+		let m = TableMatrix::from(vec![vec![1.0, -0.5], vec![20.0, 0.0]]);
+		assert_eq!(format!("{m:?}"), "TableMatrix 2x2\n 1 -0.5\n20    0\n");
+	}
+
+	#[test]
+	fn conversions_from_and_to_rows_round_trip() {
+		let rows = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+		let m = TableMatrix::from(rows.clone());
+		assert_eq!(m.size, (2, 3));
+		assert_eq!(m[(1, 0)], 4.0);
+		assert_eq!(Vec::<Vec<f64>>::from(m.clone()), rows);
+		assert_eq!(TableMatrix::from(Vec::<Vec<f64>>::from(m.clone())), m);
+		let slices: &[&[f64]] = &[&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]];
+		assert_eq!(TableMatrix::from(slices), m);
+		assert_eq!(TableMatrix::from(Vec::<Vec<f64>>::new()).size, (0, 0));
+	}
+
+	#[test]
+	#[should_panic(expected = "todas as linhas devem ter 2 colunas")]
+	fn from_ragged_rows_panics() {
+		let _ = TableMatrix::from(vec![vec![1.0, 2.0], vec![3.0]]);
+	}
+
+	#[test]
+	fn mul_strassen_matches_mul() {
+		for (m, k, n) in [(3, 2, 4), (64, 64, 64), (150, 130, 170), (256, 256, 256)] {
+			let a = random::<TableMatrix>((m, k), 1035);
+			let b = random::<TableMatrix>((k, n), 5301);
+			let tol = EPSILON * m.max(k).max(n) as f64;
+			assert_matrix_close(&TableMatrix::mul_strassen(&a, &b), &TableMatrix::mul(&a, &b), tol);
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "Incompatible matrices for multiplication")]
+	fn mul_strassen_panics_on_dimension_mismatch() {
+		TableMatrix::mul_strassen(&TableMatrix::new((2, 3)), &TableMatrix::new((2, 3)));
+	}
+
+	#[cfg(feature = "rayon")]
+	#[test]
+	fn parallel_mul_and_add_match_sequential() {
+		for (m, k, n) in [(1, 1, 1), (7, 3, 0), (40, 25, 33), (200, 200, 200)] {
+			let a = random::<TableMatrix>((m, k), 1036);
+			let b = random::<TableMatrix>((k, n), 6301);
+			let tol = EPSILON * m.max(k).max(n) as f64;
+			assert_matrix_close(&TableMatrix::mul_parallel(&a, &b), &TableMatrix::mul(&a, &b), tol);
+			let c = random::<TableMatrix>((m, k), 3610);
+			assert_eq!(TableMatrix::add_parallel(&a, &c).data, TableMatrix::add(&a, &c).data);
+		}
+	}
+
+	#[test]
+	fn inverse_rejects_singular_and_non_square() {
+		let mut singular = random::<TableMatrix>((4, 4), 8401);
+		for j in 0..4 {
+			let value = singular[(0, j)];
+			singular[(3, j)] = 2.0 * value;
+		}
+		assert_eq!(TableMatrix::inverse(&singular), Err(MatrixError::SingularMatrix));
+		assert_eq!(
+			TableMatrix::inverse(&TableMatrix::new((2, 3))),
+			Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) })
+		);
+	}
+
+	#[test]
+	fn operators_delegate_to_the_trait_methods() {
+		let a = TableMatrix::from(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+		let b = TableMatrix::from(vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+		let c = TableMatrix::from(vec![vec![1.0, 1.0], vec![1.0, 1.0]]);
+		assert_eq!(&a * &b + &c, TableMatrix::from(vec![vec![3.0, 2.0], vec![5.0, 4.0]]));
+		assert_eq!(&a + &c, TableMatrix::from(vec![vec![2.0, 3.0], vec![4.0, 5.0]]));
+		assert_eq!(&a - &c, TableMatrix::from(vec![vec![0.0, 1.0], vec![2.0, 3.0]]));
+		assert_eq!(&a * 2.0, TableMatrix::from(vec![vec![2.0, 4.0], vec![6.0, 8.0]]));
+		assert_eq!(-a.clone(), TableMatrix::from(vec![vec![-1.0, -2.0], vec![-3.0, -4.0]]));
+
+		let mut d = a.clone();
+		d += &c;
+		d *= 0.5;
+		assert_eq!(d, TableMatrix::from(vec![vec![1.0, 1.5], vec![2.0, 2.5]]));
+	}
+
+	#[test]
+	fn solve_rejects_a_right_hand_side_of_the_wrong_size() {
+		let a = TableMatrix::eye(3);
+		assert_eq!(solve(&a, &[1.0, 2.0]), Err(MatrixError::DimensionMismatch { expected: (3, 1), got: (2, 1) }));
+		assert_eq!(solve(&a, &[1.0; 4]), Err(MatrixError::DimensionMismatch { expected: (3, 1), got: (4, 1) }));
+		assert_eq!(solve(&TableMatrix::new((2, 3)), &[1.0; 2]), Err(MatrixError::DimensionMismatch { expected: (2, 2), got: (2, 3) }));
+	}
+
+	#[test]
+	#[should_panic(expected = "Incompatible vector for solve_lu")]
+	fn solve_lu_panics_on_a_right_hand_side_of_the_wrong_size() {
+		let (l, u, perm) = lu_decompose(&TableMatrix::eye(3)).unwrap();
+		solve_lu(&l, &u, &perm, &[1.0, 2.0]);
+	}
+}
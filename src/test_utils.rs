@@ -0,0 +1,159 @@
+//! Funçoes auxiliares compartilhadas pelos modulos de teste
+//!
+//! O macro `matrix_tests!` gera um `#[test]` por tipo de matriz (ou de mapa) a partir de uma funçao generica,
+//! de modo que cada propriedade é verificada em todas as implementaçoes sem repetir a lista de tipos.
+use crate::{Matrix, MatrixInfo, Pair, map_matrix::{Map, TransposableMap}, solvers::vec_ops::norm};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Gera um modulo `$name` com um `#[test]` por tipo, cada um chamando a funçao `$check`
+/// - `matrix_tests!(nome => check, ...)`: `check::<M>()` para todas as matrizes de uso geral
+/// - `matrix_tests!(sparse: nome => check, ...)`: `check::<M>()` apenas para as `MapMatrix`
+/// - `matrix_tests!(stores: nome => check, ...)`: `check(mapa)` com um mapa vazio de cada `Map<Pair, f64>`,
+///   incluindo um `TransposableMap` com a transposiçao pendente
+macro_rules! matrix_tests {
+	(sparse: $($name:ident => $check:ident),+ $(,)?) => {
+		$(crate::test_utils::matrix_tests!(@types $name $check [
+			hash_map_matrix: crate::HashMapMatrix,
+			tree_matrix: crate::TreeMatrix,
+			open_addr_matrix: crate::OpenAddrMatrix,
+			#[cfg(feature = "ahash")] ahash_map_matrix: crate::AHashMapMatrix,
+		]);)+
+	};
+	(stores: $($name:ident => $check:ident),+ $(,)?) => {
+		$(mod $name {
+			use super::*;
+			use crate::test_utils::{empty_store, transposed_store};
+
+			#[test]
+			fn hash_map_store() {
+				$check(empty_store::<crate::HashMapStore<crate::Pair, f64>>());
+			}
+
+			#[test]
+			fn tree_store() {
+				$check(empty_store::<crate::TreeStore<crate::Pair, f64>>());
+			}
+
+			#[test]
+			fn open_addr_store() {
+				$check(empty_store::<crate::OpenAddrHashStore>());
+			}
+
+			#[cfg(feature = "ahash")]
+			#[test]
+			fn ahash_map_store() {
+				$check(empty_store::<crate::AHashMapStore<crate::Pair, f64>>());
+			}
+
+			#[test]
+			fn transposed_hash_map_store() {
+				$check(transposed_store::<crate::HashMapStore<crate::Pair, f64>>());
+			}
+
+			#[test]
+			fn transposed_tree_store() {
+				$check(transposed_store::<crate::TreeStore<crate::Pair, f64>>());
+			}
+		})+
+	};
+	(@types $name:ident $check:ident [$($(#[$attr:meta])* $test:ident: $matrix:ty),+ $(,)?]) => {
+		mod $name {
+			use super::*;
+			$(
+				$(#[$attr])*
+				#[test]
+				fn $test() {
+					$check::<$matrix>();
+				}
+			)+
+		}
+	};
+	($($name:ident => $check:ident),+ $(,)?) => {
+		$(crate::test_utils::matrix_tests!(@types $name $check [
+			table_matrix: crate::TableMatrix,
+			hash_map_matrix: crate::HashMapMatrix,
+			tree_matrix: crate::TreeMatrix,
+			open_addr_matrix: crate::OpenAddrMatrix,
+			#[cfg(feature = "ahash")] ahash_map_matrix: crate::AHashMapMatrix,
+		]);)+
+	};
+}
+pub(crate) use matrix_tests;
+
+/// Mapa vazio do tipo `T`
+pub(crate) fn empty_store<T: Map<Pair, f64>>() -> T {
+	T::from_iter(std::iter::empty())
+}
+
+/// Mapa vazio do tipo `T` com a transposiçao pendente, para que cada acesso troque as chaves
+pub(crate) fn transposed_store<T: Map<Pair, f64>>() -> TransposableMap<T> {
+	let mut map = TransposableMap::new(empty_store());
+	map.transpose();
+	map
+}
+
+/// Verifica que as matrizes tem o mesmo tamanho e que os valores em cada posiçao diferem menos que `tol`
+pub(crate) fn assert_matrix_close(a: &impl Matrix, b: &impl Matrix, tol: f64) {
+	assert_eq!(a.size(), b.size());
+	for i in 0..a.size().0 {
+		for j in 0..a.size().1 {
+			assert!((a.get((i, j)) - b.get((i, j))).abs() < tol, "({i}, {j}): {} != {}", a.get((i, j)), b.get((i, j)));
+		}
+	}
+}
+
+/// Matriz densa `size` com valores uniformes em `[-1, 1)`, preenchida em ordem de linha
+pub(crate) fn random<M: Matrix>(size: Pair, seed: u64) -> M {
+	let mut rng = StdRng::seed_from_u64(seed);
+	let mut a = M::new(size);
+	for i in 0..size.0 {
+		for j in 0..size.1 {
+			a.set((i, j), rng.random_range(-1.0..1.0));
+		}
+	}
+	a
+}
+
+/// Matriz densa `n x n` com valores uniformes em `[-1, 1)` (ver `random`)
+pub(crate) fn random_square<M: Matrix>(n: usize, seed: u64) -> M {
+	random((n, n), seed)
+}
+
+/// Matriz `3 x 4` com os valores fora de ordem, um valor pequeno e a linha 1 vazia
+pub(crate) fn sample_info() -> MatrixInfo {
+	MatrixInfo {
+		size: (3, 4),
+		values: vec![((2, 3), -0.125), ((0, 1), 2.5), ((2, 0), 1e-3), ((0, 3), 7.0)],
+	}
+}
+
+/// A matriz de `sample_info` no tipo `M`
+pub(crate) fn sample<M: Matrix>() -> M {
+	M::from_info(&sample_info())
+}
+
+/// Matriz tridiagonal `n x n` com `diagonal` na diagonal principal e `off` nas duas vizinhas
+/// `tridiagonal(n, 2.0, -1.0)` é o laplaciano 1D
+pub(crate) fn tridiagonal<M: Matrix>(n: usize, diagonal: f64, off: f64) -> M {
+	let mut a = M::new((n, n));
+	for i in 0..n {
+		a.set((i, i), diagonal);
+		if i > 0 {
+			a.set((i, i - 1), off);
+			a.set((i - 1, i), off);
+		}
+	}
+	a
+}
+
+/// A `MatrixInfo` com os valores ordenados por posiçao, para comparaçoes que nao dependem da ordem de iteraçao
+pub(crate) fn sorted(mut info: MatrixInfo) -> MatrixInfo {
+	info.sort_by_position();
+	info
+}
+
+/// Norma do residuo, `||A x - b||`
+pub(crate) fn residual(a: &impl Matrix, x: &[f64], b: &[f64]) -> f64 {
+	let ax = a.mul_vec(x);
+	norm(&ax.iter().zip(b).map(|(p, q)| p - q).collect::<Vec<_>>())
+}
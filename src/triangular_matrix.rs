@@ -0,0 +1,392 @@
+use std::ops::{AddAssign, MulAssign};
+
+use crate::{basic::{Matrix, MatrixError, MatrixInfo, Pair, check_add_sizes, check_mul_sizes}, table_matrix::TableMatrix};
+
+/// Numero de posiçoes de um triangulo `n x n` incluindo a diagonal, `n * (n + 1) / 2`
+fn packed_len(n: usize) -> usize {
+	n * (n + 1) / 2
+}
+
+/// Matriz triangular inferior `n x n`, guardando apenas as posiçoes `(i, j)` com `j <= i`
+///
+/// As linhas do triangulo ficam em sequencia em `data` (armazenamento compactado): `(i, j)` fica no indice
+/// `i * (i + 1) / 2 + j`, e a matriz usa `n * (n + 1) / 2` valores, cerca de metade de uma `TableMatrix`.
+/// `get` acima da diagonal retorna 0 sem consultar `data`, e `set` e `from_info` entram em panico com valores
+/// nao nulos acima dela, assim como as operaçoes padrao do `Matrix` cujo resultado nao é triangular inferior.
+/// A matriz é sempre quadrada: `new` entra em panico com dimensoes diferentes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LowerTriMatrix {
+	n: usize,
+	data: Vec<f64>,
+}
+
+/// Matriz triangular superior `n x n`, guardando apenas as posiçoes `(i, j)` com `i <= j`
+///
+/// As colunas do triangulo ficam em sequencia em `data`: `(i, j)` fica no indice `j * (j + 1) / 2 + i`.
+/// É o mesmo vetor da `LowerTriMatrix` transposta, entao `transpose` e `LowerTriMatrix::transpose` nao copiam os valores.
+/// `get` abaixo da diagonal retorna 0 sem consultar `data`, e `set` e `from_info` entram em panico com valores
+/// nao nulos abaixo dela, assim como as operaçoes padrao do `Matrix` cujo resultado nao é triangular superior.
+/// A matriz é sempre quadrada: `new` entra em panico com dimensoes diferentes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpperTriMatrix {
+	n: usize,
+	data: Vec<f64>,
+}
+
+impl LowerTriMatrix {
+	fn index(i: usize, j: usize) -> usize {
+		i * (i + 1) / 2 + j
+	}
+
+	/// Transposta como `UpperTriMatrix`, reaproveitando `data`
+	/// Complexidade de tempo: O(1)
+	pub fn transpose(self) -> UpperTriMatrix {
+		UpperTriMatrix { n: self.n, data: self.data }
+	}
+
+	/// Produto `L * U` com uma matriz triangular superior, como o de uma fatoraçao LU, em uma `TableMatrix`
+	/// `C[i][j]` soma apenas `k <= min(i, j)`, onde os dois fatores podem ser nao nulos.
+	/// Entra em panico se as matrizes tiverem tamanhos diferentes.
+	/// Complexidade de tempo: O(n^3 / 3)
+	pub fn mul_upper(&self, u: &UpperTriMatrix) -> TableMatrix {
+		assert_eq!(self.n, u.n, "Incompatible matrices for multiplication");
+		let mut c = TableMatrix::new((self.n, self.n));
+		for i in 0..self.n {
+			for j in 0..self.n {
+				c[(i, j)] = (0..=i.min(j))
+					.map(|k| self.data[Self::index(i, k)] * u.data[UpperTriMatrix::index(k, j)])
+					.sum();
+			}
+		}
+		c
+	}
+
+	/// Estimativa dos bytes usados pela matriz: a struct e um `f64` por posiçao do triangulo
+	pub fn memory_estimate(&self) -> usize {
+		size_of::<Self>() + packed_len(self.n) * size_of::<f64>()
+	}
+}
+
+impl UpperTriMatrix {
+	fn index(i: usize, j: usize) -> usize {
+		j * (j + 1) / 2 + i
+	}
+
+	/// Transposta como `LowerTriMatrix`, reaproveitando `data`
+	/// Complexidade de tempo: O(1)
+	pub fn transpose(self) -> LowerTriMatrix {
+		LowerTriMatrix { n: self.n, data: self.data }
+	}
+
+	/// Estimativa dos bytes usados pela matriz: a struct e um `f64` por posiçao do triangulo
+	pub fn memory_estimate(&self) -> usize {
+		size_of::<Self>() + packed_len(self.n) * size_of::<f64>()
+	}
+}
+
+impl Matrix for LowerTriMatrix {
+	/// Entra em panico se a matriz nao for quadrada
+	fn new(size: Pair) -> Self {
+		assert_eq!(size.0, size.1, "LowerTriMatrix must be square");
+		LowerTriMatrix { n: size.0, data: vec![0.0; packed_len(size.0)] }
+	}
+
+	fn size(&self) -> Pair {
+		(self.n, self.n)
+	}
+
+	/// Entra em panico se `value` nao for 0 e a posiçao estiver acima da diagonal
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if pos.1 <= pos.0 {
+			self.data[Self::index(pos.0, pos.1)] = value;
+		} else {
+			assert!(value == 0.0, "LowerTriMatrix only stores values on or below the diagonal");
+		}
+	}
+
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if pos.1 <= pos.0 { self.data[Self::index(pos.0, pos.1)] } else { 0.0 }
+	}
+
+	/// A transposta de uma matriz triangular inferior é triangular superior: entra em panico se houver
+	/// algum valor nao nulo abaixo da diagonal. Para transpor em O(1) use `LowerTriMatrix::transpose`.
+	fn transposed(self) -> Self {
+		assert!(
+			self.iter_nnz().all(|((i, j), _)| i == j),
+			"transpose of a LowerTriMatrix is upper triangular, use LowerTriMatrix::transpose"
+		);
+		self
+	}
+
+	/// Complexidade de tempo: O(n^2 / 2)
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size(), b.size())?;
+		Ok(LowerTriMatrix { n: a.n, data: a.data.iter().zip(&b.data).map(|(x, y)| x + y).collect() })
+	}
+
+	/// O produto de triangulares inferiores é triangular inferior: `C[i][j]` soma apenas `j <= k <= i`
+	/// Complexidade de tempo: O(n^3 / 6)
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size(), b.size())?;
+		let mut c = LowerTriMatrix::new((a.n, a.n));
+		for i in 0..a.n {
+			for j in 0..=i {
+				c.data[Self::index(i, j)] = (j..=i).map(|k| a.data[Self::index(i, k)] * b.data[Self::index(k, j)]).sum();
+			}
+		}
+		Ok(c)
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		LowerTriMatrix { n: a.n, data: a.data.iter().map(|value| value * scalar).collect() }
+	}
+
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		for value in a.data.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Entra em panico se a matriz nao for quadrada ou houver algum valor nao nulo acima da diagonal
+	fn from_info(info: &MatrixInfo) -> Self {
+		let mut m = LowerTriMatrix::new(info.size);
+		for (pos, value) in info.values.iter() {
+			m.set(*pos, *value);
+		}
+		m
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size(),
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Percorre `data` em ordem, linha por linha, pulando os zeros
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new((0..self.n)
+			.flat_map(|i| (0..=i).map(move |j| (i, j)))
+			.zip(self.data.iter().copied())
+			.filter(|(_, value)| *value != 0.0))
+	}
+
+	fn diagonal(&self) -> Vec<f64> {
+		(0..self.n).map(|i| self.data[Self::index(i, i)]).collect()
+	}
+
+	/// Complexidade de tempo: O(n^2 / 2)
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.n, v.len(), "Incompatible vector for mul_vec");
+		(0..self.n)
+			.map(|i| self.data[Self::index(i, 0)..=Self::index(i, i)].iter().zip(v).map(|(x, y)| x * y).sum())
+			.collect()
+	}
+}
+
+impl Matrix for UpperTriMatrix {
+	/// Entra em panico se a matriz nao for quadrada
+	fn new(size: Pair) -> Self {
+		assert_eq!(size.0, size.1, "UpperTriMatrix must be square");
+		UpperTriMatrix { n: size.0, data: vec![0.0; packed_len(size.0)] }
+	}
+
+	fn size(&self) -> Pair {
+		(self.n, self.n)
+	}
+
+	/// Entra em panico se `value` nao for 0 e a posiçao estiver abaixo da diagonal
+	fn set(&mut self, pos: Pair, value: f64) {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if pos.0 <= pos.1 {
+			self.data[Self::index(pos.0, pos.1)] = value;
+		} else {
+			assert!(value == 0.0, "UpperTriMatrix only stores values on or above the diagonal");
+		}
+	}
+
+	fn get(&self, pos: Pair) -> f64 {
+		assert!(pos.0 < self.n && pos.1 < self.n, "position out of bounds");
+		if pos.0 <= pos.1 { self.data[Self::index(pos.0, pos.1)] } else { 0.0 }
+	}
+
+	/// A transposta de uma matriz triangular superior é triangular inferior: entra em panico se houver
+	/// algum valor nao nulo acima da diagonal. Para transpor em O(1) use `UpperTriMatrix::transpose`.
+	fn transposed(self) -> Self {
+		assert!(
+			self.iter_nnz().all(|((i, j), _)| i == j),
+			"transpose of an UpperTriMatrix is lower triangular, use UpperTriMatrix::transpose"
+		);
+		self
+	}
+
+	/// Complexidade de tempo: O(n^2 / 2)
+	fn try_add(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_add_sizes(a.size(), b.size())?;
+		Ok(UpperTriMatrix { n: a.n, data: a.data.iter().zip(&b.data).map(|(x, y)| x + y).collect() })
+	}
+
+	/// O produto de triangulares superiores é triangular superior: `C[i][j]` soma apenas `i <= k <= j`
+	/// Complexidade de tempo: O(n^3 / 6)
+	fn try_mul(a: &Self, b: &Self) -> Result<Self, MatrixError> {
+		check_mul_sizes(a.size(), b.size())?;
+		let mut c = UpperTriMatrix::new((a.n, a.n));
+		for j in 0..a.n {
+			for i in 0..=j {
+				c.data[Self::index(i, j)] = (i..=j).map(|k| a.data[Self::index(i, k)] * b.data[Self::index(k, j)]).sum();
+			}
+		}
+		Ok(c)
+	}
+
+	fn muls(a: &Self, scalar: f64) -> Self {
+		UpperTriMatrix { n: a.n, data: a.data.iter().map(|value| value * scalar).collect() }
+	}
+
+	fn muls_assign(a: &mut Self, scalar: f64) {
+		for value in a.data.iter_mut() {
+			*value *= scalar;
+		}
+	}
+
+	/// Entra em panico se a matriz nao for quadrada ou houver algum valor nao nulo abaixo da diagonal
+	fn from_info(info: &MatrixInfo) -> Self {
+		let mut m = UpperTriMatrix::new(info.size);
+		for (pos, value) in info.values.iter() {
+			m.set(*pos, *value);
+		}
+		m
+	}
+
+	fn to_info(&self) -> MatrixInfo {
+		MatrixInfo {
+			size: self.size(),
+			values: self.iter_nnz().collect(),
+		}
+	}
+
+	/// Percorre `data` em ordem, coluna por coluna, pulando os zeros
+	fn iter_nnz<'a>(&'a self) -> Box<dyn Iterator<Item = (Pair, f64)> + 'a> {
+		Box::new((0..self.n)
+			.flat_map(|j| (0..=j).map(move |i| (i, j)))
+			.zip(self.data.iter().copied())
+			.filter(|(_, value)| *value != 0.0))
+	}
+
+	fn diagonal(&self) -> Vec<f64> {
+		(0..self.n).map(|i| self.data[Self::index(i, i)]).collect()
+	}
+
+	/// Soma cada coluna compactada de `data`, multiplicada por `v[j]`, no resultado
+	/// Complexidade de tempo: O(n^2 / 2)
+	fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+		assert_eq!(self.n, v.len(), "Incompatible vector for mul_vec");
+		let mut result = vec![0.0; self.n];
+		for (j, vj) in v.iter().enumerate() {
+			let col = &self.data[Self::index(0, j)..=Self::index(j, j)];
+			for (r, value) in result.iter_mut().zip(col) {
+				*r += value * vj;
+			}
+		}
+		result
+	}
+}
+
+/// Resolve `L x = b` por substituiçao direta, percorrendo cada linha compactada de `L` uma vez
+/// Nao verifica a diagonal: um zero nela produz valores infinitos ou `NaN`.
+/// Entra em panico se `b` nao tiver n elementos.
+/// Complexidade de tempo: O(n^2 / 2)
+pub fn solve_lower(l: &LowerTriMatrix, b: &[f64]) -> Vec<f64> {
+	assert_eq!(l.n, b.len(), "Incompatible vector for solve_lower");
+	let mut x = vec![0.0; l.n];
+	for i in 0..l.n {
+		let row = &l.data[LowerTriMatrix::index(i, 0)..=LowerTriMatrix::index(i, i)];
+		let sum: f64 = row[..i].iter().zip(&x).map(|(value, xj)| value * xj).sum();
+		x[i] = (b[i] - sum) / row[i];
+	}
+	x
+}
+
+/// Resolve `U x = b` por substituiçao reversa, subtraindo cada coluna compactada de `U` do lado direito
+/// Nao verifica a diagonal: um zero nela produz valores infinitos ou `NaN`.
+/// Entra em panico se `b` nao tiver n elementos.
+/// Complexidade de tempo: O(n^2 / 2)
+pub fn solve_upper(u: &UpperTriMatrix, b: &[f64]) -> Vec<f64> {
+	assert_eq!(u.n, b.len(), "Incompatible vector for solve_upper");
+	let mut x = b.to_vec();
+	for j in (0..u.n).rev() {
+		let col = &u.data[UpperTriMatrix::index(0, j)..=UpperTriMatrix::index(j, j)];
+		x[j] /= col[j];
+		let xj = x[j];
+		for (xi, value) in x[..j].iter_mut().zip(col) {
+			*xi -= value * xj;
+		}
+	}
+	x
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&LowerTriMatrix> for LowerTriMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for LowerTriMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+/// `a += &b`, equivalente a `Matrix::add_assign(&mut a, &b)`
+impl AddAssign<&UpperTriMatrix> for UpperTriMatrix {
+	fn add_assign(&mut self, other: &Self) {
+		Matrix::add_assign(self, other);
+	}
+}
+
+/// `a *= s`, equivalente a `Matrix::muls_assign(&mut a, s)`
+impl MulAssign<f64> for UpperTriMatrix {
+	fn mul_assign(&mut self, scalar: f64) {
+		Matrix::muls_assign(self, scalar);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lower() -> LowerTriMatrix {
+		let mut l = LowerTriMatrix::new((3, 3));
+		for (pos, value) in [((0, 0), 2.0), ((1, 0), 1.0), ((1, 1), 3.0), ((2, 0), 4.0), ((2, 1), -1.0), ((2, 2), 5.0)] {
+			l.set(pos, value);
+		}
+		l
+	}
+
+	#[test]
+	fn info_roundtrip_keeps_triangle() {
+		let l = lower();
+		assert_eq!(LowerTriMatrix::from_info(&l.to_info()), l);
+		let u = l.transpose();
+		assert_eq!(UpperTriMatrix::from_info(&u.to_info()), u);
+		assert_eq!(u.get((0, 2)), 4.0);
+		assert_eq!(u.get((2, 0)), 0.0);
+	}
+
+	#[test]
+	fn solves_known_triangular_systems() {
+		let l = lower();
+		assert_eq!(solve_lower(&l, &[2.0, 7.0, 17.0]), vec![1.0, 2.0, 3.0]);
+		assert_eq!(solve_upper(&l.transpose(), &[16.0, 3.0, 15.0]), vec![1.0, 2.0, 3.0]);
+	}
+
+	#[test]
+	#[should_panic(expected = "transpose of a LowerTriMatrix is upper triangular")]
+	fn transposed_panics_outside_the_diagonal() {
+		lower().transposed();
+	}
+}
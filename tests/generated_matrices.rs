@@ -0,0 +1,81 @@
+//! Testes das matrizes geradas pelo `MatrixGenerator` (feature `testing`) em cada implementaçao de `Matrix`:
+//! as matrizes tem a estrutura pedida e geradores com a mesma semente geram as mesmas matrizes
+use projeto::{EPSILON, HashMapMatrix, Matrix, MatrixGenerator, OpenAddrMatrix, TableMatrix, TreeMatrix};
+
+const SEED: u64 = 1061;
+
+fn uniform_has_the_requested_population<M: Matrix>() {
+    let a: M = MatrixGenerator::with_seed(SEED).uniform((40, 25), 300);
+    assert_eq!(a.size(), (40, 25));
+    assert_eq!(a.nnz(), 300);
+    assert!(a.iter_nnz().all(|(_, value)| (-10.0..10.0).contains(&value)));
+    // a populaçao é limitada ao numero de posiçoes
+    let full: M = MatrixGenerator::with_seed(SEED).uniform((4, 5), 100);
+    assert_eq!(full.nnz(), 20);
+}
+
+fn symmetric_is_symmetric<M: Matrix>() {
+    let a: M = MatrixGenerator::with_seed(SEED).symmetric(30, 200);
+    assert!(a.is_symmetric(EPSILON));
+    assert!(a.iter_nnz().all(|((i, j), value)| a.get((j, i)) == value));
+    assert!(a.nnz() >= 200);
+}
+
+fn banded_has_the_requested_bandwidth<M: Matrix>() {
+    let a: M = MatrixGenerator::with_seed(SEED).banded(30, 3);
+    assert_eq!((a.lower_bandwidth(), a.upper_bandwidth()), (3, 3));
+    assert_eq!(a.nnz(), 30 + 2 * (29 + 28 + 27));
+}
+
+fn same_seed_gives_the_same_matrix<M: Matrix>() {
+    let (mut g, mut h) = (MatrixGenerator::with_seed(SEED), MatrixGenerator::with_seed(SEED));
+    let (a, b): (M, M) = (g.gaussian((20, 20), 150, 0.0, 1.0), h.gaussian((20, 20), 150, 0.0, 1.0));
+    for i in 0..20 {
+        for j in 0..20 {
+            assert_eq!(a.get((i, j)), b.get((i, j)));
+        }
+    }
+}
+
+macro_rules! generated_matrices {
+    ($name:ident, $matrix:ty) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn uniform() {
+                uniform_has_the_requested_population::<$matrix>();
+            }
+
+            #[test]
+            fn symmetric() {
+                symmetric_is_symmetric::<$matrix>();
+            }
+
+            #[test]
+            fn banded() {
+                banded_has_the_requested_bandwidth::<$matrix>();
+            }
+
+            #[test]
+            fn same_seed() {
+                same_seed_gives_the_same_matrix::<$matrix>();
+            }
+        }
+    };
+}
+
+generated_matrices!(table_matrix, TableMatrix);
+generated_matrices!(hash_map_matrix, HashMapMatrix);
+generated_matrices!(tree_matrix, TreeMatrix);
+generated_matrices!(open_addr_matrix, OpenAddrMatrix);
+
+#[test]
+fn uniform_table_matches_uniform_info() {
+    let table = MatrixGenerator::with_seed(SEED).uniform_table((15, 10), 60);
+    let info = MatrixGenerator::with_seed(SEED).uniform_info((15, 10), 60);
+    for ((i, j), value) in info.values {
+        assert_eq!(table.get((i, j)), value);
+    }
+    assert_eq!(table.iter_nnz().filter(|(_, value)| *value != 0.0).count(), 60);
+}
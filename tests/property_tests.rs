@@ -0,0 +1,143 @@
+//! Testes de propriedades: identidades algebricas verificadas em matrizes aleatorias geradas pelo `proptest`
+//! (ver `matrix_info`), para `HashMapMatrix`, `TreeMatrix`, `OpenAddrMatrix`, `TableMatrix`
+//! e, com a feature `ahash`, `AHashMapMatrix`
+use projeto::{approx_eq, lu_decompose, solve, solve_lu, EPSILON, HashMapMatrix, Matrix, MatrixInfo, OpenAddrMatrix, TableMatrix, TreeMatrix};
+#[cfg(feature = "ahash")]
+use projeto::AHashMapMatrix;
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseResult;
+
+/// Matrizes aleatorias ate 10x10, com valores em `[-100, 100]`
+/// Metade dos casos é esparsa (cada posiçao tem valor com probabilidade 0,1) e metade é densa (todas as posiçoes tem valor)
+fn matrix_info() -> BoxedStrategy<MatrixInfo> {
+    (1..=10usize, 1..=10usize, any::<bool>())
+        .prop_flat_map(|(rows, cols, dense)| {
+            let cells = if dense {
+                prop::collection::vec((-100.0..=100.0).prop_map(Some), rows * cols).boxed()
+            } else {
+                prop::collection::vec(prop::option::weighted(0.1, -100.0..=100.0), rows * cols).boxed()
+            };
+            (Just((rows, cols)), cells)
+        })
+        .prop_map(|(size, cells)| MatrixInfo {
+            size,
+            values: cells.into_iter()
+                .enumerate()
+                .filter_map(|(k, value)| value.map(|value| ((k / size.1, k % size.1), value)))
+                .collect(),
+        })
+        .boxed()
+}
+
+/// Duas matrizes do mesmo tamanho: os valores de `b` fora do tamanho de `a` sao descartados
+fn same_size_pair() -> impl Strategy<Value = (MatrixInfo, MatrixInfo)> {
+    (matrix_info(), matrix_info()).prop_map(|(a, mut b)| {
+        b.values.retain(|((i, j), _)| *i < a.size.0 && *j < a.size.1);
+        b.size = a.size;
+        (a, b)
+    })
+}
+
+fn add_is_commutative<M: Matrix>(a: &MatrixInfo, b: &MatrixInfo) -> TestCaseResult {
+    let (a, b) = (M::from_info(a), M::from_info(b));
+    prop_assert!(approx_eq(&M::add(&a, &b), &M::add(&b, &a), EPSILON));
+    Ok(())
+}
+
+fn add_zero_is_identity<M: Matrix>(a: &MatrixInfo) -> TestCaseResult {
+    let a = M::from_info(a);
+    prop_assert!(approx_eq(&M::add(&a, &M::new(a.size())), &a, EPSILON));
+    Ok(())
+}
+
+fn mul_eye_is_identity<M: Matrix>(a: &MatrixInfo) -> TestCaseResult {
+    let a = M::from_info(a);
+    prop_assert!(approx_eq(&M::mul(&a, &M::eye(a.size().1)), &a, EPSILON));
+    Ok(())
+}
+
+fn muls_two_is_add_self<M: Matrix>(a: &MatrixInfo) -> TestCaseResult {
+    let a = M::from_info(a);
+    prop_assert!(approx_eq(&M::muls(&a, 2.0), &M::add(&a, &a), EPSILON));
+    Ok(())
+}
+
+fn double_transpose_is_identity<M: Matrix>(info: &MatrixInfo) -> TestCaseResult {
+    let a = M::from_info(info);
+    prop_assert!(approx_eq(&M::from_info(info).transposed().transposed(), &a, EPSILON));
+    Ok(())
+}
+
+/// Gera um modulo com os testes de todas as propriedades para o tipo de matriz `$matrix`
+macro_rules! matrix_properties {
+    ($name:ident, $matrix:ty) => {
+        mod $name {
+            use super::*;
+
+            proptest! {
+                #[test]
+                fn add_commutative((a, b) in same_size_pair()) {
+                    add_is_commutative::<$matrix>(&a, &b)?;
+                }
+
+                #[test]
+                fn add_zero(a in matrix_info()) {
+                    add_zero_is_identity::<$matrix>(&a)?;
+                }
+
+                #[test]
+                fn mul_eye(a in matrix_info()) {
+                    mul_eye_is_identity::<$matrix>(&a)?;
+                }
+
+                #[test]
+                fn muls_two(a in matrix_info()) {
+                    muls_two_is_add_self::<$matrix>(&a)?;
+                }
+
+                #[test]
+                fn double_transpose(a in matrix_info()) {
+                    double_transpose_is_identity::<$matrix>(&a)?;
+                }
+            }
+        }
+    };
+}
+
+matrix_properties!(hash_map_matrix, HashMapMatrix);
+matrix_properties!(tree_matrix, TreeMatrix);
+matrix_properties!(open_addr_matrix, OpenAddrMatrix);
+#[cfg(feature = "ahash")]
+matrix_properties!(ahash_map_matrix, AHashMapMatrix);
+matrix_properties!(table_matrix, TableMatrix);
+
+/// Sistema `A x = b` de tamanho 10 a 100, com `A` aleatoria em `[-1, 1]` somada a `n * I` para ficar bem condicionada
+fn linear_system() -> impl Strategy<Value = (TableMatrix, Vec<f64>)> {
+    (10usize..=100).prop_flat_map(|n| {
+        (prop::collection::vec(-1.0..1.0f64, n * n), prop::collection::vec(-10.0..10.0f64, n)).prop_map(move |(values, b)| {
+            let mut a = TableMatrix::from(values.chunks(n).map(|row| row.to_vec()).collect::<Vec<_>>());
+            for i in 0..n {
+                a.set((i, i), a.get((i, i)) + n as f64);
+            }
+            (a, b)
+        })
+    })
+}
+
+fn residual(a: &TableMatrix, x: &[f64], b: &[f64]) -> f64 {
+    a.mul_vec(x).iter().zip(b).map(|(p, q)| (p - q).powi(2)).sum::<f64>().sqrt()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn solve_and_solve_lu_have_small_residuals((a, b) in linear_system()) {
+        let tol = 1e-10 * b.len() as f64;
+        let x = solve(&a, &b).unwrap();
+        prop_assert!(residual(&a, &x, &b) < tol, "solve: {}", residual(&a, &x, &b));
+        let (l, u, perm) = lu_decompose(&a).unwrap();
+        let x = solve_lu(&l, &u, &perm, &b);
+        prop_assert!(residual(&a, &x, &b) < tol, "solve_lu: {}", residual(&a, &x, &b));
+    }
+}